@@ -0,0 +1,642 @@
+//! Cargo manifests and commands
+//!
+//! The CLI runs `cargo` to create crates and to add dependencies, since `cargo` knows how to find
+//! the latest version of a crate in the registry. Manifests are edited with `toml_edit` afterwards,
+//! e.g. to move the dependencies of a new workspace member into `[workspace.dependencies]`.
+
+use std::fs::{read, read_to_string, remove_file, write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, anyhow, bail};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, Value, value};
+
+use crate::input::ClawlessDependency;
+
+/// The kinds of dependencies in a manifest
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// The keys of a dependency that a member can set when it inherits the dependency from its
+/// workspace, while all other keys move to `[workspace.dependencies]`
+const MEMBER_KEYS: [&str; 3] = ["features", "optional", "public"];
+
+/// Add Clawless as a dependency of a crate with `cargo add`
+pub fn add_clawless_dependency(crate_path: &Path, dependency: &ClawlessDependency) -> Result<()> {
+    let mut cargo_add_exec = Command::new("cargo");
+
+    // Add the arguments to add a new dependency
+    cargo_add_exec.current_dir(crate_path).arg("add");
+
+    match dependency.clawless_version() {
+        Some(version) => cargo_add_exec.arg(format!("clawless@{version}")),
+        None => cargo_add_exec.arg("clawless"),
+    };
+
+    if let Some(path) = dependency.clawless_path() {
+        cargo_add_exec.arg("--path").arg(path);
+    }
+
+    if dependency.offline() {
+        cargo_add_exec.arg("--offline");
+    }
+
+    run_cargo(&mut cargo_add_exec, "cargo add")
+        .context("failed to add clawless as a dependency with `cargo add`")?;
+
+    Ok(())
+}
+
+/// Set the author and the description in the `[package]` table of a crate
+///
+/// `cargo new` writes neither of them, so templates without a manifest rely on this to apply
+/// `--author` and `--description`. Values that are `None` are left unchanged.
+pub fn describe_package(
+    crate_path: &Path,
+    author: Option<&str>,
+    description: Option<&str>,
+) -> Result<()> {
+    let path = crate_path.join("Cargo.toml");
+    let mut manifest = read_manifest(&path)?;
+
+    let package = manifest
+        .get_mut("package")
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| anyhow!("{} has no [package] table", path.display()))?;
+
+    if let Some(description) = description {
+        package.insert("description", value(description));
+    }
+    if let Some(author) = author {
+        package.insert("authors", value(Array::from_iter([author])));
+    }
+
+    write(&path, manifest.to_string()).context(format!("failed to write {}", path.display()))
+}
+
+/// Run a `cargo` command, and return its output as the error if it fails
+///
+/// The output is only shown on failure, since `cargo` reports its progress on stderr.
+pub fn run_cargo(command: &mut Command, name: &str) -> Result<()> {
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .context(format!("failed to run `{name}`"))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+
+    if message.is_empty() {
+        bail!("`{name}` exited with {}", output.status);
+    }
+
+    bail!("{message}");
+}
+
+/// Find the root of the crate that contains a directory
+///
+/// Like `cargo`, this returns the closest directory with a `Cargo.toml` that has a `[package]`
+/// table, starting with the directory itself.
+pub fn find_package(directory: &Path) -> Result<PathBuf> {
+    for directory in directory.ancestors() {
+        let path = directory.join("Cargo.toml");
+        if path.is_file() && read_manifest(&path)?.contains_key("package") {
+            return Ok(directory.to_path_buf());
+        }
+    }
+
+    Err(anyhow!(
+        "failed to find a Cargo.toml with a `[package]` in the current directory or any of its parent directories"
+    ))
+}
+
+/// Find the root of the Cargo workspace that contains a directory
+///
+/// Like `cargo`, this returns the closest directory with a `Cargo.toml` that has a `[workspace]`
+/// table, starting with the directory itself.
+pub fn find_workspace(directory: &Path) -> Result<Option<PathBuf>> {
+    for directory in directory.ancestors() {
+        let path = directory.join("Cargo.toml");
+        if !path.is_file() {
+            continue;
+        }
+
+        if read_manifest(&path)?.contains_key("workspace") {
+            return Ok(Some(directory.to_path_buf()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Declare the dependencies of a workspace member in `[workspace.dependencies]`
+///
+/// Every dependency of the member that matches the filter is replaced with
+/// `{ workspace = true }`, and its version or path is moved to the manifest of the workspace. The
+/// member keeps its features. If the workspace already declares a dependency, the member uses the
+/// workspace's declaration. Returns the names of the dependencies that were changed.
+pub fn inherit_workspace_dependencies(
+    workspace: &Path,
+    crate_path: &Path,
+    filter: impl Fn(&str) -> bool,
+) -> Result<Vec<String>> {
+    // The root package of a workspace declares its dependencies directly
+    if normalize(workspace) == normalize(crate_path) {
+        return Ok(Vec::new());
+    }
+
+    let workspace_manifest_path = workspace.join("Cargo.toml");
+    let crate_manifest_path = crate_path.join("Cargo.toml");
+
+    let mut workspace_manifest = read_manifest(&workspace_manifest_path)?;
+    let mut crate_manifest = read_manifest(&crate_manifest_path)?;
+
+    let workspace_dependencies = workspace_manifest
+        .get_mut("workspace")
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| {
+            anyhow!(
+                "`workspace` in {} is not a table",
+                workspace_manifest_path.display()
+            )
+        })?
+        .entry("dependencies")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_like_mut()
+        .ok_or_else(|| {
+            anyhow!(
+                "`workspace.dependencies` in {} is not a table",
+                workspace_manifest_path.display()
+            )
+        })?;
+
+    let mut inherited = Vec::new();
+
+    for kind in DEPENDENCY_TABLES {
+        let Some(dependencies) = crate_manifest
+            .get_mut(kind)
+            .and_then(Item::as_table_like_mut)
+        else {
+            continue;
+        };
+
+        let names: Vec<String> = dependencies
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .filter(|name| filter(name))
+            .collect();
+
+        for name in names {
+            let Some((declaration, member)) = dependencies.get(&name).and_then(split_dependency)
+            else {
+                continue;
+            };
+
+            if !workspace_dependencies.contains_key(&name) {
+                let declaration = relocate_path(declaration, crate_path, workspace);
+
+                // Keep the short form `name = "version"` for dependencies without other keys
+                match declaration.get("version") {
+                    Some(version) if declaration.len() == 1 => {
+                        workspace_dependencies.insert(&name, value(version.clone()))
+                    }
+                    _ => workspace_dependencies.insert(&name, value(declaration)),
+                };
+            }
+
+            dependencies.insert(&name, value(member));
+            if !inherited.contains(&name) {
+                inherited.push(name);
+            }
+        }
+    }
+
+    if inherited.is_empty() {
+        return Ok(inherited);
+    }
+
+    write(&workspace_manifest_path, workspace_manifest.to_string()).context(format!(
+        "failed to write {}",
+        workspace_manifest_path.display()
+    ))?;
+    write(&crate_manifest_path, crate_manifest.to_string())
+        .context(format!("failed to write {}", crate_manifest_path.display()))?;
+
+    Ok(inherited)
+}
+
+/// A copy of the manifest and lockfile of a workspace, which can be restored if a command fails
+///
+/// `cargo new` and `cargo add` change the files of the workspace, e.g. to add a member.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ManifestBackup {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl ManifestBackup {
+    /// Copy the manifest and lockfile of a workspace
+    pub fn new(workspace: &Path) -> Result<Self> {
+        let files = ["Cargo.toml", "Cargo.lock"]
+            .into_iter()
+            .map(|name| {
+                let path = workspace.join(name);
+                let content = if path.exists() {
+                    Some(read(&path).context(format!("failed to read {}", path.display()))?)
+                } else {
+                    None
+                };
+
+                Ok((path, content))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { files })
+    }
+
+    /// Restore the files, and remove the ones that did not exist when the backup was made
+    pub fn restore(&self) -> Result<()> {
+        for (path, content) in &self.files {
+            match content {
+                Some(content) => {
+                    write(path, content).context(format!("failed to restore {}", path.display()))?
+                }
+                None if path.exists() => {
+                    remove_file(path).context(format!("failed to remove {}", path.display()))?
+                }
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<DocumentMut> {
+    read_to_string(path)
+        .context(format!("failed to read {}", path.display()))?
+        .parse()
+        .context(format!("failed to parse {}", path.display()))
+}
+
+/// Split a dependency into its declaration for the workspace and the entry of the member
+///
+/// Returns `None` for dependencies that already inherit from the workspace.
+fn split_dependency(item: &Item) -> Option<(InlineTable, InlineTable)> {
+    let mut declaration = InlineTable::new();
+    let mut member = InlineTable::new();
+    member.insert("workspace", true.into());
+
+    if let Some(version) = item.as_str() {
+        declaration.insert("version", version.into());
+        return Some((declaration, member));
+    }
+
+    let table = item.as_table_like()?;
+    if table.contains_key("workspace") {
+        return None;
+    }
+
+    for (key, item) in table.iter() {
+        let Some(value) = item.as_value() else {
+            continue;
+        };
+
+        let mut value = value.clone();
+        value.decor_mut().clear();
+
+        if MEMBER_KEYS.contains(&key) {
+            member.insert(key, value);
+        } else {
+            declaration.insert(key, value);
+        }
+    }
+
+    Some((declaration, member))
+}
+
+/// Make the path of a dependency relative to the workspace instead of the member
+fn relocate_path(mut declaration: InlineTable, crate_path: &Path, workspace: &Path) -> InlineTable {
+    if let Some(path) = declaration.get("path").and_then(Value::as_str) {
+        let path = relative_path(workspace, &crate_path.join(path));
+        declaration.insert("path", path.to_string_lossy().as_ref().into());
+    }
+
+    declaration
+}
+
+/// Returns the path of `to` relative to the directory `from`
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = normalize(from);
+    let to = normalize(to);
+
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut path = PathBuf::new();
+    for _ in from.components().skip(common) {
+        path.push("..");
+    }
+    for component in to.components().skip(common) {
+        path.push(component);
+    }
+
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+
+    path
+}
+
+/// Remove `.` and `..` from a path without accessing the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir_all;
+
+    use indoc::indoc;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn binary_crate(directory: &Path, name: &str) -> PathBuf {
+        run_cargo(
+            Command::new("cargo")
+                .current_dir(directory)
+                .args(["new", "--bin", "--offline", name]),
+            "cargo new",
+        )
+        .unwrap();
+
+        directory.join(name)
+    }
+
+    fn workspace(manifest: &str) -> TempDir {
+        let workspace = TempDir::new().unwrap();
+        write(workspace.path().join("Cargo.toml"), manifest).unwrap();
+
+        workspace
+    }
+
+    fn member(workspace: &TempDir, manifest: &str) -> PathBuf {
+        let crate_path = workspace.path().join("crates").join("app");
+        create_dir_all(&crate_path).unwrap();
+        write(crate_path.join("Cargo.toml"), manifest).unwrap();
+
+        crate_path
+    }
+
+    #[test]
+    fn add_clawless_dependency_adds_dependency() {
+        let cwd = TempDir::new().unwrap();
+        let crate_path = binary_crate(cwd.path(), "my_crate");
+
+        add_clawless_dependency(&crate_path, &ClawlessDependency::default()).unwrap();
+
+        let cargo_toml_contents = read_to_string(crate_path.join("Cargo.toml")).unwrap();
+
+        assert!(cargo_toml_contents.contains("clawless"));
+    }
+
+    #[test]
+    fn describe_package_sets_author_and_description() {
+        let workspace = workspace("");
+        let crate_path = member(
+            &workspace,
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        );
+
+        describe_package(&crate_path, Some("Jane Doe"), Some("An app")).unwrap();
+
+        assert_eq!(
+            indoc! {r#"
+                [package]
+                name = "app"
+                version = "0.1.0"
+                description = "An app"
+                authors = ["Jane Doe"]
+            "#},
+            read_to_string(crate_path.join("Cargo.toml")).unwrap()
+        );
+    }
+
+    #[test]
+    fn describe_package_keeps_manifest_without_values() {
+        let workspace = workspace("");
+        let crate_path = member(&workspace, "[package]\nname = \"app\"\n");
+
+        describe_package(&crate_path, None, None).unwrap();
+
+        assert_eq!(
+            "[package]\nname = \"app\"\n",
+            read_to_string(crate_path.join("Cargo.toml")).unwrap()
+        );
+    }
+
+    #[test]
+    fn add_clawless_dependency_adds_local_checkout() {
+        let cwd = TempDir::new().unwrap();
+        let crate_path = binary_crate(cwd.path(), "my_crate");
+        let clawless_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("clawless");
+
+        let dependency = ClawlessDependency::builder()
+            .offline(true)
+            .clawless_path(clawless_path)
+            .build();
+        add_clawless_dependency(&crate_path, &dependency).unwrap();
+
+        let cargo_toml_contents = read_to_string(crate_path.join("Cargo.toml")).unwrap();
+
+        assert!(cargo_toml_contents.contains("path = "));
+    }
+
+    #[test]
+    fn add_clawless_dependency_returns_cargo_output() {
+        let cwd = TempDir::new().unwrap();
+        let crate_path = binary_crate(cwd.path(), "my_crate");
+
+        let dependency = ClawlessDependency::builder()
+            .offline(true)
+            .clawless_version("9999.0.0")
+            .build();
+        let error = add_clawless_dependency(&crate_path, &dependency).unwrap_err();
+
+        let message = format!("{error:#}");
+        assert!(message.starts_with("failed to add clawless as a dependency with `cargo add`: "));
+        assert!(message.contains("clawless@9999.0.0"));
+    }
+
+    #[test]
+    fn find_package_skips_virtual_manifests() {
+        let workspace = workspace("[workspace]\nmembers = []\n");
+        let crate_path = member(&workspace, "[package]\nname = \"app\"\n");
+        create_dir_all(crate_path.join("src")).unwrap();
+
+        assert_eq!(crate_path, find_package(&crate_path.join("src")).unwrap());
+        assert!(find_package(workspace.path()).is_err());
+    }
+
+    #[test]
+    fn find_workspace_returns_closest_workspace() {
+        let workspace = workspace("[workspace]\nmembers = []\n");
+        let crate_path = member(&workspace, "[package]\nname = \"app\"\n");
+
+        assert_eq!(
+            Some(workspace.path().to_path_buf()),
+            find_workspace(&crate_path).unwrap()
+        );
+    }
+
+    #[test]
+    fn find_workspace_returns_none_outside_of_workspace() {
+        let directory = TempDir::new().unwrap();
+        write(
+            directory.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(None, find_workspace(directory.path()).unwrap());
+    }
+
+    #[test]
+    fn inherit_workspace_dependencies_moves_dependencies_to_workspace() {
+        let workspace = workspace(indoc! {r#"
+            [workspace]
+            members = ["crates/app"]
+
+            [workspace.dependencies]
+            serde = "1.0.200"
+        "#});
+        let crate_path = member(
+            &workspace,
+            indoc! {r#"
+                [package]
+                name = "app"
+
+                [dependencies]
+                clawless = { version = "0.4.0", path = "../../../clawless" }
+                serde = { version = "1", features = ["derive"] }
+                tokio = { workspace = true }
+
+                [dev-dependencies]
+                tempfile = "3"
+            "#},
+        );
+
+        let inherited =
+            inherit_workspace_dependencies(workspace.path(), &crate_path, |_| true).unwrap();
+
+        assert_eq!(vec!["clawless", "serde", "tempfile"], inherited);
+        assert_eq!(
+            indoc! {r#"
+                [workspace]
+                members = ["crates/app"]
+
+                [workspace.dependencies]
+                serde = "1.0.200"
+                clawless = { version = "0.4.0", path = "../clawless" }
+                tempfile = "3"
+            "#},
+            read_to_string(workspace.path().join("Cargo.toml")).unwrap()
+        );
+        assert_eq!(
+            indoc! {r#"
+                [package]
+                name = "app"
+
+                [dependencies]
+                clawless = { workspace = true }
+                serde = { workspace = true, features = ["derive"] }
+                tokio = { workspace = true }
+
+                [dev-dependencies]
+                tempfile = { workspace = true }
+            "#},
+            read_to_string(crate_path.join("Cargo.toml")).unwrap()
+        );
+    }
+
+    #[test]
+    fn inherit_workspace_dependencies_applies_filter() {
+        let workspace = workspace("[workspace]\nmembers = [\"crates/app\"]\n");
+        let crate_path = member(
+            &workspace,
+            "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\nclawless = \"0.4.0\"\n",
+        );
+
+        let inherited = inherit_workspace_dependencies(workspace.path(), &crate_path, |name| {
+            name == "clawless"
+        })
+        .unwrap();
+
+        assert_eq!(vec!["clawless"], inherited);
+        assert_eq!(
+            "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\nclawless = { workspace = true }\n",
+            read_to_string(crate_path.join("Cargo.toml")).unwrap()
+        );
+    }
+
+    #[test]
+    fn manifest_backup_restores_files() {
+        let workspace = workspace("[workspace]\n");
+        let backup = ManifestBackup::new(workspace.path()).unwrap();
+
+        write(
+            workspace.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\"]\n",
+        )
+        .unwrap();
+        write(workspace.path().join("Cargo.lock"), "version = 4\n").unwrap();
+        backup.restore().unwrap();
+
+        assert_eq!(
+            "[workspace]\n",
+            read_to_string(workspace.path().join("Cargo.toml")).unwrap()
+        );
+        assert!(!workspace.path().join("Cargo.lock").exists());
+    }
+
+    #[test]
+    fn relative_path_walks_up_to_common_ancestor() {
+        assert_eq!(
+            PathBuf::from("../clawless/crates/clawless"),
+            relative_path(
+                Path::new("/work/monorepo"),
+                Path::new("/work/monorepo/crates/app/../../../clawless/crates/clawless")
+            )
+        );
+    }
+}
@@ -0,0 +1,480 @@
+//! The command tree of a Clawless project, read from its source
+//!
+//! The tree is built without compiling the project. Starting at the `commands` module, every
+//! module that is declared with `mod name;` is parsed with `syn`, and its functions with a
+//! `#[command]` attribute become the commands of the tree. The modules of a command are its
+//! subcommands, mirroring how the `#[command]` macro registers commands with their parent.
+
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use getset::Getters;
+use quote::ToTokens;
+use serde::Serialize;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, File, Item, ItemFn, Lit, Meta, Token};
+
+use crate::source::parse;
+
+/// The commands of a project, ordered by name
+#[derive(Clone, Eq, PartialEq, Debug, Getters, Serialize)]
+pub struct CommandTree {
+    /// The top-level commands of the project
+    #[getset(get = "pub")]
+    commands: Vec<CommandEntry>,
+}
+
+/// A command in the command tree of a project
+#[derive(Clone, Eq, PartialEq, Debug, Getters, Serialize)]
+pub struct CommandEntry {
+    /// The name of the command on the command line
+    #[getset(get = "pub")]
+    name: String,
+
+    /// The aliases of the command
+    #[getset(get = "pub")]
+    aliases: Vec<String>,
+
+    /// The other arguments of the `#[command]` attribute, e.g. `require_subcommand`
+    #[getset(get = "pub")]
+    attributes: Vec<String>,
+
+    /// The first line of the documentation of the command
+    #[getset(get = "pub")]
+    summary: Option<String>,
+
+    /// The file that defines the command, relative to the project
+    #[getset(get = "pub")]
+    file: PathBuf,
+
+    /// The line of the command function in the file
+    #[getset(get = "pub")]
+    line: usize,
+
+    /// The subcommands of the command, ordered by name
+    #[getset(get = "pub")]
+    subcommands: Vec<CommandEntry>,
+}
+
+impl CommandTree {
+    /// Read the command tree from the source of a project
+    pub fn read(project: &Path) -> Result<Self> {
+        let src = project.join("src");
+
+        let module = [src.join("commands.rs"), src.join("commands").join("mod.rs")]
+            .into_iter()
+            .find(|path| path.exists())
+            .ok_or_else(|| {
+                anyhow!(
+                    "failed to find the commands module at src/commands.rs or src/commands/mod.rs"
+                )
+            })?;
+
+        let file = read_module(&module)?;
+        let commands = read_submodules(project, &file, &module, &src.join("commands"))?;
+
+        Ok(Self { commands })
+    }
+}
+
+impl Display for CommandTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rows = Vec::new();
+        for command in &self.commands {
+            command.rows(0, &mut rows);
+        }
+
+        let label_width = rows.iter().map(|(label, _, _)| label.len()).max();
+        let location_width = rows.iter().map(|(_, location, _)| location.len()).max();
+
+        let lines: Vec<String> = rows
+            .iter()
+            .map(|(label, location, summary)| {
+                let line = format!(
+                    "{label:label_width$}  {location:location_width$}  {summary}",
+                    label_width = label_width.unwrap_or_default(),
+                    location_width = location_width.unwrap_or_default(),
+                );
+
+                line.trim_end().to_string()
+            })
+            .collect();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl CommandEntry {
+    /// Create the entry of a command function
+    pub fn new(function: &ItemFn, file: PathBuf, subcommands: Vec<CommandEntry>) -> Result<Self> {
+        let attribute = function
+            .attrs
+            .iter()
+            .find(|attribute| is_command_attribute(attribute))
+            .ok_or_else(|| anyhow!("the function is not a command"))?;
+
+        let mut name = function.sig.ident.unraw().to_string();
+        let mut aliases = Vec::new();
+        let mut attributes = Vec::new();
+
+        if let Meta::List(_) = &attribute.meta {
+            let arguments = attribute
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .context(format!(
+                    "failed to parse the attribute of the command in {}",
+                    file.display()
+                ))?;
+
+            for argument in arguments {
+                match argument {
+                    Meta::NameValue(meta) if meta.path.is_ident("name") => {
+                        name = expression_to_string(&meta.value);
+                    }
+                    Meta::NameValue(meta) if meta.path.is_ident("alias") => {
+                        aliases.push(expression_to_string(&meta.value));
+                    }
+                    Meta::NameValue(meta) => attributes.push(format!(
+                        "{} = {}",
+                        path_to_string(&meta.path),
+                        expression_to_string(&meta.value)
+                    )),
+                    Meta::Path(path) => attributes.push(path_to_string(&path)),
+                    Meta::List(list) => attributes.push(list.to_token_stream().to_string()),
+                }
+            }
+        }
+
+        Ok(Self {
+            name,
+            aliases,
+            attributes,
+            summary: summary(function),
+            file,
+            line: function.sig.ident.span().start().line,
+            subcommands,
+        })
+    }
+
+    /// Collect the label, location, and summary of the command and its subcommands
+    fn rows(&self, depth: usize, rows: &mut Vec<(String, String, String)>) {
+        let mut label = format!("{}{}", "  ".repeat(depth), self.name);
+
+        if !self.aliases.is_empty() {
+            label.push_str(&format!(" ({})", self.aliases.join(", ")));
+        }
+
+        if !self.attributes.is_empty() {
+            label.push_str(&format!(" [{}]", self.attributes.join(", ")));
+        }
+
+        let location = format!("{}:{}", self.file.display(), self.line);
+        let summary = self.summary.clone().unwrap_or_default();
+
+        rows.push((label, location, summary));
+
+        for subcommand in &self.subcommands {
+            subcommand.rows(depth + 1, rows);
+        }
+    }
+}
+
+/// Read the commands that are defined in the submodules of a module
+///
+/// The files of the submodules are in `directory`, either as `name.rs` or as `name/mod.rs`.
+fn read_submodules(
+    project: &Path,
+    file: &File,
+    path: &Path,
+    directory: &Path,
+) -> Result<Vec<CommandEntry>> {
+    let mut commands = Vec::new();
+
+    for item in &file.items {
+        let Item::Mod(module) = item else {
+            continue;
+        };
+
+        // Inline modules such as `mod tests { ... }` do not have files of their own
+        if module.content.is_some() {
+            continue;
+        }
+
+        let name = module.ident.unraw().to_string();
+        let module_path = [
+            directory.join(format!("{name}.rs")),
+            directory.join(&name).join("mod.rs"),
+        ]
+        .into_iter()
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            anyhow!(
+                "failed to find the module `{name}` that is declared in {}",
+                relative(project, path).display()
+            )
+        })?;
+
+        let module_file = read_module(&module_path)?;
+        let functions = command_functions(&module_file);
+
+        // Modules without a command are helpers, and cannot have subcommands
+        if functions.is_empty() {
+            continue;
+        }
+
+        // Both `name.rs` and `name/mod.rs` declare their submodules in the directory `name`
+        let subcommands =
+            read_submodules(project, &module_file, &module_path, &directory.join(&name))?;
+
+        for function in functions {
+            commands.push(CommandEntry::new(
+                function,
+                relative(project, &module_path),
+                subcommands.clone(),
+            )?);
+        }
+    }
+
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(commands)
+}
+
+/// Returns the functions of a file that are annotated with `#[command]`
+pub fn command_functions(file: &File) -> Vec<&ItemFn> {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Fn(function) if function.attrs.iter().any(is_command_attribute) => Some(function),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_command_attribute(attribute: &Attribute) -> bool {
+    attribute
+        .path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "command")
+}
+
+/// Returns the first line of the doc comment of a function, which is the `about` of the command
+fn summary(function: &ItemFn) -> Option<String> {
+    function
+        .attrs
+        .iter()
+        .find_map(|attribute| match &attribute.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(lit) => Some(lit.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+fn path_to_string(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+fn expression_to_string(expression: &Expr) -> String {
+    match expression {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(lit) => lit.value(),
+            lit => lit.to_token_stream().to_string(),
+        },
+        Expr::Path(expr) => path_to_string(&expr.path),
+        expression => expression.to_token_stream().to_string(),
+    }
+}
+
+fn read_module(path: &Path) -> Result<File> {
+    let content = read_to_string(path).context(format!("failed to read {}", path.display()))?;
+
+    parse(&content).context(format!("failed to parse {}", path.display()))
+}
+
+fn relative(project: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(project)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use indoc::indoc;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn project() -> TempDir {
+        let project = TempDir::new().unwrap();
+        let commands = project.path().join("src").join("commands");
+        create_dir_all(commands.join("db")).unwrap();
+
+        write(
+            project.path().join("src").join("commands.rs"),
+            "mod db;\nmod greet;\nmod util;\n\nclawless::commands!();\n",
+        )
+        .unwrap();
+        write(
+            commands.join("greet.rs"),
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                #[derive(Debug, Args)]
+                pub struct GreetArgs {}
+
+                /// Greet the user
+                ///
+                /// Prints a greeting.
+                #[command(alias = "g", alias = "hi")]
+                pub async fn greet(args: GreetArgs, context: Context) -> CommandResult {
+                    Ok(())
+                }
+            "#},
+        )
+        .unwrap();
+        write(
+            commands.join("db.rs"),
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                mod migrate;
+
+                #[derive(Debug, Args)]
+                pub struct DbArgs {}
+
+                /// Manage the database
+                #[command(require_subcommand, theme = crate::theme::theme)]
+                pub async fn db(args: DbArgs, context: Context) -> CommandResult {
+                    Ok(())
+                }
+
+                #[cfg(test)]
+                mod tests {}
+            "#},
+        )
+        .unwrap();
+        write(
+            commands.join("db").join("migrate.rs"),
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                #[derive(Debug, Args)]
+                pub struct MigrateArgs {}
+
+                #[clawless::command]
+                pub async fn migrate(args: MigrateArgs, context: Context) -> CommandResult {
+                    Ok(())
+                }
+            "#},
+        )
+        .unwrap();
+        write(commands.join("util.rs"), "pub fn helper() {}\n").unwrap();
+
+        project
+    }
+
+    #[test]
+    fn read_finds_commands_and_subcommands() {
+        let project = project();
+
+        let tree = CommandTree::read(project.path()).unwrap();
+
+        let names: Vec<&String> = tree.commands().iter().map(CommandEntry::name).collect();
+        assert_eq!(vec!["db", "greet"], names);
+
+        let db = &tree.commands()[0];
+        assert_eq!(
+            &vec!["require_subcommand", "theme = crate::theme::theme"],
+            db.attributes()
+        );
+        assert_eq!(&Some("Manage the database".to_string()), db.summary());
+        assert_eq!(1, db.subcommands().len());
+
+        let migrate = &db.subcommands()[0];
+        assert_eq!("migrate", migrate.name());
+        assert_eq!(&None, migrate.summary());
+        assert_eq!(&PathBuf::from("src/commands/db/migrate.rs"), migrate.file());
+        assert_eq!(7, *migrate.line());
+    }
+
+    #[test]
+    fn read_finds_aliases() {
+        let project = project();
+
+        let tree = CommandTree::read(project.path()).unwrap();
+
+        let greet = &tree.commands()[1];
+        assert_eq!(&vec!["g", "hi"], greet.aliases());
+        assert_eq!(&Some("Greet the user".to_string()), greet.summary());
+        assert_eq!(10, *greet.line());
+    }
+
+    #[test]
+    fn read_uses_name_attribute() {
+        let project = project();
+        write(
+            project.path().join("src").join("commands").join("greet.rs"),
+            indoc! {r#"
+                #[command(name = "say-hello")]
+                pub async fn say_hello(args: SayHelloArgs, context: Context) -> CommandResult {
+                    Ok(())
+                }
+            "#},
+        )
+        .unwrap();
+
+        let tree = CommandTree::read(project.path()).unwrap();
+
+        let greet = &tree.commands()[1];
+        assert_eq!("say-hello", greet.name());
+        assert!(greet.attributes().is_empty());
+    }
+
+    #[test]
+    fn read_fails_for_missing_module() {
+        let project = project();
+        write(
+            project.path().join("src").join("commands.rs"),
+            "mod deploy;\n\nclawless::commands!();\n",
+        )
+        .unwrap();
+
+        let error = CommandTree::read(project.path()).unwrap_err();
+
+        assert_eq!(
+            "failed to find the module `deploy` that is declared in src/commands.rs",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn display_prints_aligned_tree() {
+        let project = project();
+
+        let tree = CommandTree::read(project.path()).unwrap();
+
+        assert_eq!(
+            indoc! {"
+                db [require_subcommand, theme = crate::theme::theme]  src/commands/db.rs:10         Manage the database
+                  migrate                                             src/commands/db/migrate.rs:7
+                greet (g, hi)                                         src/commands/greet.rs:10      Greet the user
+            "}
+            .trim_end(),
+            tree.to_string()
+        );
+    }
+}
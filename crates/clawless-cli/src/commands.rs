@@ -1,10 +1,27 @@
 //! Command implementations for the Clawless CLI
 //!
 //! This module contains all the commands that the Clawless CLI provides:
+//! - `check-compat` - Check two versions of an application for breaking changes
 //! - `new` - Create a new Clawless project
+//! - `init` - Turn an existing binary crate into a Clawless project
+//! - `list` - List the commands of a Clawless project
+//! - `doctor` - Check a Clawless project for structural problems
+//! - `migrate` - Migrate existing applications to Clawless
 //! - `generate` - Generate code scaffolding (subcommands for different generators)
+//! - `destroy` - Remove generated code
+//! - `rename` - Rename generated code
+//! - `move` - Move generated code to another location
+//! - `config` - Show and change the configuration of the CLI (built in)
 
+mod check_compat;
+mod destroy;
+mod doctor;
 mod generate;
+mod init;
+mod list;
+mod migrate;
+mod r#move;
 mod new;
+mod rename;
 
-clawless::commands!();
+clawless::commands!(config = crate::config::Config);
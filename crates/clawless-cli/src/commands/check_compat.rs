@@ -0,0 +1,92 @@
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use clawless::manifest::Manifest;
+use clawless::prelude::*;
+
+/// Arguments for the `check-compat` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
+pub struct CheckCompatArgs {
+    /// Path to the manifest of the previous version of the application
+    old: PathBuf,
+
+    /// Path to the manifest of the new version of the application
+    new: PathBuf,
+}
+
+/// Check two versions of a Clawless application for breaking changes
+///
+/// This command compares the manifests of two versions of an application and reports changes to
+/// the command-line surface that can break scripts written against the older version, such as
+/// removed commands or aliases, renamed flags, or arguments that are now required. The command
+/// fails if it finds any breaking changes, which makes it usable as a release gate in CI.
+///
+/// Clawless applications print their manifest with the hidden `--clawless-manifest` flag.
+/// Manifests can also be created in code with `clawless::manifest::Manifest::from_registry`.
+///
+/// # Examples
+///
+/// ```shell
+/// mycli --clawless-manifest > cli-v2.json
+/// clawless check-compat cli-v1.json cli-v2.json
+/// ```
+#[command(name = "check-compat")]
+pub async fn check_compat(args: CheckCompatArgs, context: Context) -> CommandResult {
+    let cwd = context.current_working_directory().get();
+
+    let old = read_manifest(&cwd.join(&args.old))?;
+    let new = read_manifest(&cwd.join(&args.new))?;
+
+    let breaking_changes = old.breaking_changes(&new);
+
+    if breaking_changes.is_empty() {
+        println!("No breaking changes found");
+        return Ok(());
+    }
+
+    for change in &breaking_changes {
+        println!("- {change}");
+    }
+
+    anyhow::bail!("found {} breaking change(s)", breaking_changes.len());
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest, Error> {
+    let json =
+        read_to_string(path).context(format!("failed to read manifest at {}", path.display()))?;
+
+    Manifest::from_json(&json).context(format!("failed to parse manifest at {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use clawless::clap::{Arg, Command};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn read_manifest_parses_json() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("manifest.json");
+
+        let manifest = Manifest::from_command(
+            &Command::new("app").subcommand(Command::new("deploy").arg(Arg::new("env"))),
+        );
+        write(&path, manifest.to_json().unwrap()).unwrap();
+
+        assert_eq!(manifest, read_manifest(&path).unwrap());
+    }
+
+    #[test]
+    fn read_manifest_fails_for_invalid_json() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("manifest.json");
+
+        write(&path, "not json").unwrap();
+
+        assert!(read_manifest(&path).is_err());
+    }
+}
@@ -0,0 +1,24 @@
+//! Commands that remove code from Clawless projects
+
+use clawless::prelude::*;
+
+mod command;
+
+/// Arguments for the `destroy` command group
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct DestroyArgs {}
+
+/// Remove code from Clawless projects
+///
+/// This is a command group containing subcommands for removing code that was
+/// generated with `clawless generate`.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless destroy command db/migrate
+/// ```
+#[command(require_subcommand, alias = "d")]
+pub async fn destroy(_args: DestroyArgs, _context: Context) -> CommandResult {
+    Ok(())
+}
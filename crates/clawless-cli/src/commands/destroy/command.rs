@@ -0,0 +1,47 @@
+use clawless::prelude::*;
+
+use crate::input::CommandName;
+use crate::project::find_clawless_project;
+use crate::refactor::destroy_command;
+
+/// Arguments for the `destroy command` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct DestroyCommandArgs {
+    /// Name of the command to remove.
+    ///
+    /// Use slash notation for nested commands (e.g., "db/migrate").
+    name: String,
+
+    /// Remove the subcommands of the command as well.
+    #[arg(short, long)]
+    recursive: bool,
+}
+
+/// Remove a command from a Clawless project
+///
+/// This command deletes the file of the command and removes its `mod`
+/// statement from the parent module. Directories of command groups that
+/// become empty are removed as well.
+///
+/// # Examples
+///
+/// Remove a nested command:
+/// ```shell
+/// clawless destroy command db/migrate
+/// ```
+///
+/// Remove a command group and all of its subcommands:
+/// ```shell
+/// clawless destroy command --recursive db
+/// ```
+#[command(alias = "c")]
+pub async fn command(args: DestroyCommandArgs, context: Context) -> CommandResult {
+    let project = find_clawless_project(context.current_working_directory())?;
+    let command_name = CommandName::try_from(&args.name)?;
+
+    for change in destroy_command(&project, &command_name, args.recursive)? {
+        context.output().success(change.relative_to(&project));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use clawless::prelude::*;
+
+use crate::doctor::{Problems, diagnose};
+use crate::project::find_clawless_project;
+
+/// Arguments for the `doctor` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct DoctorArgs {
+    /// Fix the problems that can be fixed automatically.
+    #[arg(long)]
+    fix: bool,
+}
+
+/// Check a Clawless project for structural problems
+///
+/// This command reads the project with `syn`, without compiling it, and reports problems that the
+/// Clawless macros cannot detect:
+///
+/// - command files that are not declared with `mod`
+/// - `mod` declarations whose file does not exist
+/// - commands that are defined outside of `src/commands`
+/// - a commands module that does not call `clawless::commands!()`
+/// - command groups that declare subcommands without defining a command
+/// - commands in the same group whose names or aliases collide
+/// - arguments without a doc comment, which have no help text
+/// - a `clawless` dependency that does not match the version of the CLI
+///
+/// Every problem is reported with its file and line. Pass `--fix` to fix the problems that can be
+/// fixed automatically, such as missing or dangling `mod` declarations. The command fails if any
+/// problems remain.
+///
+/// Pass `--output json` to print the remaining problems as JSON, e.g. for editor integrations.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless doctor
+/// clawless doctor --fix
+/// ```
+#[command]
+pub async fn doctor(args: DoctorArgs, context: Context) -> CommandResult {
+    let project = find_clawless_project(context.current_working_directory())?;
+    let problems = diagnose(&project)?;
+
+    let mut remaining = Problems::default();
+
+    for problem in problems {
+        match problem.fix() {
+            Some(fix) if args.fix => {
+                fix.apply()?;
+                context.output().success(format!("Fixed {problem}"));
+            }
+            _ => remaining.0.push(problem),
+        }
+    }
+
+    // JSON is always printed, so that tools can tell an empty result from a failure
+    if !remaining.0.is_empty() || context.output().format() == OutputFormat::Json {
+        context.output().print(&remaining)?;
+    }
+
+    if remaining.0.is_empty() {
+        context.output().success("No problems found");
+        return Ok(());
+    }
+
+    let fixable = remaining
+        .0
+        .iter()
+        .filter(|problem| problem.fix().is_some())
+        .count();
+    let remaining = remaining.0.len();
+
+    if fixable > 0 {
+        anyhow::bail!(
+            "found {remaining} problem(s), {fixable} of which can be fixed with `clawless doctor --fix`"
+        );
+    }
+
+    anyhow::bail!("found {remaining} problem(s)");
+}
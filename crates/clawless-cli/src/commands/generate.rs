@@ -3,6 +3,7 @@
 use clawless::prelude::*;
 
 mod command;
+mod config;
 
 /// Arguments for the `generate` command group
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
@@ -17,6 +18,7 @@ pub struct GenerateArgs {}
 ///
 /// ```shell
 /// clawless generate command my-command
+/// clawless generate config
 /// ```
 #[command(require_subcommand, alias = "g")]
 pub async fn generate(_args: GenerateArgs, _context: Context) -> CommandResult {
@@ -1,12 +1,14 @@
 use std::fs::{create_dir_all, read_to_string, write};
 use std::path::{Path, PathBuf};
 
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use clawless::prelude::*;
 use convert_case::{Case, Casing};
 use indoc::indoc;
 
-use crate::input::CommandName;
+use crate::input::{Argument, CommandName};
+use crate::project::{find_clawless_project, find_command_module, find_parent_module};
+use crate::source::{ident, insert_mod};
 
 /// Arguments for the `generate command` command
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
@@ -15,6 +17,22 @@ pub struct GenerateCommandArgs {
     ///
     /// Use slash notation for nested commands (e.g., "db/migrate").
     name: String,
+
+    /// Create missing parent commands as command groups.
+    #[arg(short, long)]
+    parents: bool,
+
+    /// Add an option to the command (e.g., "env:String" or "tags:Vec<String>").
+    #[arg(long = "arg", value_name = "NAME:TYPE")]
+    arguments: Vec<String>,
+
+    /// Add a boolean flag to the command.
+    #[arg(long = "flag", value_name = "NAME")]
+    flags: Vec<String>,
+
+    /// Add an alias for the command.
+    #[arg(long = "alias", value_name = "ALIAS")]
+    aliases: Vec<String>,
 }
 
 /// Generate a new command in a Clawless project
@@ -38,6 +56,16 @@ pub struct GenerateCommandArgs {
 /// ```shell
 /// clawless generate command db/migrate
 /// ```
+///
+/// Generate a nested command and the `db` command group if it does not exist yet:
+/// ```shell
+/// clawless generate command --parents db/migrate
+/// ```
+///
+/// Generate a command with arguments and an alias:
+/// ```shell
+/// clawless generate command deploy --arg env:String --flag force --alias d
+/// ```
 #[command(alias = "c")]
 pub async fn command(args: GenerateCommandArgs, context: Context) -> CommandResult {
     // Check is command is running inside a Clawless project
@@ -46,97 +74,183 @@ pub async fn command(args: GenerateCommandArgs, context: Context) -> CommandResu
     // Parse command name to support nested commands (e.g. `clawless generate command generate/context`)
     let command_name = CommandName::try_from(&args.name)?;
 
+    // Parse the arguments of the command before any files are changed
+    let arguments = parse_arguments(&args.arguments, &args.flags)?;
+    check_aliases(&args.aliases)?;
+
+    // Create missing parent command groups if the user asked for them
+    let mut created = if args.parents {
+        create_parent_commands(&project, &command_name)?
+    } else {
+        Vec::new()
+    };
+
     // Create `src/commands/<parent commands>/` directories if they do not exist
     create_parent_directory(&project, &command_name)?;
 
     // Create `src/commands/<parent commands>/<command>.rs` file with boilerplate code
-    create_command_file(&project, &command_name)?;
+    create_command_file(&project, &command_name, &arguments, &args.aliases)?;
+    created.push(command_name.path_from_project_root(&project));
 
     // Add mod <command> to parent module
     insert_mod_statement(&project, &command_name)?;
 
     // Print a success message to the user
+    for path in created {
+        let path = path.strip_prefix(&project).unwrap_or(&path);
+        context
+            .output()
+            .success(format!("Created {}", path.display()));
+    }
 
     Ok(())
 }
 
-fn find_clawless_project(current_working_directory: &CurrentWorkingDirectory) -> Result<PathBuf> {
-    let main_rs_path = find_main_rs(current_working_directory)
-        .ok_or_else(|| anyhow!("failed to find a main.rs file in the current directory or any of its parent directories"))?;
+fn create_parent_directory(project: &Path, command_name: &CommandName) -> Result<()> {
+    let mut dir_path = project.join("src").join("commands");
 
-    check_main_rs(&main_rs_path)?;
+    for module in command_name.parent_modules() {
+        dir_path = dir_path.join(module.to_case(Case::Snake));
+    }
 
-    let project_path = main_rs_path
-        .parent() // src
-        .and_then(Path::parent) // crate root
-        .ok_or_else(|| anyhow!("failed to determine the project directory from main.rs path"))?
-        .to_path_buf();
+    create_dir_all(&dir_path).context(format!(
+        "failed to create parent directories for command at {}",
+        dir_path.display()
+    ))?;
 
-    Ok(project_path)
+    Ok(())
 }
 
-fn find_main_rs(current_working_directory: &CurrentWorkingDirectory) -> Option<PathBuf> {
-    let mut dir = current_working_directory.get().to_path_buf();
+/// Create a command group for every parent of a command that does not exist yet
+///
+/// Returns the paths of the files that were created.
+fn create_parent_commands(project: &Path, command_name: &CommandName) -> Result<Vec<PathBuf>> {
+    let mut created = Vec::new();
 
-    loop {
-        // Check for src/main.rs first, then main.rs in the current directory
-        let src_main_rs = dir.join("src").join("main.rs");
-        if src_main_rs.exists() {
-            return Some(src_main_rs);
+    for parent in command_name.ancestors() {
+        if find_command_module(project, &parent).is_some() {
+            continue;
         }
 
-        let main_rs = dir.join("main.rs");
-        if main_rs.exists() {
-            return Some(main_rs);
-        }
+        create_parent_directory(project, &parent)?;
+        create_group_file(project, &parent)?;
+        insert_mod_statement(project, &parent)?;
 
-        // Move up to parent directory, or return None if at filesystem root
-        dir = dir.parent()?.to_path_buf();
+        created.push(parent.path_from_project_root(project));
     }
+
+    Ok(created)
 }
 
-fn check_main_rs(path: &Path) -> Result<()> {
-    let content =
-        read_to_string(path).context(format!("failed to read main.rs at {}", path.display()))?;
+fn create_group_file(project_path: &Path, command_name: &CommandName) -> Result<()> {
+    let struct_prefix = command_name.name().to_case(Case::Pascal);
+
+    let boilerplate = format!(
+        indoc! {
+            r#"use clawless::prelude::*;
 
-    if !content.contains("clawless::main!") {
-        anyhow::bail!(
-            "the main.rs file at '{}' does not contain the 'clawless::main!' macro, indicating that this is not a Clawless project",
-            path.display()
-        );
-    }
+            #[derive(Clone, Debug, Args)]
+            pub struct {}Args {{}}
+
+            #[command(require_subcommand)]
+            pub async fn {}(_args: {}Args, _context: Context) -> CommandResult {{
+                Ok(())
+            }}
+            "#
+        },
+        struct_prefix,
+        ident(&command_name.module_name()),
+        struct_prefix
+    );
+
+    let group_file_path = command_name.path_from_project_root(project_path);
+
+    write(&group_file_path, boilerplate).context(format!(
+        "failed to create file for new command group at {}",
+        group_file_path.display()
+    ))?;
 
     Ok(())
 }
 
-fn create_parent_directory(project: &Path, command_name: &CommandName) -> Result<()> {
-    let mut dir_path = project.join("src").join("commands");
+fn parse_arguments(arguments: &[String], flags: &[String]) -> Result<Vec<Argument>> {
+    let arguments = arguments
+        .iter()
+        .map(Argument::try_from)
+        .chain(flags.iter().map(|flag| Argument::flag(flag)))
+        .collect::<Result<Vec<_>>>()?;
 
-    for module in command_name.parent_modules() {
-        dir_path = dir_path.join(module.to_case(Case::Snake));
+    for (index, argument) in arguments.iter().enumerate() {
+        if arguments[..index]
+            .iter()
+            .any(|other| other.name() == argument.name())
+        {
+            anyhow::bail!(
+                "the argument `{}` is defined more than once",
+                argument.name()
+            );
+        }
     }
 
-    create_dir_all(&dir_path).context(format!(
-        "failed to create parent directories for command at {}",
-        dir_path.display()
-    ))?;
+    Ok(arguments)
+}
+
+fn check_aliases(aliases: &[String]) -> Result<()> {
+    if let Some(alias) = aliases
+        .iter()
+        .find(|alias| alias.is_empty() || alias.contains(|c: char| c.is_whitespace() || c == '"'))
+    {
+        anyhow::bail!("`{alias}` is not a valid alias for a command");
+    }
 
     Ok(())
 }
 
-fn create_command_file(project_path: &Path, command_name: &CommandName) -> Result<()> {
+fn create_command_file(
+    project_path: &Path,
+    command_name: &CommandName,
+    arguments: &[Argument],
+    aliases: &[String],
+) -> Result<()> {
     let struct_prefix = command_name.name().to_case(Case::Pascal);
 
+    let fields = if arguments.is_empty() {
+        "    // Define command arguments here\n".to_string()
+    } else {
+        arguments
+            .iter()
+            .map(|argument| {
+                format!(
+                    "    /// TODO: Describe `{}`\n    #[arg(long)]\n    {}: {},\n",
+                    argument.long(),
+                    argument.name(),
+                    argument.ty()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let attribute = if aliases.is_empty() {
+        "#[command]".to_string()
+    } else {
+        let aliases: Vec<String> = aliases
+            .iter()
+            .map(|alias| format!("alias = \"{alias}\""))
+            .collect();
+        format!("#[command({})]", aliases.join(", "))
+    };
+
     let boilerplate = format!(
         indoc! {
             r#"use clawless::prelude::*;
 
-            #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
+            #[derive(Clone, Debug, Args)]
             pub struct {}Args {{
-                // Define command arguments here
-            }}
+            {}}}
 
-            #[command]
+            /// TODO: Describe the command
+            {}
             pub async fn {}(args: {}Args, context: Context) -> CommandResult {{
                 // Command implementation goes here
                 Ok(())
@@ -144,7 +258,9 @@ fn create_command_file(project_path: &Path, command_name: &CommandName) -> Resul
             "#
         },
         struct_prefix,
-        command_name.name(),
+        fields,
+        attribute,
+        ident(&command_name.module_name()),
         struct_prefix
     );
 
@@ -162,161 +278,28 @@ fn insert_mod_statement(project: &Path, command_name: &CommandName) -> Result<()
     // Find the parent module file where the mod statement should be inserted
     let parent = find_parent_module(project, command_name)?;
 
-    let mod_statement = format!("mod {};", command_name.name());
+    let content = read_to_string(&parent)?;
+    let updated = insert_mod(&content, &command_name.module_name()).context(format!(
+        "failed to declare the command in {}",
+        parent.display()
+    ))?;
 
-    // Read current content and ensure we don't duplicate the mod statement
-    let mut content = read_to_string(&parent)?;
-    if content.contains(&mod_statement) {
-        return Ok(());
+    // Write the updated content back to the parent module file if the module was not declared yet
+    if updated != content {
+        write(&parent, updated)?;
     }
 
-    // Read the file into memory so that we can iterate over it
-    let mut lines: Vec<&str> = content.lines().collect();
-
-    // Find insertion point: after last mod, or last use, or first non-doc-comment line, or at start
-    let index =
-        lines
-            .iter()
-            .enumerate()
-            .rev()
-            .find_map(|(index, line)| line.trim_start().starts_with("mod ").then_some(index))
-            .or_else(|| {
-                lines.iter().enumerate().rev().find_map(|(index, line)| {
-                    line.trim_start().starts_with("use ").then_some(index)
-                })
-            })
-            .or_else(|| {
-                lines.iter().enumerate().find_map(|(index, line)| {
-                    (!line.trim_start().starts_with("//!")).then_some(index)
-                })
-            })
-            .unwrap_or(0);
-
-    // Insert the mod statement after the found index
-    lines.insert(index + 1, &mod_statement);
-    content = lines.join("\n");
-
-    // Insert a trailing newline
-    content.push('\n');
-
-    // Write the updated content back to the parent module file
-    write(&parent, content)?;
-
     Ok(())
 }
 
-fn find_parent_module(project: &Path, command_name: &CommandName) -> Result<PathBuf> {
-    let parent_modules = command_name.parent_modules();
-
-    // The parent module is either "commands" (if no nesting) or the last element in parent_modules
-    let commands_default = "commands".to_string();
-    let parent_name = parent_modules.last().unwrap_or(&commands_default);
-
-    // Determine the path to the parent module file (either as a file or mod.rs)
-    let mut base = project.join("src").join("commands");
-
-    // Navigate down to the parent's directory, excluding the parent itself
-    if parent_modules.len() > 1 {
-        for module in &parent_modules[..parent_modules.len() - 1] {
-            base = base.join(module.to_case(Case::Snake));
-        }
-    } else if parent_modules.is_empty() {
-        // Parent is src/commands itself
-        base = project.join("src");
-    }
-
-    let candidate_file = base.join(format!("{}.rs", parent_name.to_case(Case::Snake)));
-    let candidate_dir_mod = base.join(parent_name.to_case(Case::Snake)).join("mod.rs");
-
-    if candidate_file.exists() {
-        Ok(candidate_file)
-    } else if candidate_dir_mod.exists() {
-        Ok(candidate_dir_mod)
-    } else {
-        Err(anyhow!(
-            "parent module `{}` does not exist under `src/commands`; refusing to create it",
-            parent_name
-        ))
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use std::fs::{create_dir, create_dir_all, write};
+    use std::fs::{create_dir_all, write};
 
     use tempfile::TempDir;
 
     use super::*;
 
-    #[test]
-    fn check_clawless_project_finds_main_in_src_directory() {
-        let cwd = TempDir::new().unwrap();
-
-        let src_directory = cwd.path().join("src");
-        create_dir(&src_directory).unwrap();
-
-        let main_rs_path = src_directory.join("main.rs");
-        write(&main_rs_path, "clawless::main!();").unwrap();
-
-        let check = find_clawless_project(&cwd.path().into()).unwrap();
-
-        assert_eq!(cwd.path(), &check);
-    }
-
-    #[test]
-    fn check_clawless_project_finds_main_in_directory() {
-        let cwd = TempDir::new().unwrap();
-
-        let src_directory = cwd.path().join("src");
-        create_dir(&src_directory).unwrap();
-
-        let main_rs_path = src_directory.join("main.rs");
-        write(&main_rs_path, "clawless::main!();").unwrap();
-
-        let check = find_clawless_project(&src_directory.as_path().into()).unwrap();
-
-        assert_eq!(cwd.path(), &check);
-    }
-
-    #[test]
-    fn check_clawless_project_finds_main_in_parent_directory() {
-        let cwd = TempDir::new().unwrap();
-
-        let src_directory = cwd.path().join("src");
-
-        // Create a subdirectory in which to call `check_clawless_project`
-        let sub_dir = src_directory.join("subdir");
-        create_dir_all(&sub_dir).unwrap();
-
-        let main_rs_path = src_directory.join("main.rs");
-        write(&main_rs_path, "clawless::main!();").unwrap();
-
-        let check = find_clawless_project(&sub_dir.as_path().into()).unwrap();
-
-        assert_eq!(cwd.path(), &check);
-    }
-
-    #[test]
-    fn check_clawless_project_fails_without_main_rs() {
-        let cwd = TempDir::new().unwrap();
-
-        let check = find_clawless_project(&cwd.path().into());
-
-        assert!(check.is_err());
-    }
-
-    #[test]
-    fn check_clawless_project_fails_without_clawless_macro() {
-        let cwd = TempDir::new().unwrap();
-
-        let main_rs_path = cwd.path().join("main.rs");
-        write(&main_rs_path, "fn main() {}").unwrap();
-
-        let check = find_clawless_project(&cwd.path().into());
-
-        assert!(check.is_err());
-    }
-
     #[test]
     fn create_command_file_writes_boilerplate() {
         let cwd = TempDir::new().unwrap();
@@ -327,7 +310,7 @@ mod tests {
             .parent_modules(vec!["parent".into()])
             .build();
 
-        create_command_file(cwd.path(), &command_name).unwrap();
+        create_command_file(cwd.path(), &command_name, &[], &[]).unwrap();
 
         let command_file_path = command_name.path_from_project_root(cwd.path());
         let content = read_to_string(command_file_path).unwrap();
@@ -337,39 +320,101 @@ mod tests {
     }
 
     #[test]
-    fn find_parent_module_locates_file_module() {
+    fn create_command_file_writes_arguments_and_aliases() {
         let cwd = TempDir::new().unwrap();
         create_dir_all(cwd.path().join("src").join("commands")).unwrap();
 
-        let commands_rs_path = cwd.path().join("src").join("commands.rs");
-        write(&commands_rs_path, "").unwrap();
+        let command_name = CommandName::builder().name("deploy").build();
+        let arguments = parse_arguments(
+            &["env:String".into(), "dry_run:bool".into()],
+            &["force".into()],
+        )
+        .unwrap();
 
-        let command_name = CommandName::builder()
-            .name("test".to_string())
-            .parent_modules(vec![])
-            .build();
+        create_command_file(cwd.path(), &command_name, &arguments, &["d".into()]).unwrap();
+
+        let content = read_to_string(command_name.path_from_project_root(cwd.path())).unwrap();
+
+        assert_eq!(
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                #[derive(Clone, Debug, Args)]
+                pub struct DeployArgs {
+                    /// TODO: Describe `--env`
+                    #[arg(long)]
+                    env: String,
+
+                    /// TODO: Describe `--dry-run`
+                    #[arg(long)]
+                    dry_run: bool,
+
+                    /// TODO: Describe `--force`
+                    #[arg(long)]
+                    force: bool,
+                }
+
+                /// TODO: Describe the command
+                #[command(alias = "d")]
+                pub async fn deploy(args: DeployArgs, context: Context) -> CommandResult {
+                    // Command implementation goes here
+                    Ok(())
+                }
+            "#},
+            content
+        );
+    }
 
-        let parent_module_path = find_parent_module(cwd.path(), &command_name).unwrap();
+    #[test]
+    fn parse_arguments_fails_for_duplicate_names() {
+        let error = parse_arguments(&["force:bool".into()], &["force".into()]).unwrap_err();
 
-        assert_eq!(parent_module_path, commands_rs_path);
+        assert_eq!(
+            "the argument `force` is defined more than once",
+            error.to_string()
+        );
     }
 
     #[test]
-    fn find_parent_module_locates_mod_rs_module() {
+    fn create_parent_commands_creates_missing_groups() {
         let cwd = TempDir::new().unwrap();
         create_dir_all(cwd.path().join("src").join("commands")).unwrap();
+        write(cwd.path().join("src").join("commands.rs"), "mod greet;\n").unwrap();
 
-        let commands_mod_rs_path = cwd.path().join("src").join("commands").join("mod.rs");
-        write(&commands_mod_rs_path, "").unwrap();
+        let command_name = CommandName::try_from(&"db/schema/migrate".to_string()).unwrap();
 
-        let command_name = CommandName::builder()
-            .name("test".to_string())
-            .parent_modules(vec![])
-            .build();
+        let created = create_parent_commands(cwd.path(), &command_name).unwrap();
+
+        let commands = cwd.path().join("src").join("commands");
+        assert_eq!(
+            vec![
+                commands.join("db.rs"),
+                commands.join("db").join("schema.rs")
+            ],
+            created
+        );
+        assert_eq!(
+            "mod greet;\nmod db;\n",
+            read_to_string(cwd.path().join("src").join("commands.rs")).unwrap()
+        );
+
+        let db = read_to_string(commands.join("db.rs")).unwrap();
+        assert!(db.contains("#[command(require_subcommand)]"));
+        assert!(db.contains("mod schema;"));
+    }
+
+    #[test]
+    fn create_parent_commands_keeps_existing_groups() {
+        let cwd = TempDir::new().unwrap();
+        let commands = cwd.path().join("src").join("commands");
+        create_dir_all(commands.join("db")).unwrap();
+        write(commands.join("db").join("mod.rs"), "mod seed;\n").unwrap();
+
+        let command_name = CommandName::try_from(&"db/migrate".to_string()).unwrap();
 
-        let parent_module_path = find_parent_module(cwd.path(), &command_name).unwrap();
+        let created = create_parent_commands(cwd.path(), &command_name).unwrap();
 
-        assert_eq!(parent_module_path, commands_mod_rs_path);
+        assert!(created.is_empty());
     }
 
     #[test]
@@ -0,0 +1,225 @@
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow, bail};
+use clawless::prelude::*;
+use indoc::indoc;
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, value};
+
+use crate::project::find_clawless_project;
+use crate::source::{add_commands_option, insert_mod};
+
+/// The path of the configuration struct that is passed to `clawless::commands!`
+const CONFIG_STRUCT: &str = "crate::config::Config";
+
+/// Boilerplate for the configuration struct of a Clawless project
+const CONFIG_BOILERPLATE: &str = indoc! {r#"
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    /// Configuration of the application
+    ///
+    /// Users can change the settings with the built-in `config` command, and editors can use the
+    /// schema from `config schema` to autocomplete the configuration file.
+    #[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Config {
+        // Define settings here
+    }
+"#};
+
+/// Arguments for the `generate config` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct GenerateConfigArgs {}
+
+/// Generate a configuration struct in a Clawless project
+///
+/// This command creates `src/config.rs` with a configuration struct, declares it as the
+/// configuration of the application in `clawless::commands!`, and adds `serde` and `schemars`
+/// as dependencies. The application then gets a built-in `config` command, and validates its
+/// configuration files against the struct.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless generate config
+/// ```
+#[command]
+pub async fn config(_args: GenerateConfigArgs, context: Context) -> CommandResult {
+    let project = find_clawless_project(context.current_working_directory())?;
+
+    // Prepare the changes of all files before any of them is written
+    let config_file = project.join("src").join("config.rs");
+    if config_file.exists() {
+        bail!(
+            "the configuration file at {} already exists",
+            config_file.display()
+        );
+    }
+
+    let main = project.join("src").join("main.rs");
+    let main_content = insert_mod(&read(&main)?, "config").context(format!(
+        "failed to declare the configuration in {}",
+        main.display()
+    ))?;
+
+    let commands = commands_module(&project);
+    let commands_content = add_commands_option(&read(&commands)?, "config", CONFIG_STRUCT)
+        .context(format!(
+            "failed to declare the configuration struct in {}",
+            commands.display()
+        ))?;
+
+    let manifest = project.join("Cargo.toml");
+    let manifest_content = add_dependencies(&read(&manifest)?).context(format!(
+        "failed to add the dependencies to {}",
+        manifest.display()
+    ))?;
+
+    write(&config_file, CONFIG_BOILERPLATE).context(format!(
+        "failed to create configuration file at {}",
+        config_file.display()
+    ))?;
+    for (path, content) in [
+        (main, main_content),
+        (commands, commands_content),
+        (manifest, manifest_content),
+    ] {
+        write(&path, content).context(format!("failed to write {}", path.display()))?;
+    }
+
+    context
+        .output()
+        .success("Created the configuration struct in src/config.rs");
+
+    Ok(())
+}
+
+/// Returns the path of the commands module, which is either `commands.rs` or `commands/mod.rs`
+fn commands_module(project: &Path) -> PathBuf {
+    let commands = project.join("src").join("commands.rs");
+
+    if commands.exists() {
+        commands
+    } else {
+        project.join("src").join("commands").join("mod.rs")
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    read_to_string(path).context(format!("failed to read {}", path.display()))
+}
+
+/// Add `schemars` and `serde` with its `derive` feature to the dependencies of a manifest
+///
+/// Existing dependencies keep their version, but `serde` gets the `derive` feature if it does not
+/// have it yet, since the configuration struct derives its traits.
+fn add_dependencies(content: &str) -> Result<String> {
+    let mut manifest: DocumentMut = content.parse()?;
+
+    let dependencies = manifest
+        .entry("dependencies")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow!("`dependencies` is not a table"))?;
+
+    if !dependencies.contains_key("schemars") {
+        dependencies.insert("schemars", value("1"));
+    }
+
+    match dependencies.get_mut("serde") {
+        None => {
+            let mut serde = InlineTable::new();
+            serde.insert("version", "1".into());
+            serde.insert("features", Array::from_iter(["derive"]).into());
+            dependencies.insert("serde", value(serde));
+        }
+        Some(serde) => {
+            if let Some(version) = serde.as_str() {
+                let mut table = InlineTable::new();
+                table.insert("version", version.into());
+                *serde = value(table);
+            }
+
+            let serde = serde.as_table_like_mut().ok_or_else(|| {
+                anyhow!("the `serde` dependency is neither a version nor a table")
+            })?;
+            let features = serde
+                .entry("features")
+                .or_insert(value(Array::new()))
+                .as_array_mut()
+                .ok_or_else(|| {
+                    anyhow!("the features of the `serde` dependency are not an array")
+                })?;
+
+            if !features
+                .iter()
+                .any(|feature| feature.as_str() == Some("derive"))
+            {
+                features.push("derive");
+            }
+        }
+    }
+
+    Ok(manifest.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn add_dependencies_adds_missing_dependencies() {
+        let manifest = "[package]\nname = \"cli\"\n\n[dependencies]\nclawless = \"0.3.0\"\n";
+
+        assert_eq!(
+            indoc! {r#"
+                [package]
+                name = "cli"
+
+                [dependencies]
+                clawless = "0.3.0"
+                schemars = "1"
+                serde = { version = "1", features = ["derive"] }
+            "#},
+            add_dependencies(manifest).unwrap()
+        );
+    }
+
+    #[test]
+    fn add_dependencies_adds_derive_feature_to_version() {
+        let manifest = "[dependencies]\nserde = \"1.0.200\"\nschemars = \"0.9\"\n";
+
+        assert_eq!(
+            "[dependencies]\nserde = { version = \"1.0.200\", features = [\"derive\"] }\nschemars = \"0.9\"\n",
+            add_dependencies(manifest).unwrap()
+        );
+    }
+
+    #[test]
+    fn add_dependencies_adds_derive_feature_to_table() {
+        let manifest = indoc! {r#"
+            [dependencies]
+            schemars = "1"
+
+            [dependencies.serde]
+            version = "1"
+            features = ["rc"]
+        "#};
+
+        assert!(
+            add_dependencies(manifest)
+                .unwrap()
+                .contains("features = [\"rc\", \"derive\"]")
+        );
+    }
+
+    #[test]
+    fn add_dependencies_keeps_derive_feature() {
+        let manifest = "[dependencies]\nschemars = \"1\"\nserde = { version = \"1\", features = [\"derive\"] }\n";
+
+        assert_eq!(manifest, add_dependencies(manifest).unwrap());
+    }
+}
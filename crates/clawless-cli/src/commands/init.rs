@@ -0,0 +1,242 @@
+use std::fs::{read_to_string, write};
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use clawless::prelude::*;
+use indoc::indoc;
+use quote::{ToTokens, quote};
+use syn::ItemFn;
+
+use crate::cargo::{
+    add_clawless_dependency, find_package, find_workspace, inherit_workspace_dependencies,
+};
+use crate::input::ClawlessDependency;
+use crate::source::{find_fn, insert_mod, parse, remove_fn};
+use crate::template::COMMANDS_MODULE;
+
+/// The entry point of new Clawless projects, which replaces an empty `main.rs`
+const MAIN_RS: &str = include_str!("../../templates/minimal/src/main.rs");
+
+/// The call of the entry point macro, which is appended to an existing `main.rs`
+const MAIN_MACRO: &str = indoc! {r#"
+    // Initialize and start the Clawless application
+    //
+    // This macro sets up the Clawless runtime, parses the command-line arguments, and then
+    // calls the appropriate command function based on the user's input.
+    clawless::main!();
+"#};
+
+/// Arguments for the `init` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
+pub struct InitArgs {
+    /// Replace a `main` function that does more than print "Hello, world!"
+    #[arg(long)]
+    force: bool,
+
+    #[command(flatten)]
+    dependency: ClawlessDependency,
+}
+
+/// Turn an existing binary crate into a Clawless project
+///
+/// This command converts the crate that contains the working directory in place. It declares the
+/// `commands` module in `src/main.rs` and calls `clawless::main!()` there, creates
+/// `src/commands.rs`, and adds the Clawless framework as a dependency. An existing
+/// `src/commands.rs` that calls `clawless::commands!()` is kept, e.g. after
+/// `clawless migrate clap`.
+///
+/// The other items in `src/main.rs` are kept, but the `main` function is replaced, since Clawless
+/// generates its own. A `main` function that only prints "Hello, world!", as created by
+/// `cargo new`, is replaced without asking. Any other `main` function is only replaced with
+/// `--force`, so move its code into a command first.
+///
+/// If the crate is a member of a Cargo workspace, Clawless is declared in
+/// `[workspace.dependencies]` and inherited with `{ workspace = true }`.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless init
+/// clawless init --force --offline --clawless-path ../clawless/crates/clawless
+/// ```
+#[command]
+pub async fn init(args: InitArgs, context: Context) -> CommandResult {
+    let working_directory = context.current_working_directory().get();
+    let crate_path = find_package(working_directory)?;
+
+    let main_rs_path = crate_path.join("src").join("main.rs");
+    if !main_rs_path.is_file() {
+        bail!(
+            "the crate at {} has no src/main.rs; `clawless init` only converts binary crates",
+            crate_path.display()
+        );
+    }
+
+    let main_rs = read_to_string(&main_rs_path)
+        .context(format!("failed to read {}", main_rs_path.display()))?;
+    if main_rs.contains("clawless::main!") {
+        bail!(
+            "the crate at {} is already a Clawless project",
+            crate_path.display()
+        );
+    }
+
+    let has_commands_module = find_commands_module(&crate_path)?;
+
+    // Change main.rs in memory first, so that nothing is written if it cannot be converted
+    let main_rs = convert_main_rs(&main_rs, args.force)
+        .context(format!("failed to convert {}", main_rs_path.display()))?;
+
+    add_clawless_dependency(&crate_path, &args.dependency.resolve(working_directory))?;
+    if let Some(workspace) = find_workspace(&crate_path)? {
+        inherit_workspace_dependencies(&workspace, &crate_path, |name| name == "clawless")?;
+    }
+
+    write(&main_rs_path, main_rs).context(format!("failed to write {}", main_rs_path.display()))?;
+
+    if !has_commands_module {
+        let commands_rs_path = crate_path.join("src").join("commands.rs");
+        write(&commands_rs_path, COMMANDS_MODULE)
+            .context(format!("failed to write {}", commands_rs_path.display()))?;
+    }
+
+    context.output().success(format!(
+        "Converted the crate at {} into a Clawless project",
+        crate_path.display()
+    ));
+    context
+        .output()
+        .info("Add a command with `clawless generate command <name>`");
+
+    Ok(())
+}
+
+/// Returns whether the crate already has a commands module, e.g. from `clawless migrate clap`
+///
+/// A module named `commands` that does not call `clawless::commands!` is an error, since it
+/// cannot be used for the commands of the application.
+fn find_commands_module(crate_path: &Path) -> Result<bool> {
+    let src = crate_path.join("src");
+
+    for path in [src.join("commands.rs"), src.join("commands").join("mod.rs")] {
+        if !path.exists() {
+            continue;
+        }
+
+        let content =
+            read_to_string(&path).context(format!("failed to read {}", path.display()))?;
+        if !content.contains("clawless::commands!") {
+            bail!(
+                "the module at {} already exists; rename it before running `clawless init`",
+                path.display()
+            );
+        }
+
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// Declare the `commands` module in `main.rs`, and replace its `main` function with Clawless
+fn convert_main_rs(content: &str, force: bool) -> Result<String> {
+    let file = parse(content)?;
+
+    let content = match find_fn(&file, "main") {
+        Some(function) if !force && !is_hello_world(function) => bail!(
+            "src/main.rs already defines a `main` function; move its code into a command, or pass `--force` to replace it"
+        ),
+        Some(_) => remove_fn(content, "main")?,
+        None => content.to_string(),
+    };
+
+    // Use the entry point of new projects if nothing else is left, e.g. after `cargo new`
+    if content.trim().is_empty() {
+        return Ok(MAIN_RS.into());
+    }
+
+    let content = insert_mod(&content, "commands")?;
+
+    Ok(format!("{}\n\n{MAIN_MACRO}", content.trim_end()))
+}
+
+/// Returns whether a function is the `main` function that `cargo new` creates
+fn is_hello_world(function: &ItemFn) -> bool {
+    let hello_world = quote! {
+        {
+            println!("Hello, world!");
+        }
+    };
+
+    function.block.to_token_stream().to_string() == hello_world.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn convert_main_rs_replaces_hello_world() {
+        let content = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+
+        assert_eq!(MAIN_RS, convert_main_rs(content, false).unwrap());
+    }
+
+    #[test]
+    fn convert_main_rs_keeps_other_items() {
+        let content = indoc! {r#"
+            use std::env;
+
+            mod client;
+
+            fn main() {
+                println!("Hello, world!");
+            }
+
+            fn token() -> Option<String> {
+                env::var("TOKEN").ok()
+            }
+        "#};
+
+        assert_eq!(
+            format!(
+                "use std::env;\n\nmod client;\nmod commands;\n\nfn token() -> Option<String> {{\n    env::var(\"TOKEN\").ok()\n}}\n\n{MAIN_MACRO}"
+            ),
+            convert_main_rs(content, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn convert_main_rs_requires_force_for_custom_main() {
+        let content = "fn main() {\n    client::run();\n}\n";
+
+        let error = convert_main_rs(content, false).unwrap_err();
+        assert!(error.to_string().contains("pass `--force`"));
+
+        assert_eq!(MAIN_RS, convert_main_rs(content, true).unwrap());
+    }
+
+    #[test]
+    fn main_macro_matches_template() {
+        assert_eq!(MAIN_RS, format!("mod commands;\n\n{MAIN_MACRO}"));
+    }
+
+    #[test]
+    fn find_commands_module_keeps_clawless_modules() {
+        let crate_dir = TempDir::new().unwrap();
+        create_dir(crate_dir.path().join("src")).unwrap();
+        assert!(!find_commands_module(crate_dir.path()).unwrap());
+
+        let commands_rs = crate_dir.path().join("src").join("commands.rs");
+        write(&commands_rs, COMMANDS_MODULE).unwrap();
+        assert!(find_commands_module(crate_dir.path()).unwrap());
+
+        write(&commands_rs, "pub fn run() {}\n").unwrap();
+        let error = find_commands_module(crate_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("rename it"), "{error}");
+    }
+}
@@ -0,0 +1,33 @@
+use clawless::prelude::*;
+
+use crate::command_tree::CommandTree;
+use crate::project::find_clawless_project;
+
+/// Arguments for the `list` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct ListArgs {}
+
+/// List the commands of a Clawless project
+///
+/// This command reads the command tree of the project from the modules in `src/commands/`,
+/// without compiling the project. For every command it prints the name, aliases, and attributes,
+/// the file and line where the command is defined, and the first line of its documentation.
+/// Subcommands are indented below their parent.
+///
+/// Pass `--output json` to print the tree as JSON, e.g. for editor integrations.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless list
+/// clawless routes --output json
+/// ```
+#[command(alias = "routes")]
+pub async fn list(_args: ListArgs, context: Context) -> CommandResult {
+    let project = find_clawless_project(context.current_working_directory())?;
+    let tree = CommandTree::read(&project)?;
+
+    context.output().print(&tree)?;
+
+    Ok(())
+}
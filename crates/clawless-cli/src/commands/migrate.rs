@@ -0,0 +1,26 @@
+//! Migration commands for existing applications
+
+use clawless::prelude::*;
+
+// The module is not named `clap`, since it would shadow the `clap` crate that `#[derive(Args)]`
+// refers to in this module
+mod from_clap;
+
+/// Arguments for the `migrate` command group
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct MigrateArgs {}
+
+/// Migrate existing applications to Clawless
+///
+/// This is a command group containing subcommands for different frameworks. Run with a
+/// subcommand to migrate an application that uses that framework.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless migrate clap
+/// ```
+#[command(require_subcommand)]
+pub async fn migrate(_args: MigrateArgs, _context: Context) -> CommandResult {
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use clawless::prelude::*;
+
+use crate::cargo::find_package;
+use crate::migration::Migration;
+
+/// Arguments for the `migrate clap` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct ClapArgs {}
+
+/// Generate Clawless commands from a clap application
+///
+/// This command reads the `#[derive(Parser)]` struct and the `#[derive(Subcommand)]` enums of an
+/// application that uses clap's derive API, and generates the equivalent `src/commands/` tree.
+/// Every variant becomes a command function, and its fields move into the `*Args` struct of the
+/// command. Doc comments, `#[arg]` attributes, names, and aliases are preserved. Variants with a
+/// `#[derive(Args)]` struct use the fields of the struct, and variants with their own
+/// `#[derive(Subcommand)]` enum become command groups.
+///
+/// The clap types and the code that handles them are not changed. Move the code of each variant
+/// into its command, replace the entry point with `clawless init --force`, and then remove the
+/// clap types. Arguments of the root command and options that Clawless does not support are
+/// reported, since they cannot be migrated automatically.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless migrate clap
+/// clawless init --force
+/// ```
+#[command]
+pub async fn clap(_args: ClapArgs, context: Context) -> CommandResult {
+    let project = find_package(context.current_working_directory().get())?;
+
+    let migration = Migration::plan(&project)?;
+    let written = migration.write(&project)?;
+
+    for path in written {
+        let path = path.strip_prefix(&project).unwrap_or(&path);
+        context
+            .output()
+            .success(format!("Created {}", path.display()));
+    }
+
+    for note in migration.notes() {
+        context.output().warning(note);
+    }
+
+    context.output().info(
+        "Move the code of each variant into its command, and run `clawless init --force` to replace the entry point",
+    );
+
+    Ok(())
+}
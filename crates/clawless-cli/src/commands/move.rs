@@ -0,0 +1,24 @@
+//! Commands that move code in Clawless projects
+
+use clawless::prelude::*;
+
+mod command;
+
+/// Arguments for the `move` command group
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct MoveArgs {}
+
+/// Move code in Clawless projects
+///
+/// This is a command group containing subcommands for moving code that was
+/// generated with `clawless generate`.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless move command greet admin/greet
+/// ```
+#[command(require_subcommand, alias = "mv")]
+pub async fn r#move(_args: MoveArgs, _context: Context) -> CommandResult {
+    Ok(())
+}
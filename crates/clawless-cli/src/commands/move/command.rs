@@ -0,0 +1,52 @@
+use clawless::prelude::*;
+
+use crate::input::CommandName;
+use crate::project::find_clawless_project;
+use crate::refactor::move_command;
+
+/// Arguments for the `move command` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct MoveCommandArgs {
+    /// Name of the command to move.
+    ///
+    /// Use slash notation for nested commands (e.g., "db/migrate").
+    name: String,
+
+    /// New location of the command (e.g., "admin/greet").
+    destination: String,
+}
+
+/// Move a command to another command group in a Clawless project
+///
+/// This command moves the file of the command and its subcommands, removes
+/// the `mod` statement from the old parent module, and adds it to the new
+/// one. If the last part of the destination differs from the name of the
+/// command, the command function and its `*Args` struct are renamed as well.
+/// Directories of command groups that become empty are removed.
+///
+/// The command group at the destination must exist. Create it with
+/// `clawless generate command --parents` if necessary.
+///
+/// # Examples
+///
+/// Move a command into a command group:
+/// ```shell
+/// clawless move command greet admin/greet
+/// ```
+///
+/// Move a nested command to the top level:
+/// ```shell
+/// clawless move command db/migrate migrate
+/// ```
+#[command(alias = "c")]
+pub async fn command(args: MoveCommandArgs, context: Context) -> CommandResult {
+    let project = find_clawless_project(context.current_working_directory())?;
+    let command_name = CommandName::try_from(&args.name)?;
+    let destination = CommandName::try_from(&args.destination)?;
+
+    for change in move_command(&project, &command_name, &destination)? {
+        context.output().success(change.relative_to(&project));
+    }
+
+    Ok(())
+}
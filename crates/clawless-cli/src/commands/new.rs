@@ -1,57 +1,190 @@
-use std::fs::{create_dir_all, write};
+use std::collections::BTreeMap;
+use std::fs::remove_dir_all;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use clawless::prelude::*;
-use indoc::indoc;
 use typed_fields::name;
 
+use crate::cargo::{
+    ManifestBackup, add_clawless_dependency, describe_package, find_workspace,
+    inherit_workspace_dependencies, run_cargo,
+};
+use crate::config::Config;
+use crate::input::ClawlessDependency;
+use crate::template::{DEFAULT_TEMPLATE, Template, Variables, resolve_path};
+
 name!(CrateName);
 
+/// The description of new projects, if none is passed with `--description`
+const DEFAULT_DESCRIPTION: &str = "A command-line application built with Clawless";
+
 /// Arguments for the `new` command
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
 pub struct NewArgs {
     /// Name of the new Clawless project to create
     name: CrateName,
+
+    /// Template for the project: `minimal`, `full`, `workspace`, a template from the
+    /// configuration, or the path of a template directory
+    #[arg(short, long)]
+    template: Option<String>,
+
+    /// Author of the project, which defaults to the Git user if the template uses `{{author}}`
+    #[arg(long)]
+    author: Option<String>,
+
+    /// Short description of the project
+    #[arg(long)]
+    description: Option<String>,
+
+    #[command(flatten)]
+    dependency: ClawlessDependency,
 }
 
 /// Create a new Clawless project with a complete setup
 ///
-/// This command creates a new binary crate using `cargo new`, adds the Clawless
-/// framework as a dependency, and sets up the project structure with example code
-/// to get you started quickly.
+/// This command creates a new binary crate using `cargo new`, writes the files of a
+/// template into it, and adds the Clawless framework as a dependency.
 ///
-/// The generated project includes:
-/// - A `main.rs` file with the Clawless entry point
-/// - A `commands.rs` module for organizing commands
-/// - A sample `greet` command demonstrating the framework
+/// The built-in templates are:
+/// - `minimal` - The Clawless entry point, a `commands.rs` module, and a sample `greet` command
+/// - `full` - The minimal setup with a configuration struct, a README, and a GitHub workflow
+/// - `workspace` - A Cargo workspace with the CLI and a separate crate for its business logic
+///
+/// A template can also be a directory, either passed as a path or configured by name in the
+/// `new.templates` table of the configuration, where relative paths are resolved against the
+/// directory of the configuration file. The default template can be changed with
+/// `clawless config set new.template <name>`. The variables `{{crate_name}}`,
+/// `{{crate_ident}}`, `{{author}}`, and `{{description}}` are replaced in the paths and contents
+/// of the files of a template. Templates without a `Cargo.toml` keep the manifest of `cargo new`,
+/// which gets the author and the description if they are passed explicitly.
+///
+/// Inside an existing Cargo workspace, the project is added as a member of the workspace, and its
+/// dependencies are declared in `[workspace.dependencies]` and inherited with
+/// `{ workspace = true }`. Dependencies that the workspace already declares keep the workspace's
+/// version.
+///
+/// Clawless is added from the registry by default. Pass `--clawless-path` to depend on a local
+/// checkout instead, or `--clawless-version` to pin a version. With `--offline`, `cargo` only uses
+/// crates that are already downloaded or vendored. If any step fails, the output of `cargo` is
+/// shown and the partially created project is removed.
 ///
 /// # Examples
 ///
 /// ```shell
 /// clawless new my-app
+/// clawless new my-app --template full --description "Deploy our services"
+/// clawless new my-app --template ~/templates/company
+/// clawless new my-app --offline --clawless-path ../clawless/crates/clawless
 /// ```
 #[command(alias = "n")]
 pub async fn new(args: NewArgs, context: Context) -> CommandResult {
-    // Call `cargo new` to create a new binary crate
-    let crate_path = create_binary_crate(&context, &args.name)?;
-
-    // Call `cargo add` to add Clawless as a dependency to the new crate
-    add_clawless_dependency(&crate_path)?;
+    let config: Config = context.config().deserialize()?;
+    let working_directory = context.current_working_directory().get();
+
+    // Find the template before the crate is created, so that a typo does not leave a crate behind
+    let template_name = args
+        .template
+        .or(config.new.template)
+        .unwrap_or_else(|| DEFAULT_TEMPLATE.into());
+    let user_templates = user_templates(&context, config.new.templates, working_directory);
+    let template = Template::find(&template_name, &user_templates, working_directory)?;
+
+    let workspace = find_workspace(working_directory)?;
+    if let Some(workspace) = &workspace
+        && template.creates_workspace()?
+    {
+        anyhow::bail!(
+            "the template `{template_name}` creates a workspace, which cannot be nested in the workspace at {}",
+            workspace.display()
+        );
+    }
 
-    // Update the main.rs file to use clawless
-    overwrite_main_rs(&crate_path)?;
+    // Only ask Git for the author if the template uses it
+    let author = args.author.or(config.new.author);
+    let default_author = match &author {
+        None if template.uses_variable("author")? => git_author(),
+        _ => String::new(),
+    };
+    let has_manifest = template.manifest()?.is_some();
+
+    let variables = Variables::builder()
+        .crate_name(args.name.get())
+        .author(author.clone().unwrap_or(default_author))
+        .description(
+            args.description
+                .clone()
+                .unwrap_or_else(|| DEFAULT_DESCRIPTION.into()),
+        )
+        .build();
+
+    // `cargo new` adds the crate to the members of a workspace, which is undone if a later step fails
+    let backup = workspace.as_deref().map(ManifestBackup::new).transpose()?;
+    let restore_workspace = || backup.as_ref().map_or(Ok(()), ManifestBackup::restore);
 
-    // Create src/commands.rs and initialize clawless
-    create_commands_rs(&crate_path)?;
+    // Call `cargo new` to create a new binary crate
+    let crate_path = match create_binary_crate(&context, &args.name, args.dependency.offline()) {
+        Ok(crate_path) => crate_path,
+        Err(error) => {
+            restore_workspace()?;
+            return Err(error);
+        }
+    };
+
+    let dependency = args.dependency.resolve(working_directory);
+    let result = template
+        .render(&crate_path, &variables)
+        .and_then(|_| {
+            if has_manifest {
+                return Ok(());
+            }
+            describe_package(&crate_path, author.as_deref(), args.description.as_deref())
+        })
+        .and_then(|_| add_clawless_dependency(&crate_path, &dependency))
+        .and_then(|_| match &workspace {
+            Some(workspace) => inherit_workspace_dependencies(workspace, &crate_path, |_| true),
+            None => Ok(Vec::new()),
+        })
+        .map(|_| ());
+
+    // Remove the partially created project, so that the command can be run again
+    if let Err(error) = result {
+        remove_dir_all(&crate_path).context(format!(
+            "failed to remove the partially created project at {}",
+            crate_path.display()
+        ))?;
+        restore_workspace()?;
+        context.output().warning(format!(
+            "Removed the partially created project at {}",
+            crate_path.display()
+        ));
+
+        return Err(error);
+    }
 
-    // Create src/commands/greet.rs with the greeting example
-    create_greeting_command(&crate_path)?;
+    if let Some(workspace) = workspace {
+        context.output().success(format!(
+            "Added {} to the workspace at {}",
+            args.name.get(),
+            workspace.display()
+        ));
+    }
 
     Ok(())
 }
 
-fn create_binary_crate(context: &Context, crate_name: &CrateName) -> Result<PathBuf, Error> {
+fn create_binary_crate(
+    context: &Context,
+    crate_name: &CrateName,
+    offline: bool,
+) -> Result<PathBuf, Error> {
+    let crate_path = context
+        .current_working_directory()
+        .get()
+        .join(crate_name.get());
+    let existed = crate_path.exists();
+
     let mut cargo_new_exec = Command::new("cargo");
 
     // Add the arguments to create a new binary crate
@@ -61,120 +194,76 @@ fn create_binary_crate(context: &Context, crate_name: &CrateName) -> Result<Path
         .arg("--bin")
         .arg(crate_name.get());
 
-    // TODO: If the command fails, we should capture the output and return it as part of the error
-    cargo_new_exec.stdout(Stdio::null()).stderr(Stdio::null());
-
-    // Run `cargo new` and check that it succeeded
-    if !cargo_new_exec
-        .status()
-        .context("failed to run `cargo new`")?
-        .success()
-    {
-        anyhow::bail!("failed to create new crate with `cargo new`");
+    if offline {
+        cargo_new_exec.arg("--offline");
     }
 
-    let crate_path = context
-        .current_working_directory()
-        .get()
-        .join(crate_name.get());
-
-    Ok(crate_path)
-}
-
-fn add_clawless_dependency(crate_path: &Path) -> Result<(), Error> {
-    let mut cargo_add_exec = Command::new("cargo");
-
-    // Add the arguments to add a new dependency
-    cargo_add_exec
-        .current_dir(crate_path)
-        .arg("add")
-        .arg("clawless");
-
-    // TODO: If the command fails, we should capture the output and return it as part of the error
-    cargo_add_exec.stdout(Stdio::null()).stderr(Stdio::null());
+    if let Err(error) = run_cargo(&mut cargo_new_exec, "cargo new") {
+        // `cargo new` can fail after creating the directory, e.g. when it cannot initialize Git
+        if !existed && crate_path.exists() {
+            remove_dir_all(&crate_path).context(format!(
+                "failed to remove the partially created crate at {}",
+                crate_path.display()
+            ))?;
+        }
 
-    // Run `cargo add` and check that it succeeded
-    if !cargo_add_exec
-        .status()
-        .context("failed to run `cargo add`")?
-        .success()
-    {
-        anyhow::bail!("failed to add clawless as a dependency with `cargo add`");
+        return Err(error.context("failed to create new crate with `cargo new`"));
     }
 
-    Ok(())
-}
-
-fn overwrite_main_rs(crate_path: &Path) -> Result<(), Error> {
-    let main_rs_path = crate_path.join("src").join("main.rs");
-
-    let main_rs_contents = indoc! {r#"
-        mod commands;
-
-        // Initialize and start the Clawless application
-        //
-        // This macro sets up the Clawless runtime, parses the command-line arguments, and then
-        // calls the appropriate command function based on the user's input.
-        clawless::main!();
-    "#};
-
-    write(&main_rs_path, main_rs_contents).context("failed to overwrite main.rs")?;
-
-    Ok(())
+    Ok(crate_path)
 }
 
-fn create_commands_rs(crate_path: &Path) -> Result<(), Error> {
-    let commands_rs_path = crate_path.join("src").join("commands.rs");
-
-    let commands_rs_contents = indoc! {r#"
-        mod greet;
-
-        // Collect the commands of the application
-        //
-        // This macro collects all the command functions defined in this module and its sub-modules,
-        // and registers them with the Clawless runtime so they can be invoked from the command line.
-        clawless::commands!();
-    "#};
-
-    write(&commands_rs_path, commands_rs_contents).context("failed to create commands.rs")?;
-
-    Ok(())
+/// Resolve the paths of the templates from the configuration
+///
+/// Relative paths are resolved against the directory of the configuration file that declares them,
+/// so that a project can ship its templates next to its `.clawless-cli.toml`.
+fn user_templates(
+    context: &Context,
+    templates: BTreeMap<String, PathBuf>,
+    working_directory: &Path,
+) -> BTreeMap<String, PathBuf> {
+    templates
+        .into_iter()
+        .map(|(name, path)| {
+            let file = context
+                .config()
+                .get(&format!("new.templates.{name}"))
+                .and_then(|entry| entry.path().clone());
+            let base = file
+                .as_deref()
+                .and_then(Path::parent)
+                .unwrap_or(working_directory);
+
+            let path = resolve_path(&path, base);
+            (name, path)
+        })
+        .collect()
 }
 
-fn create_greeting_command(crate_path: &Path) -> Result<(), Error> {
-    let commands_dir_path = crate_path.join("src").join("commands");
-    create_dir_all(&commands_dir_path).context("failed to create directory for commands module")?;
-
-    let greet_rs_path = commands_dir_path.join("greet.rs");
-
-    let greet_rs_contents = indoc! {r#"
-        use clawless::prelude::*;
-
-        #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
-        pub struct GreetArgs {
-            /// Name of the person to greet
-            #[arg(default_value = "World")]
-            name: String,
-        }
-
-        #[command]
-        pub async fn greet(args: GreetArgs) -> CommandResult {
-            // Print the greeting to the console
-            println!("Hello, {}!", args.name);
-
-            // Exit the CLI successfully
-            Ok(())
-        }
-    "#};
-
-    write(&greet_rs_path, greet_rs_contents).context("failed to create greet.rs")?;
-
-    Ok(())
+/// Returns the name and email of the Git user, or an empty string if Git is not configured
+fn git_author() -> String {
+    let git_config = |key: &str| {
+        Command::new("git")
+            .args(["config", "--get", key])
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    match (git_config("user.name"), git_config("user.email")) {
+        (Some(name), Some(email)) => format!("{name} <{email}>"),
+        (Some(name), None) => name,
+        _ => String::new(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::{create_dir_all, read_to_string};
+    use std::fs::create_dir_all;
 
     use tempfile::TempDir;
 
@@ -190,7 +279,7 @@ mod tests {
 
         let crate_name = CrateName::new("my_crate");
 
-        let crate_path = create_binary_crate(&context, &crate_name).unwrap();
+        let crate_path = create_binary_crate(&context, &crate_name, false).unwrap();
 
         assert!(crate_path.exists());
     }
@@ -208,69 +297,35 @@ mod tests {
 
         let crate_name = CrateName::new("crate-that-already-exists");
 
-        assert!(create_binary_crate(&context, &crate_name).is_err());
+        let error = create_binary_crate(&context, &crate_name, false).unwrap_err();
+
+        // The error includes the output of `cargo new`, and the existing directory is kept
+        assert!(format!("{error:#}").contains("already exists"));
+        assert!(cwd.path().join("crate-that-already-exists").exists());
     }
 
     #[test]
-    fn add_clawless_dependency_adds_dependency() {
-        let cwd = TempDir::new().unwrap();
-
+    fn user_templates_resolves_paths_relative_to_configuration_file() {
+        let file = clawless::config::ConfigFile::parse(
+            Path::new("/project/.clawless-cli.toml"),
+            "[new.templates]\ncompany = \"templates/company\"\n".into(),
+        )
+        .unwrap();
+        let config = clawless::config::Config::builder()
+            .project_file(file)
+            .build();
         let context = Context::builder()
-            .current_working_directory(cwd.path())
+            .current_working_directory(Path::new("/project/crates/app"))
+            .config(config)
             .build();
+        let templates =
+            BTreeMap::from([("company".to_string(), PathBuf::from("templates/company"))]);
 
-        let crate_path = create_binary_crate(&context, &CrateName::new("my_crate")).unwrap();
-
-        add_clawless_dependency(&crate_path).unwrap();
-
-        let cargo_toml_contents = read_to_string(crate_path.join("Cargo.toml")).unwrap();
-
-        assert!(cargo_toml_contents.contains("clawless"));
-    }
-
-    #[test]
-    fn overwrite_main_rs_updates_file() {
-        let cwd = TempDir::new().unwrap();
-        let main_rs_path = cwd.path().join("src").join("main.rs");
-
-        // Create a dummy crate structure and main.rs file
-        create_dir_all(cwd.path().join("src")).unwrap();
-        write(&main_rs_path, "// dummy main.rs").unwrap();
-
-        overwrite_main_rs(cwd.path()).unwrap();
-
-        let main_rs_contents = read_to_string(main_rs_path).unwrap();
-
-        assert!(main_rs_contents.contains("clawless::main!();"));
-    }
-
-    #[test]
-    fn create_commands_rs_creates_file() {
-        let cwd = TempDir::new().unwrap();
-        let src_path = cwd.path().join("src");
-
-        // Create the src directory
-        create_dir_all(&src_path).unwrap();
-
-        create_commands_rs(cwd.path()).unwrap();
-
-        let commands_rs_contents = read_to_string(src_path.join("commands.rs")).unwrap();
-
-        assert!(commands_rs_contents.contains("clawless::commands!();"));
-    }
-
-    #[test]
-    fn create_greeting_command_creates_file() {
-        let cwd = TempDir::new().unwrap();
-        let commands_dir_path = cwd.path().join("src").join("commands");
-
-        // Create the commands directory
-        create_dir_all(&commands_dir_path).unwrap();
-
-        create_greeting_command(cwd.path()).unwrap();
-
-        let greet_rs_contents = read_to_string(commands_dir_path.join("greet.rs")).unwrap();
+        let templates = user_templates(&context, templates, Path::new("/project/crates/app"));
 
-        assert!(greet_rs_contents.contains("pub async fn greet"));
+        assert_eq!(
+            Some(&PathBuf::from("/project/templates/company")),
+            templates.get("company")
+        );
     }
 }
@@ -0,0 +1,24 @@
+//! Commands that rename code in Clawless projects
+
+use clawless::prelude::*;
+
+mod command;
+
+/// Arguments for the `rename` command group
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct RenameArgs {}
+
+/// Rename code in Clawless projects
+///
+/// This is a command group containing subcommands for renaming code that was
+/// generated with `clawless generate`.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless rename command greet hello
+/// ```
+#[command(require_subcommand)]
+pub async fn rename(_args: RenameArgs, _context: Context) -> CommandResult {
+    Ok(())
+}
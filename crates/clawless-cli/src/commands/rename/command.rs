@@ -0,0 +1,57 @@
+use anyhow::bail;
+use clawless::prelude::*;
+
+use crate::input::CommandName;
+use crate::project::find_clawless_project;
+use crate::refactor::move_command;
+
+/// Arguments for the `rename command` command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+pub struct RenameCommandArgs {
+    /// Name of the command to rename.
+    ///
+    /// Use slash notation for nested commands (e.g., "db/migrate").
+    name: String,
+
+    /// New name of the command, without its parent commands.
+    new_name: String,
+}
+
+/// Rename a command in a Clawless project
+///
+/// This command renames the file of the command, its `mod` statement in the
+/// parent module, the command function, and its `*Args` struct. The
+/// subcommands of the command are moved with it.
+///
+/// Use `clawless move command` to move a command to another command group.
+///
+/// # Examples
+///
+/// ```shell
+/// clawless rename command greet hello
+/// clawless rename command db/migrate upgrade
+/// ```
+#[command(alias = "c")]
+pub async fn command(args: RenameCommandArgs, context: Context) -> CommandResult {
+    let project = find_clawless_project(context.current_working_directory())?;
+    let command_name = CommandName::try_from(&args.name)?;
+
+    let new_name = CommandName::try_from(&args.new_name)?;
+    if !new_name.parent_modules().is_empty() {
+        bail!(
+            "the new name `{}` must not contain parent commands; use `clawless move command` to move a command",
+            args.new_name
+        );
+    }
+
+    let new_name = CommandName::builder()
+        .name(new_name.name())
+        .parent_modules(command_name.parent_modules().clone())
+        .build();
+
+    for change in move_command(&project, &command_name, &new_name)? {
+        context.output().success(change.relative_to(&project));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! Configuration of the Clawless CLI
+//!
+//! The CLI reads its configuration from the user's configuration file, e.g.
+//! `~/.config/clawless-cli/config.toml` on Linux, and from `.clawless-cli.toml` files in the
+//! working directory or its parents. The settings can be changed with `clawless config set`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of the Clawless CLI
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Settings for `clawless new`
+    pub new: NewConfig,
+}
+
+/// Settings for creating new projects
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct NewConfig {
+    /// The template that is used when `--template` is not passed
+    pub template: Option<String>,
+
+    /// The author of new projects, which defaults to the Git user
+    pub author: Option<String>,
+
+    /// Templates that can be passed to `--template` by name, with the path to their directory
+    pub templates: BTreeMap<String, PathBuf>,
+}
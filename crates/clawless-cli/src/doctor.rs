@@ -0,0 +1,784 @@
+//! Checks for problems in the structure of a Clawless project
+//!
+//! The macros of Clawless can only see the module that they are used in, so they cannot detect
+//! problems that span several files, e.g. a command file that is never declared with `mod`. The
+//! doctor reads the whole project with `syn` and reports these problems with their location. Some
+//! of them can be fixed automatically.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::{read_dir, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use getset::Getters;
+use semver::{Version, VersionReq};
+use serde::{Serialize, Serializer};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Attribute, File, FnArg, Item, ItemFn, Type};
+use toml_edit::{Document, DocumentMut, value};
+
+use crate::command_tree::{CommandEntry, command_functions};
+use crate::source::{insert_mod, remove_mod};
+
+/// A problem in a project
+#[derive(Clone, Eq, PartialEq, Debug, Getters, Serialize)]
+pub struct Problem {
+    /// The file or directory with the problem, relative to the project
+    #[getset(get = "pub")]
+    path: PathBuf,
+
+    /// The line of the problem, if it is located in a file
+    #[getset(get = "pub")]
+    line: Option<usize>,
+
+    /// A description of the problem
+    #[getset(get = "pub")]
+    message: String,
+
+    /// The change that fixes the problem, if it can be fixed automatically
+    #[getset(get = "pub")]
+    #[serde(rename = "fixable", serialize_with = "serialize_fixable")]
+    fix: Option<Fix>,
+}
+
+/// The problems that remain in a project, which the `doctor` command prints as its result
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Problems(pub Vec<Problem>);
+
+/// A change that fixes a problem
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Fix {
+    /// Declare a module in its parent module
+    DeclareModule { parent: PathBuf, name: String },
+
+    /// Remove the declaration of a module whose file does not exist
+    RemoveModule { parent: PathBuf, name: String },
+
+    /// Call `clawless::commands!()` at the end of the commands module
+    CallCommandsMacro { module: PathBuf },
+
+    /// Change the version requirement of the `clawless` dependency
+    UpdateClawlessVersion { manifest: PathBuf, version: String },
+}
+
+impl Problem {
+    fn new(project: &Path, path: &Path, line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            path: path
+                .strip_prefix(project)
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|_| path.to_path_buf()),
+            line,
+            message: message.into(),
+            fix: None,
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}: {}", self.path.display(), self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl Display for Problems {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lines: Vec<String> = self.0.iter().map(Problem::to_string).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Serialize the fix of a problem as whether the problem can be fixed automatically
+fn serialize_fixable<S>(fix: &Option<Fix>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_bool(fix.is_some())
+}
+
+impl Fix {
+    /// Apply the fix to the files of the project
+    pub fn apply(&self) -> Result<()> {
+        match self {
+            Fix::DeclareModule { parent, name } => {
+                update_file(parent, |content| insert_mod(content, name))
+            }
+            Fix::RemoveModule { parent, name } => {
+                update_file(parent, |content| remove_mod(content, name))
+            }
+            Fix::CallCommandsMacro { module } => update_file(module, |content| {
+                let separator = if content.is_empty() || content.ends_with("\n\n") {
+                    ""
+                } else if content.ends_with('\n') {
+                    "\n"
+                } else {
+                    "\n\n"
+                };
+
+                Ok(format!("{content}{separator}clawless::commands!();\n"))
+            }),
+            Fix::UpdateClawlessVersion { manifest, version } => update_file(manifest, |content| {
+                let mut document = content.parse::<DocumentMut>()?;
+
+                let dependency = &mut document["dependencies"]["clawless"];
+                if dependency.is_str() {
+                    *dependency = value(version.as_str());
+                } else {
+                    dependency["version"] = value(version.as_str());
+                }
+
+                Ok(document.to_string())
+            }),
+        }
+    }
+}
+
+/// Check a project for problems
+///
+/// The problems are ordered by the path of the file, and by their line within the file.
+pub fn diagnose(project: &Path) -> Result<Vec<Problem>> {
+    let src = project.join("src");
+
+    let module = [src.join("commands.rs"), src.join("commands").join("mod.rs")]
+        .into_iter()
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            anyhow!("failed to find the commands module at src/commands.rs or src/commands/mod.rs")
+        })?;
+
+    let mut doctor = Doctor {
+        project,
+        problems: Vec::new(),
+    };
+
+    doctor.check_module(&module, &src.join("commands"), true)?;
+    doctor.check_commands_outside_of_tree(&src)?;
+    doctor.check_clawless_version()?;
+
+    let mut problems = doctor.problems;
+    problems.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+
+    Ok(problems)
+}
+
+struct Doctor<'a> {
+    project: &'a Path,
+    problems: Vec<Problem>,
+}
+
+impl Doctor<'_> {
+    fn report(
+        &mut self,
+        path: &Path,
+        line: Option<usize>,
+        message: impl Into<String>,
+    ) -> &mut Problem {
+        self.problems
+            .push(Problem::new(self.project, path, line, message));
+
+        self.problems.last_mut().expect("a problem was just added")
+    }
+
+    /// Check a module and its submodules, and return the commands that the module defines
+    ///
+    /// The files of the submodules are in `directory`, either as `name.rs` or as `name/mod.rs`.
+    fn check_module(
+        &mut self,
+        path: &Path,
+        directory: &Path,
+        root: bool,
+    ) -> Result<Vec<CommandEntry>> {
+        let Some(file) = self.parse(path)? else {
+            return Ok(Vec::new());
+        };
+
+        let functions = command_functions(&file);
+        for function in &functions {
+            self.check_argument_docs(&file, function, path);
+        }
+
+        let relative_path = self.relative(path);
+        let commands = functions
+            .into_iter()
+            .map(|function| CommandEntry::new(function, relative_path.clone(), Vec::new()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut declared = Vec::new();
+        let mut subcommands = Vec::new();
+
+        for item in &file.items {
+            let Item::Mod(module) = item else {
+                continue;
+            };
+
+            // Inline modules have no file, and modules with a `#[path]` can be anywhere
+            if module.content.is_some() || has_attribute(&module.attrs, "path") {
+                continue;
+            }
+
+            let name = module.ident.unraw().to_string();
+            let line = module.ident.span().start().line;
+            declared.push(name.clone());
+
+            match module_file(directory, &name) {
+                Some(module_path) => {
+                    subcommands.extend(self.check_module(
+                        &module_path,
+                        &directory.join(&name),
+                        false,
+                    )?);
+                }
+                None => {
+                    let message = format!(
+                        "the module `{name}` is declared, but neither {name}.rs nor {name}/mod.rs exists in {}",
+                        self.relative(directory).display()
+                    );
+
+                    self.report(path, Some(line), message).fix = Some(Fix::RemoveModule {
+                        parent: path.to_path_buf(),
+                        name,
+                    });
+                }
+            }
+        }
+
+        self.check_undeclared_modules(path, directory, &declared)?;
+        self.check_duplicate_names(&subcommands);
+
+        if !subcommands.is_empty() {
+            if root && !calls_commands_macro(&file) {
+                self.report(
+                    path,
+                    None,
+                    "the module declares commands, but does not call `clawless::commands!()`",
+                )
+                .fix = Some(Fix::CallCommandsMacro {
+                    module: path.to_path_buf(),
+                });
+            } else if !root && commands.is_empty() {
+                self.report(
+                    path,
+                    None,
+                    "the module declares subcommands, but does not define a `#[command]` function for them",
+                );
+            }
+        }
+
+        Ok(commands)
+    }
+
+    /// Report the files and directories in the directory of a module that are not declared
+    fn check_undeclared_modules(
+        &mut self,
+        parent: &Path,
+        directory: &Path,
+        declared: &[String],
+    ) -> Result<()> {
+        if !directory.is_dir() {
+            return Ok(());
+        }
+
+        let mut entries = read_dir(directory)
+            .context(format!("failed to read {}", directory.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+
+        for entry in entries {
+            let name = if entry.is_dir() {
+                entry.file_name()
+            } else if entry.extension().is_some_and(|extension| extension == "rs") {
+                entry.file_stem()
+            } else {
+                continue;
+            };
+
+            let Some(name) = name.and_then(|name| name.to_str()).map(str::to_string) else {
+                continue;
+            };
+
+            if name == "mod" || declared.contains(&name) {
+                continue;
+            }
+
+            if entry.is_dir() {
+                // The directory belongs to the file of the module, which is reported instead
+                if directory.join(format!("{name}.rs")).exists() {
+                    continue;
+                }
+
+                if !entry.join("mod.rs").exists() {
+                    self.report(
+                        &entry,
+                        None,
+                        format!("the directory has neither a module file {name}.rs nor a mod.rs"),
+                    );
+                    continue;
+                }
+            }
+
+            let message = format!(
+                "the module `{name}` is not declared with `mod {name};` in {}",
+                self.relative(parent).display()
+            );
+            let path = if entry.is_dir() {
+                entry.join("mod.rs")
+            } else {
+                entry
+            };
+
+            self.report(&path, None, message).fix = Some(Fix::DeclareModule {
+                parent: parent.to_path_buf(),
+                name,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Report commands in a command group whose names or aliases are already used by a sibling
+    fn check_duplicate_names(&mut self, commands: &[CommandEntry]) {
+        let mut names: HashMap<&String, &CommandEntry> = HashMap::new();
+
+        for command in commands {
+            let aliases = command.aliases().iter().map(|alias| (alias, "alias"));
+
+            for (name, kind) in std::iter::once((command.name(), "name")).chain(aliases) {
+                if let Some(other) = names.get(name) {
+                    let message = format!(
+                        "the {kind} `{name}` of the command `{}` is already used by the command `{}` at {}:{}",
+                        command.name(),
+                        other.name(),
+                        other.file().display(),
+                        other.line()
+                    );
+
+                    let path = self.project.join(command.file());
+                    self.report(&path, Some(*command.line()), message);
+                } else {
+                    names.insert(name, command);
+                }
+            }
+        }
+    }
+
+    /// Report fields of the arguments struct of a command that have no doc comment
+    ///
+    /// The doc comment of a field is its help text on the command line. Only structs that are
+    /// defined in the same file as the command are checked.
+    fn check_argument_docs(&mut self, file: &File, function: &ItemFn, path: &Path) {
+        let Some(FnArg::Typed(argument)) = function.sig.inputs.first() else {
+            return;
+        };
+        let Type::Path(ty) = argument.ty.as_ref() else {
+            return;
+        };
+        let Some(name) = ty.path.segments.last().map(|segment| &segment.ident) else {
+            return;
+        };
+
+        let structure = file.items.iter().find_map(|item| match item {
+            Item::Struct(structure) if &structure.ident == name => Some(structure),
+            _ => None,
+        });
+
+        let Some(structure) = structure else {
+            return;
+        };
+
+        for field in &structure.fields {
+            // Flattened structs and subcommands are documented where they are defined
+            if has_attribute(&field.attrs, "doc") || has_attribute(&field.attrs, "command") {
+                continue;
+            }
+
+            let field_name = field
+                .ident
+                .as_ref()
+                .map(|ident| ident.unraw().to_string())
+                .unwrap_or_else(|| "_".into());
+
+            self.report(
+                path,
+                Some(field.span().start().line),
+                format!("the argument `{field_name}` of `{name}` has no doc comment, so it has no help text"),
+            );
+        }
+    }
+
+    /// Report commands that are defined outside of `src/commands`
+    fn check_commands_outside_of_tree(&mut self, directory: &Path) -> Result<()> {
+        let commands = self.project.join("src").join("commands");
+
+        let mut entries = read_dir(directory)
+            .context(format!("failed to read {}", directory.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+
+        for entry in entries {
+            if entry == commands {
+                continue;
+            }
+
+            if entry.is_dir() {
+                self.check_commands_outside_of_tree(&entry)?;
+                continue;
+            }
+
+            if entry.extension().is_none_or(|extension| extension != "rs") {
+                continue;
+            }
+
+            // Files that cannot be parsed are reported by the compiler
+            let content =
+                read_to_string(&entry).context(format!("failed to read {}", entry.display()))?;
+            let Ok(file) = syn::parse_file(&content) else {
+                continue;
+            };
+
+            for function in command_functions(&file) {
+                let message = format!(
+                    "the command `{}` is defined outside of src/commands, so it is not part of the command tree",
+                    function.sig.ident.unraw()
+                );
+
+                self.report(
+                    &entry,
+                    Some(function.sig.ident.span().start().line),
+                    message,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Report a `clawless` dependency that does not match the version of the CLI
+    ///
+    /// The CLI generates code for the version of Clawless that it was released with.
+    fn check_clawless_version(&mut self) -> Result<()> {
+        let manifest = self.project.join("Cargo.toml");
+        let Ok(content) = read_to_string(&manifest) else {
+            return Ok(());
+        };
+
+        let document = Document::parse(content.as_str())
+            .context(format!("failed to parse {}", manifest.display()))?;
+
+        let Some(dependency) = document
+            .get("dependencies")
+            .and_then(|dependencies| dependencies.get("clawless"))
+        else {
+            return Ok(());
+        };
+
+        // Dependencies from a path or the workspace have no version requirement in the manifest
+        let Some(requirement) = dependency.as_str().or_else(|| {
+            dependency
+                .get("version")
+                .and_then(|version| version.as_str())
+        }) else {
+            return Ok(());
+        };
+
+        let line = dependency
+            .span()
+            .map(|span| content[..span.start].matches('\n').count() + 1);
+
+        let Ok(requirement) = VersionReq::parse(requirement) else {
+            self.report(
+                &manifest,
+                line,
+                format!("`{requirement}` is not a valid version requirement for clawless"),
+            );
+            return Ok(());
+        };
+
+        let version = Version::parse(env!("CARGO_PKG_VERSION"))?;
+        if requirement.matches(&version) {
+            return Ok(());
+        }
+
+        let message = format!(
+            "the clawless dependency `{requirement}` does not match the version {version} of the Clawless CLI"
+        );
+        self.report(&manifest, line, message).fix = Some(Fix::UpdateClawlessVersion {
+            manifest: manifest.clone(),
+            version: version.to_string(),
+        });
+
+        Ok(())
+    }
+
+    /// Parse a file, and report it if it cannot be parsed
+    fn parse(&mut self, path: &Path) -> Result<Option<File>> {
+        let content = read_to_string(path).context(format!("failed to read {}", path.display()))?;
+
+        match syn::parse_file(&content) {
+            Ok(file) => Ok(Some(file)),
+            Err(error) => {
+                let line = error.span().start().line;
+                self.report(
+                    path,
+                    Some(line),
+                    format!("failed to parse the file: {error}"),
+                );
+
+                Ok(None)
+            }
+        }
+    }
+
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(self.project)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// Returns the file of a module in a directory, which is either `<name>.rs` or `<name>/mod.rs`
+fn module_file(directory: &Path, name: &str) -> Option<PathBuf> {
+    [
+        directory.join(format!("{name}.rs")),
+        directory.join(name).join("mod.rs"),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+}
+
+fn has_attribute(attributes: &[Attribute], name: &str) -> bool {
+    attributes
+        .iter()
+        .any(|attribute| attribute.path().is_ident(name))
+}
+
+fn calls_commands_macro(file: &File) -> bool {
+    file.items.iter().any(|item| match item {
+        Item::Macro(item) => item
+            .mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "commands"),
+        _ => false,
+    })
+}
+
+fn update_file(path: &Path, update: impl FnOnce(&str) -> Result<String>) -> Result<()> {
+    let content = read_to_string(path).context(format!("failed to read {}", path.display()))?;
+    let content = update(&content).context(format!("failed to update {}", path.display()))?;
+
+    write(path, content).context(format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir_all;
+
+    use indoc::indoc;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const GREET: &str = indoc! {r#"
+        use clawless::prelude::*;
+
+        #[derive(Debug, Args)]
+        pub struct GreetArgs {
+            /// The name to greet
+            name: String,
+        }
+
+        #[command]
+        pub async fn greet(args: GreetArgs, context: Context) -> CommandResult {
+            Ok(())
+        }
+    "#};
+
+    fn project(commands: &str) -> TempDir {
+        let project = TempDir::new().unwrap();
+        create_dir_all(project.path().join("src").join("commands")).unwrap();
+
+        write(
+            project.path().join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"cli\"\n\n[dependencies]\nclawless = \"{}\"\n",
+                env!("CARGO_PKG_VERSION")
+            ),
+        )
+        .unwrap();
+        write(
+            project.path().join("src").join("main.rs"),
+            "mod commands;\n\nclawless::main!();\n",
+        )
+        .unwrap();
+        write(project.path().join("src").join("commands.rs"), commands).unwrap();
+        write(
+            project.path().join("src").join("commands").join("greet.rs"),
+            GREET,
+        )
+        .unwrap();
+
+        project
+    }
+
+    fn messages(problems: &[Problem]) -> Vec<String> {
+        problems.iter().map(Problem::to_string).collect()
+    }
+
+    #[test]
+    fn diagnose_finds_no_problems_in_healthy_project() {
+        let project = project("mod greet;\n\nclawless::commands!();\n");
+
+        assert!(diagnose(project.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diagnose_finds_undeclared_and_missing_modules() {
+        let project = project("mod deploy;\n\nclawless::commands!();\n");
+
+        let problems = diagnose(project.path()).unwrap();
+
+        assert_eq!(
+            vec![
+                "src/commands/greet.rs: the module `greet` is not declared with `mod greet;` in src/commands.rs",
+                "src/commands.rs:1: the module `deploy` is declared, but neither deploy.rs nor deploy/mod.rs exists in src/commands",
+            ],
+            messages(&problems)
+        );
+
+        for problem in &problems {
+            problem.fix().as_ref().unwrap().apply().unwrap();
+        }
+
+        assert_eq!(
+            "mod greet;\n\nclawless::commands!();\n",
+            read_to_string(project.path().join("src").join("commands.rs")).unwrap()
+        );
+        assert!(diagnose(project.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diagnose_finds_missing_commands_macro() {
+        let project = project("mod greet;\n");
+
+        let problems = diagnose(project.path()).unwrap();
+
+        assert_eq!(
+            vec![
+                "src/commands.rs: the module declares commands, but does not call `clawless::commands!()`"
+            ],
+            messages(&problems)
+        );
+
+        problems[0].fix().as_ref().unwrap().apply().unwrap();
+        assert_eq!(
+            "mod greet;\n\nclawless::commands!();\n",
+            read_to_string(project.path().join("src").join("commands.rs")).unwrap()
+        );
+    }
+
+    #[test]
+    fn diagnose_finds_duplicate_names() {
+        let project = project("mod greet;\nmod hello;\n\nclawless::commands!();\n");
+        write(
+            project.path().join("src").join("commands").join("hello.rs"),
+            GREET
+                .replace("#[command]", "#[command(alias = \"greet\")]")
+                .replace("fn greet", "fn hello"),
+        )
+        .unwrap();
+
+        let problems = diagnose(project.path()).unwrap();
+
+        assert_eq!(
+            vec![
+                "src/commands/hello.rs:10: the alias `greet` of the command `hello` is already used by the command `greet` at src/commands/greet.rs:10",
+            ],
+            messages(&problems)
+        );
+    }
+
+    #[test]
+    fn diagnose_finds_undocumented_arguments() {
+        let project = project("mod greet;\n\nclawless::commands!();\n");
+        write(
+            project.path().join("src").join("commands").join("greet.rs"),
+            GREET.replace("    /// The name to greet\n", ""),
+        )
+        .unwrap();
+
+        let problems = diagnose(project.path()).unwrap();
+
+        assert_eq!(
+            vec![
+                "src/commands/greet.rs:5: the argument `name` of `GreetArgs` has no doc comment, so it has no help text",
+            ],
+            messages(&problems)
+        );
+        assert!(problems[0].fix().is_none());
+    }
+
+    #[test]
+    fn diagnose_finds_commands_outside_of_commands_directory() {
+        let project = project("mod greet;\n\nclawless::commands!();\n");
+        write(project.path().join("src").join("deploy.rs"), GREET).unwrap();
+
+        let problems = diagnose(project.path()).unwrap();
+
+        assert_eq!(
+            vec![
+                "src/deploy.rs:10: the command `greet` is defined outside of src/commands, so it is not part of the command tree",
+            ],
+            messages(&problems)
+        );
+    }
+
+    #[test]
+    fn diagnose_finds_mismatched_clawless_version() {
+        let project = project("mod greet;\n\nclawless::commands!();\n");
+        let manifest = project.path().join("Cargo.toml");
+        write(
+            &manifest,
+            "[package]\nname = \"cli\"\n\n[dependencies]\nclawless = { version = \"0.1.0\" }\n",
+        )
+        .unwrap();
+
+        let problems = diagnose(project.path()).unwrap();
+
+        assert_eq!(
+            vec![format!(
+                "Cargo.toml:5: the clawless dependency `^0.1.0` does not match the version {} of the Clawless CLI",
+                env!("CARGO_PKG_VERSION")
+            )],
+            messages(&problems)
+        );
+
+        problems[0].fix().as_ref().unwrap().apply().unwrap();
+        assert!(read_to_string(&manifest).unwrap().contains(&format!(
+            "clawless = {{ version = \"{}\" }}",
+            env!("CARGO_PKG_VERSION")
+        )));
+    }
+
+    #[test]
+    fn problems_are_printed_one_per_line() {
+        let project = Path::new("/project");
+        let problems = Problems(vec![
+            Problem::new(project, Path::new("/project/src/main.rs"), Some(3), "first"),
+            Problem::new(project, Path::new("/project/src/commands"), None, "second"),
+        ]);
+
+        assert_eq!(
+            "src/main.rs:3: first\nsrc/commands: second",
+            problems.to_string()
+        );
+    }
+}
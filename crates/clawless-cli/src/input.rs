@@ -1,8 +1,13 @@
 //! Input types and parsers for the CLI
 //!
 //! This module contains types used for parsing and validating command-line
-//! input, such as command names with support for nested hierarchies.
+//! input, such as command names with support for nested hierarchies, the
+//! arguments of generated commands, and the options for the `clawless` dependency.
 
+pub use self::argument::*;
+pub use self::clawless_dependency::*;
 pub use self::command_name::*;
 
+mod argument;
+mod clawless_dependency;
 mod command_name;
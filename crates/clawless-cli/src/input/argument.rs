@@ -0,0 +1,112 @@
+use anyhow::{Context, Result, anyhow, bail};
+use convert_case::{Case, Casing};
+use getset::Getters;
+use typed_builder::TypedBuilder;
+
+const TYPE_SEPARATOR: char = ':';
+
+/// Represents an argument of a generated command
+///
+/// Arguments are passed to the generator as `name:Type`, e.g. `env:String` or
+/// `tags:Vec<String>`. The name is converted to snake case, and the type must be
+/// a valid Rust type.
+///
+/// # Examples
+///
+/// ```
+/// # use clawless_cli::input::Argument;
+/// // Argument: "env:String"
+/// let argument = Argument::builder()
+///     .name("env")
+///     .ty("String")
+///     .build();
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Getters, TypedBuilder)]
+pub struct Argument {
+    /// The name of the field in the arguments struct
+    #[builder(setter(into))]
+    #[getset(get = "pub")]
+    name: String,
+
+    /// The Rust type of the field
+    #[builder(setter(into))]
+    #[getset(get = "pub")]
+    ty: String,
+}
+
+impl Argument {
+    /// Creates a boolean flag with the given name
+    pub fn flag(name: &str) -> Result<Self> {
+        Ok(Self {
+            name: parse_name(name)?,
+            ty: "bool".into(),
+        })
+    }
+
+    /// Returns the name of the argument on the command line (e.g., "--dry-run")
+    pub fn long(&self) -> String {
+        format!("--{}", self.name.to_case(Case::Kebab))
+    }
+}
+
+impl TryFrom<&String> for Argument {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &String) -> Result<Self> {
+        let (name, ty) = value
+            .split_once(TYPE_SEPARATOR)
+            .ok_or_else(|| anyhow!("the argument `{value}` must have the form `name:Type`"))?;
+
+        let ty = ty.trim();
+        syn::parse_str::<syn::Type>(ty).context(format!(
+            "`{ty}` is not a valid type for the argument `{name}`"
+        ))?;
+
+        Ok(Self {
+            name: parse_name(name)?,
+            ty: ty.into(),
+        })
+    }
+}
+
+fn parse_name(name: &str) -> Result<String> {
+    let name = name.trim().to_case(Case::Snake);
+
+    if name.is_empty() {
+        bail!("the name of an argument must not be empty");
+    }
+
+    syn::parse_str::<syn::Ident>(&name)
+        .context(format!("`{name}` is not a valid name for an argument"))?;
+
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_parses_name_and_type() {
+        let argument = Argument::try_from(&"dry-run:Option<Vec<String>>".to_string()).unwrap();
+
+        assert_eq!("dry_run", argument.name());
+        assert_eq!("Option<Vec<String>>", argument.ty());
+        assert_eq!("--dry-run", argument.long());
+    }
+
+    #[test]
+    fn try_from_fails_without_type() {
+        assert!(Argument::try_from(&"env".to_string()).is_err());
+    }
+
+    #[test]
+    fn try_from_fails_for_invalid_type() {
+        assert!(Argument::try_from(&"env:Vec<".to_string()).is_err());
+    }
+
+    #[test]
+    fn flag_fails_for_keyword() {
+        assert!(Argument::flag("type").is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+use clawless::prelude::{Args, clap};
+use getset::{CopyGetters, Getters};
+use typed_builder::TypedBuilder;
+
+/// Options for the `clawless` dependency of a project
+///
+/// The options are shared by the commands that add Clawless to a crate, e.g. `new` and `init`.
+/// Clawless is added from the registry by default, but it can also be added from a local
+/// checkout or pinned to a version.
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    Args,
+    CopyGetters,
+    Getters,
+    TypedBuilder,
+)]
+pub struct ClawlessDependency {
+    /// Run `cargo` without accessing the network
+    #[arg(long)]
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    offline: bool,
+
+    /// Use a local checkout of Clawless instead of the version from the registry
+    #[arg(long, value_name = "DIR", conflicts_with = "clawless_version")]
+    #[builder(default, setter(strip_option, into))]
+    #[getset(get = "pub")]
+    clawless_path: Option<PathBuf>,
+
+    /// Version requirement for Clawless, which defaults to the latest version in the registry
+    #[arg(long, value_name = "VERSION")]
+    #[builder(default, setter(strip_option, into))]
+    #[getset(get = "pub")]
+    clawless_version: Option<String>,
+}
+
+impl ClawlessDependency {
+    /// Resolve the path of a local checkout relative to a directory
+    ///
+    /// The path is passed relative to the working directory, but `cargo add` runs in the crate.
+    pub fn resolve(&self, working_directory: &Path) -> Self {
+        Self {
+            clawless_path: self
+                .clawless_path
+                .as_ref()
+                .map(|path| working_directory.join(path)),
+            ..self.clone()
+        }
+    }
+}
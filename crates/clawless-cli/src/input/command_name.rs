@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
@@ -42,9 +43,14 @@ pub struct CommandName {
 }
 
 impl CommandName {
+    /// Returns the name of the module and function of this command (e.g., "say_hello")
+    pub fn module_name(&self) -> String {
+        self.name.to_case(Case::Snake)
+    }
+
     /// Returns the filename for this command (e.g., "greet.rs")
     pub fn filename(&self) -> String {
-        format!("{}.rs", self.name.to_case(Case::Snake))
+        format!("{}.rs", self.module_name())
     }
 
     /// Constructs the full file path for this command from the project root
@@ -62,6 +68,37 @@ impl CommandName {
 
         path.join(self.filename())
     }
+
+    /// Returns the command group that contains this command, if it is nested
+    ///
+    /// For the command "db/schema/migrate", the parent is "db/schema".
+    pub fn parent(&self) -> Option<CommandName> {
+        let (name, parent_modules) = self.parent_modules.split_last()?;
+
+        Some(Self {
+            name: name.clone(),
+            parent_modules: parent_modules.to_vec(),
+        })
+    }
+
+    /// Returns all command groups that contain this command, ordered from outermost to innermost
+    pub fn ancestors(&self) -> Vec<CommandName> {
+        let mut ancestors: Vec<CommandName> =
+            std::iter::successors(self.parent(), CommandName::parent).collect();
+        ancestors.reverse();
+
+        ancestors
+    }
+}
+
+impl Display for CommandName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for module in &self.parent_modules {
+            write!(f, "{module}{COMMAND_SEPARATOR}")?;
+        }
+
+        write!(f, "{}", self.name)
+    }
 }
 
 impl TryFrom<&String> for CommandName {
@@ -1,7 +1,16 @@
 #![cfg_attr(not(doctest),doc = include_str!("../README.md"))]
 #![warn(missing_docs)]
 
+mod cargo;
+mod command_tree;
 mod commands;
+mod config;
+mod doctor;
 mod input;
+mod migration;
+mod project;
+mod refactor;
+mod source;
+mod template;
 
 clawless::main!();
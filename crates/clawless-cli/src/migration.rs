@@ -0,0 +1,1246 @@
+//! Migration of clap applications to Clawless
+//!
+//! Applications that use clap's derive API describe their commands with a `#[derive(Parser)]`
+//! struct and `#[derive(Subcommand)]` enums. The migration reads these types with `syn`, and plans
+//! a `src/commands/` tree with a command function for every variant. The fields of a variant move
+//! into the `*Args` struct of its command, and are copied as text so that their doc comments and
+//! `#[arg]` attributes are preserved.
+//!
+//! The code that handled a variant cannot be moved automatically, since applications dispatch
+//! their commands in many different ways. Parts of the application that cannot be migrated are
+//! reported as notes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs::{create_dir_all, read_dir, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clawless::clap::{self, Args};
+use clawless::context::GlobalArgs;
+use convert_case::{Case, Casing};
+use getset::Getters;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Expr, Field, Fields, GenericArgument, Item, ItemEnum, ItemStruct, Lit, Meta,
+    PathArguments, Token, Type, UseTree, Variant,
+};
+
+use crate::source::{add_commands_option, ident, parse, span_text};
+use crate::template::COMMANDS_MODULE;
+
+/// The arguments of `#[command]` that clap and Clawless share
+const ALIAS_KEYS: [&str; 4] = ["alias", "visible_alias", "aliases", "visible_aliases"];
+
+/// The files and notes of a migration
+#[derive(Clone, Eq, PartialEq, Debug, Getters)]
+pub struct Migration {
+    /// The files to create, with paths relative to the project
+    #[getset(get = "pub")]
+    files: Vec<(PathBuf, String)>,
+
+    /// The parts of the application that were not migrated
+    #[getset(get = "pub")]
+    notes: Vec<String>,
+}
+
+impl Migration {
+    /// Plan the migration of the clap application in a project
+    pub fn plan(project: &Path) -> Result<Self> {
+        let sources = read_sources(project)?;
+        let mut planner = Planner::new(&sources);
+
+        let parsers: Vec<_> = sources
+            .iter()
+            .flat_map(|source| source.file.items.iter().map(move |item| (source, item)))
+            .filter(|(_, item)| match item {
+                Item::Struct(item) => derives(&item.attrs, "Parser"),
+                Item::Enum(item) => derives(&item.attrs, "Parser"),
+                _ => false,
+            })
+            .collect();
+
+        let (source, parser) = match parsers.as_slice() {
+            [parser] => *parser,
+            [] => bail!("failed to find a struct or enum with `#[derive(Parser)]` in src/"),
+            parsers => bail!(
+                "found more than one type with `#[derive(Parser)]`: {}",
+                parsers
+                    .iter()
+                    .map(|(source, item)| source.location(item.span().start().line))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        let mut modules = Vec::new();
+
+        match parser {
+            Item::Enum(item) => planner.plan_enum(source, item, &[], &mut modules)?,
+            Item::Struct(item) => {
+                let fields = fields(&item.fields);
+                let Some((subcommand, _)) = find_subcommand(&fields) else {
+                    bail!(
+                        "`{}` in {} has no field with `#[command(subcommand)]`, so there are no commands to migrate",
+                        item.ident,
+                        source.location(item.ident.span().start().line)
+                    );
+                };
+
+                for field in fields.iter().filter(|field| !is_subcommand(field)) {
+                    planner.notes.push(format!(
+                        "`{}` in `{}` ({}) is an argument of the root command, which was not migrated; move it into the commands that use it",
+                        field_name(field),
+                        item.ident,
+                        source.location(field.span().start().line)
+                    ));
+                }
+
+                let (source, subcommands) =
+                    planner.find_subcommands(&subcommand).ok_or_else(|| {
+                        anyhow!(
+                            "failed to find the `#[derive(Subcommand)]` enum `{subcommand}` in src/"
+                        )
+                    })?;
+                planner.plan_enum(source, subcommands, &[], &mut modules)?;
+            }
+            _ => unreachable!("only structs and enums are collected"),
+        }
+
+        let mut commands = String::new();
+        for module in &modules {
+            writeln!(commands, "mod {module};")?;
+        }
+        if !commands.is_empty() {
+            commands.push('\n');
+        }
+        commands.push_str(COMMANDS_MODULE);
+
+        // Global options whose names are used by a command would make the application panic
+        if !planner.shadowed.is_empty() {
+            let flags: Vec<String> = planner
+                .shadowed
+                .iter()
+                .map(|long| format!("\"{long}\""))
+                .collect();
+            commands = add_commands_option(
+                &commands,
+                "without_global_flags",
+                &format!("[{}]", flags.join(", ")),
+            )?;
+        }
+
+        planner
+            .files
+            .insert(0, (PathBuf::from("src").join("commands.rs"), commands));
+
+        Ok(Self {
+            files: planner.files,
+            notes: planner.notes,
+        })
+    }
+
+    /// Write the files of the migration, and return their paths
+    ///
+    /// Nothing is written if any of the files already exists.
+    pub fn write(&self, project: &Path) -> Result<Vec<PathBuf>> {
+        if let Some((path, _)) = self
+            .files
+            .iter()
+            .find(|(path, _)| project.join(path).exists())
+        {
+            bail!("the file {} already exists", project.join(path).display());
+        }
+
+        let mut written = Vec::new();
+
+        for (path, content) in &self.files {
+            let path = project.join(path);
+
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent).context(format!("failed to create {}", parent.display()))?;
+            }
+
+            write(&path, content).context(format!("failed to write {}", path.display()))?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
+
+/// A Rust file of the project that is being migrated
+struct Source {
+    /// The path of the file relative to the project
+    path: PathBuf,
+
+    /// The path of the module, e.g. `crate::cli`
+    module: String,
+
+    content: String,
+    file: syn::File,
+}
+
+impl Source {
+    fn location(&self, line: usize) -> String {
+        format!("{}:{line}", self.path.display())
+    }
+}
+
+/// The options of `#[command]` or `#[clap]` on a variant
+#[derive(Default)]
+struct CommandOptions {
+    name: Option<String>,
+    aliases: Vec<String>,
+    hidden_aliases: Vec<String>,
+    hide: bool,
+    about: Option<String>,
+    flatten: bool,
+    external_subcommand: bool,
+    unsupported: Vec<String>,
+}
+
+struct Planner<'a> {
+    subcommands: BTreeMap<String, (&'a Source, &'a ItemEnum)>,
+    args: BTreeMap<String, (&'a Source, &'a ItemStruct)>,
+    files: Vec<(PathBuf, String)>,
+    notes: Vec<String>,
+
+    /// The long names of the global options that commands use for their own options
+    shadowed: BTreeSet<String>,
+}
+
+impl<'a> Planner<'a> {
+    fn new(sources: &'a [Source]) -> Self {
+        let mut subcommands = BTreeMap::new();
+        let mut args = BTreeMap::new();
+
+        for source in sources {
+            for item in &source.file.items {
+                match item {
+                    Item::Enum(item) if derives(&item.attrs, "Subcommand") => {
+                        subcommands.insert(item.ident.to_string(), (source, item));
+                    }
+                    Item::Struct(item) if derives(&item.attrs, "Args") => {
+                        args.insert(item.ident.to_string(), (source, item));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            subcommands,
+            args,
+            files: Vec::new(),
+            notes: Vec::new(),
+            shadowed: BTreeSet::new(),
+        }
+    }
+
+    fn find_subcommands(&self, name: &str) -> Option<(&'a Source, &'a ItemEnum)> {
+        self.subcommands.get(name).copied()
+    }
+
+    /// Plan a command for every variant of a `Subcommand` enum
+    ///
+    /// The commands are created in the directory of their parent, and the names of their modules
+    /// are added to `modules`.
+    fn plan_enum(
+        &mut self,
+        source: &'a Source,
+        item: &'a ItemEnum,
+        parents: &[String],
+        modules: &mut Vec<String>,
+    ) -> Result<()> {
+        for variant in &item.variants {
+            let location = source.location(variant.ident.span().start().line);
+            let label = format!("{}::{}", item.ident, variant.ident);
+            let options = command_options(&variant.attrs).context(format!(
+                "failed to parse the attributes of `{label}` ({location})"
+            ))?;
+
+            if !options.unsupported.is_empty() {
+                self.notes.push(format!(
+                    "The options {} of `{label}` ({location}) are not supported by Clawless and were not migrated",
+                    options
+                        .unsupported
+                        .iter()
+                        .map(|option| format!("`{option}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+
+            if options.external_subcommand {
+                self.notes.push(format!(
+                    "`{label}` ({location}) is an external subcommand, which was not migrated"
+                ));
+                continue;
+            }
+
+            // The variants of a flattened enum are commands of the same parent
+            if options.flatten {
+                let flattened =
+                    single_type(&variant.fields).and_then(|name| self.find_subcommands(&name));
+                match flattened {
+                    Some((source, item)) => self.plan_enum(source, item, parents, modules)?,
+                    None => self.notes.push(format!(
+                        "The flattened subcommands of `{label}` ({location}) were not found and were not migrated"
+                    )),
+                }
+                continue;
+            }
+
+            // clap names commands in kebab case, while Clawless uses the name of the function
+            let command_name = options
+                .name
+                .clone()
+                .unwrap_or_else(|| variant.ident.to_string().to_case(Case::Kebab));
+            let name = command_name.to_case(Case::Snake);
+            let module = ident(&name);
+
+            if !options.hidden_aliases.is_empty() {
+                self.notes.push(format!(
+                    "The aliases {} of `{label}` ({location}) are hidden by clap, but are shown in the help by Clawless",
+                    options
+                        .hidden_aliases
+                        .iter()
+                        .map(|alias| format!("`{alias}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+
+            if modules.contains(&module) {
+                bail!("the command `{name}` ({location}) is defined more than once");
+            }
+
+            let command = self.plan_variant(source, variant, &label, &location)?;
+
+            let mut path: PathBuf = ["src", "commands"].iter().collect();
+            path.extend(parents);
+
+            let mut children = Vec::new();
+            if let Some((subcommand_source, subcommands, _)) = &command.subcommands {
+                let mut parents = parents.to_vec();
+                parents.push(name.clone());
+                self.plan_enum(subcommand_source, subcommands, &parents, &mut children)?;
+            }
+
+            let content = command_file(&name, &command_name, &options, &command, &children);
+            self.files.push((path.join(format!("{name}.rs")), content));
+            modules.push(module);
+        }
+
+        Ok(())
+    }
+
+    /// Find the fields, documentation, and subcommands of a variant
+    fn plan_variant(
+        &mut self,
+        source: &'a Source,
+        variant: &'a Variant,
+        label: &str,
+        location: &str,
+    ) -> Result<PlannedCommand<'a>> {
+        let mut command = PlannedCommand {
+            source,
+            fields: Vec::new(),
+            documentation: documentation(source, &variant.attrs),
+            subcommands: None,
+        };
+
+        let fields = match &variant.fields {
+            Fields::Named(_) => fields(&variant.fields),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => {
+                let Some(name) = single_type(&variant.fields) else {
+                    self.notes.push(format!(
+                        "The fields of `{label}` ({location}) were not migrated, since clap only supports a single type in a tuple variant"
+                    ));
+                    return Ok(command);
+                };
+
+                if let Some((source, item)) = self.find_subcommands(&name) {
+                    command.subcommands = Some((source, item, true));
+                    return Ok(command);
+                }
+
+                let Some((source, item)) = self.args.get(&name).copied() else {
+                    self.notes.push(format!(
+                        "The arguments of `{label}` ({location}) were not migrated, since the `#[derive(Args)]` struct `{name}` was not found"
+                    ));
+                    return Ok(command);
+                };
+
+                command.source = source;
+                if command.documentation.is_empty() {
+                    command.documentation = documentation(source, &item.attrs);
+                }
+
+                fields(&item.fields)
+            }
+        };
+
+        if let Some((name, required)) = find_subcommand(&fields) {
+            match self.find_subcommands(&name) {
+                Some((source, item)) => command.subcommands = Some((source, item, required)),
+                None => self.notes.push(format!(
+                    "The subcommands of `{label}` ({location}) were not migrated, since the `#[derive(Subcommand)]` enum `{name}` was not found"
+                )),
+            }
+        }
+
+        command.fields = fields
+            .into_iter()
+            .filter(|field| !is_subcommand(field))
+            .collect();
+
+        // Global options whose names are used by a command are left out, so they are reported here
+        let globals = global_options();
+        for field in &command.fields {
+            if let Some(long) = long_name(field).filter(|long| globals.contains(long)) {
+                self.notes.push(format!(
+                    "`--{long}` of `{label}` ({}) uses the name of a global option of Clawless, which is left out with `without_global_flags` in src/commands.rs; rename the option, or remove it and use the global option instead",
+                    command.source.location(field.span().start().line)
+                ));
+                self.shadowed.insert(long);
+            }
+        }
+
+        Ok(command)
+    }
+}
+
+/// A command that is created for a variant
+struct PlannedCommand<'a> {
+    /// The file that defines the fields of the command
+    source: &'a Source,
+
+    fields: Vec<&'a Field>,
+    documentation: Vec<String>,
+
+    /// The enum with the subcommands, and whether a subcommand is required
+    subcommands: Option<(&'a Source, &'a ItemEnum, bool)>,
+}
+
+/// Generate the file of a command
+fn command_file(
+    name: &str,
+    command_name: &str,
+    options: &CommandOptions,
+    command: &PlannedCommand,
+    children: &[String],
+) -> String {
+    let struct_name = format!("{}Args", name.to_case(Case::Pascal));
+    let function = ident(name);
+    let is_required_group = matches!(command.subcommands, Some((_, _, true)));
+
+    // Group the imports like rustfmt: the standard library, other crates, and the crate itself
+    let mut groups: [BTreeSet<String>; 3] = Default::default();
+    groups[1].insert("clawless::prelude::*".into());
+    for import in imports(command) {
+        let group = match import.split("::").next() {
+            Some("std" | "core" | "alloc") => 0,
+            Some("crate") => 2,
+            _ => 1,
+        };
+        groups[group].insert(import);
+    }
+
+    let mut content = groups
+        .iter()
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            group
+                .iter()
+                .map(|import| format!("use {import};\n"))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if !children.is_empty() {
+        content.push('\n');
+        for child in children {
+            content.push_str(&format!("mod {child};\n"));
+        }
+    }
+
+    content.push_str("\n#[derive(Clone, Debug, Args)]\n");
+    if command.fields.is_empty() {
+        content.push_str(&format!("pub struct {struct_name} {{}}\n"));
+    } else {
+        content.push_str(&format!("pub struct {struct_name} {{\n"));
+        let fields: Vec<String> = command
+            .fields
+            .iter()
+            .map(|field| field_text(command.source, field))
+            .collect();
+        content.push_str(&fields.join("\n"));
+        content.push_str("}\n");
+    }
+
+    content.push('\n');
+    if command.documentation.is_empty() {
+        if let Some(about) = &options.about {
+            content.push_str(&format!("/// {about}\n"));
+        }
+    } else {
+        for line in &command.documentation {
+            content.push_str(line);
+            content.push('\n');
+        }
+    }
+
+    let mut arguments = Vec::new();
+    if is_required_group {
+        arguments.push("require_subcommand".to_string());
+    }
+    if command_name != name {
+        arguments.push(format!("name = \"{command_name}\""));
+    }
+    arguments.extend(
+        options
+            .aliases
+            .iter()
+            .map(|alias| format!("alias = \"{alias}\"")),
+    );
+    if options.hide {
+        arguments.push("hide".to_string());
+    }
+    if arguments.is_empty() {
+        content.push_str("#[command]\n");
+    } else {
+        content.push_str(&format!("#[command({})]\n", arguments.join(", ")));
+    }
+
+    if is_required_group && command.fields.is_empty() {
+        content.push_str(&format!(
+            "pub async fn {function}(_args: {struct_name}, _context: Context) -> CommandResult {{\n    Ok(())\n}}\n"
+        ));
+    } else {
+        content.push_str(&format!(
+            "pub async fn {function}(args: {struct_name}, context: Context) -> CommandResult {{\n    // Command implementation goes here\n    Ok(())\n}}\n"
+        ));
+    }
+
+    content
+}
+
+/// Returns the `use` paths that the fields of a command need
+///
+/// The fields can refer to types that are imported into or defined in the file of the clap types.
+/// Only the imports whose names appear in the fields are kept.
+fn imports(command: &PlannedCommand) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for field in &command.fields {
+        collect_idents(field.to_token_stream(), &mut names);
+    }
+
+    let source = command.source;
+    let mut imports = BTreeSet::new();
+
+    for item in &source.file.items {
+        match item {
+            Item::Use(item) => {
+                let mut paths = Vec::new();
+                flatten_use_tree(&item.tree, Vec::new(), &mut paths);
+
+                for (segments, name, alias) in paths {
+                    let visible = alias.as_deref().unwrap_or(&name);
+                    if !names.contains(visible) {
+                        continue;
+                    }
+
+                    let path = resolve_path(&source.module, &segments, &name);
+                    imports.insert(match alias {
+                        Some(alias) => format!("{path} as {alias}"),
+                        None => path,
+                    });
+                }
+            }
+            Item::Struct(ItemStruct { ident, .. })
+            | Item::Enum(ItemEnum { ident, .. })
+            | Item::Type(syn::ItemType { ident, .. })
+            | Item::Const(syn::ItemConst { ident, .. })
+            | Item::Fn(syn::ItemFn {
+                sig: syn::Signature { ident, .. },
+                ..
+            }) if names.contains(&ident.to_string()) => {
+                imports.insert(format!("{}::{ident}", source.module));
+            }
+            _ => {}
+        }
+    }
+
+    imports
+}
+
+/// Flatten a `use` tree into its paths, as `(prefix, name, alias)`
+///
+/// Glob imports are skipped, since it is unknown which names they import.
+fn flatten_use_tree(
+    tree: &UseTree,
+    prefix: Vec<String>,
+    paths: &mut Vec<(Vec<String>, String, Option<String>)>,
+) {
+    match tree {
+        UseTree::Path(path) => {
+            let mut prefix = prefix;
+            prefix.push(path.ident.to_string());
+            flatten_use_tree(&path.tree, prefix, paths);
+        }
+        UseTree::Name(name) if name.ident == "self" => {
+            if let Some((name, prefix)) = prefix.split_last() {
+                paths.push((prefix.to_vec(), name.clone(), None));
+            }
+        }
+        UseTree::Name(name) => paths.push((prefix, name.ident.to_string(), None)),
+        UseTree::Rename(rename) => paths.push((
+            prefix,
+            rename.ident.to_string(),
+            Some(rename.rename.to_string()),
+        )),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten_use_tree(tree, prefix.clone(), paths);
+            }
+        }
+        UseTree::Glob(_) => {}
+    }
+}
+
+/// Make a path from a `use` statement absolute, so that it works in the commands module
+fn resolve_path(module: &str, segments: &[String], name: &str) -> String {
+    let mut path: Vec<String> = module.split("::").map(String::from).collect();
+
+    let mut segments = segments.iter().peekable();
+    match segments.peek().map(|segment| segment.as_str()) {
+        Some("self") => {
+            segments.next();
+        }
+        Some("super") => {
+            while segments.next_if(|segment| *segment == "super").is_some() {
+                path.pop();
+            }
+        }
+        // The prelude of Clawless re-exports clap, so that the application can drop its dependency
+        Some("clap") => path = vec!["clawless".into()],
+        Some(_) => path.clear(),
+        None => {}
+    }
+
+    path.extend(segments.cloned());
+    path.push(name.into());
+    path.join("::")
+}
+
+fn collect_idents(tokens: TokenStream, names: &mut BTreeSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                names.insert(ident.unraw().to_string());
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), names),
+            _ => {}
+        }
+    }
+}
+
+/// Returns the text of a field, indented for the body of a struct
+fn field_text(source: &Source, field: &Field) -> String {
+    let span = field.span();
+    let text = span_text(&source.content, span);
+    let indentation = span.start().column;
+
+    let mut lines: Vec<String> = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let line = if index == 0 {
+                line
+            } else {
+                let whitespace = line.len() - line.trim_start().len();
+                &line[whitespace.min(indentation)..]
+            };
+
+            format!("    {line}")
+        })
+        .collect();
+
+    if let Some(last) = lines.last_mut() {
+        last.push(',');
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Returns the doc comments of an item, as they are written in the source
+fn documentation(source: &Source, attributes: &[Attribute]) -> Vec<String> {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("doc"))
+        .map(|attribute| {
+            span_text(&source.content, attribute.span())
+                .trim()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Parse the options of `#[command]` and `#[clap]` attributes
+fn command_options(attributes: &[Attribute]) -> Result<CommandOptions> {
+    let mut options = CommandOptions::default();
+
+    for attribute in attributes
+        .iter()
+        .filter(|attribute| is_clap_attribute(attribute))
+    {
+        let Meta::List(_) = &attribute.meta else {
+            continue;
+        };
+
+        let arguments =
+            attribute.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+
+        for argument in arguments {
+            let key = argument
+                .path()
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+
+            match (key.as_str(), &argument) {
+                ("name", Meta::NameValue(meta)) => options.name = string(&meta.value),
+                ("about", Meta::NameValue(meta)) => options.about = string(&meta.value),
+                (key, Meta::NameValue(meta)) if ALIAS_KEYS.contains(&key) => {
+                    let aliases = strings(&meta.value);
+                    if !key.starts_with("visible_") {
+                        options.hidden_aliases.extend(aliases.iter().cloned());
+                    }
+                    options.aliases.extend(aliases);
+                }
+                ("hide", Meta::Path(_)) => options.hide = true,
+                ("hide", Meta::NameValue(meta)) => match boolean(&meta.value) {
+                    Some(hide) => options.hide = hide,
+                    None => options
+                        .unsupported
+                        .push(argument.to_token_stream().to_string()),
+                },
+                ("flatten", Meta::Path(_)) => options.flatten = true,
+                // Variants with a `Subcommand` enum are command groups either way
+                ("subcommand", Meta::Path(_)) => {}
+                ("external_subcommand", Meta::Path(_)) => options.external_subcommand = true,
+                _ => options
+                    .unsupported
+                    .push(argument.to_token_stream().to_string()),
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+/// Returns the long names of the global options of Clawless
+fn global_options() -> BTreeSet<String> {
+    GlobalArgs::augment_args(clap::Command::new("clawless"))
+        .get_arguments()
+        .filter_map(|argument| argument.get_long())
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the long name of the option of a field, if it has one
+fn long_name(field: &Field) -> Option<String> {
+    let arguments = field
+        .attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("arg") || attribute.path().is_ident("clap"))
+        .filter_map(|attribute| {
+            attribute
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten();
+
+    let mut long = None;
+    for argument in arguments {
+        match argument {
+            Meta::Path(path) if path.is_ident("long") => {
+                long = Some(field_name(field).to_case(Case::Kebab))
+            }
+            Meta::NameValue(meta) if meta.path.is_ident("long") => long = string(&meta.value),
+            _ => {}
+        }
+    }
+
+    long
+}
+
+fn is_clap_attribute(attribute: &Attribute) -> bool {
+    attribute.path().is_ident("command") || attribute.path().is_ident("clap")
+}
+
+/// Returns whether an item derives a trait, e.g. `#[derive(Parser)]` or `#[derive(clap::Parser)]`
+fn derives(attributes: &[Attribute], name: &str) -> bool {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("derive"))
+        .filter_map(|attribute| {
+            attribute
+                .parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == name)
+        })
+}
+
+fn fields(fields: &Fields) -> Vec<&Field> {
+    fields.iter().collect()
+}
+
+fn field_name(field: &Field) -> String {
+    field
+        .ident
+        .as_ref()
+        .map(|ident| ident.unraw().to_string())
+        .unwrap_or_else(|| "_".into())
+}
+
+fn is_subcommand(field: &Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|attribute| is_clap_attribute(attribute))
+        .filter_map(|attribute| {
+            attribute
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|meta| meta.path().is_ident("subcommand"))
+}
+
+/// Find the field with `#[command(subcommand)]`, and return its type and whether it is required
+fn find_subcommand(fields: &[&Field]) -> Option<(String, bool)> {
+    let field = fields.iter().find(|field| is_subcommand(field))?;
+
+    match type_name(&field.ty)? {
+        (name, Some(inner)) if name == "Option" => Some((inner, false)),
+        (name, _) => Some((name, true)),
+    }
+}
+
+/// Returns the type of a tuple variant with a single field
+fn single_type(fields: &Fields) -> Option<String> {
+    match fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            type_name(&fields.unnamed[0].ty).map(|(name, _)| name)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the name of a type, and the name of its first generic argument
+fn type_name(ty: &Type) -> Option<(String, Option<String>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+
+    let argument = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(ty) => type_name(ty).map(|(name, _)| name),
+                _ => None,
+            })
+        }
+        _ => None,
+    };
+
+    Some((segment.ident.to_string(), argument))
+}
+
+fn string(expression: &Expr) -> Option<String> {
+    match expression {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(lit) => Some(lit.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the value of a boolean literal
+fn boolean(expression: &Expr) -> Option<bool> {
+    match expression {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Bool(lit) => Some(lit.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the strings of a string literal or an array of string literals
+fn strings(expression: &Expr) -> Vec<String> {
+    match expression {
+        Expr::Array(array) => array.elems.iter().filter_map(string).collect(),
+        expression => string(expression).into_iter().collect(),
+    }
+}
+
+/// Read and parse the Rust files in the `src` directory of a project
+fn read_sources(project: &Path) -> Result<Vec<Source>> {
+    let src = project.join("src");
+    let mut sources = Vec::new();
+    let mut directories = vec![src.clone()];
+
+    while let Some(directory) = directories.pop() {
+        let entries =
+            read_dir(&directory).context(format!("failed to read {}", directory.display()))?;
+
+        for entry in entries {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                directories.push(path);
+                continue;
+            }
+
+            if path.extension().is_none_or(|extension| extension != "rs") {
+                continue;
+            }
+
+            let content =
+                read_to_string(&path).context(format!("failed to read {}", path.display()))?;
+            let file = parse(&content).context(format!("failed to parse {}", path.display()))?;
+            let relative = path.strip_prefix(project).unwrap_or(&path).to_path_buf();
+
+            sources.push(Source {
+                module: module_path(path.strip_prefix(&src).unwrap_or(&path)),
+                path: relative,
+                content,
+                file,
+            });
+        }
+    }
+
+    sources.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(sources)
+}
+
+/// Returns the path of the module of a file, e.g. `crate::cli` for `cli.rs` or `cli/mod.rs`
+fn module_path(path: &Path) -> String {
+    let mut segments = vec!["crate".to_string()];
+
+    let components: Vec<String> = path
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    for (index, component) in components.iter().enumerate() {
+        let is_last = index == components.len() - 1;
+        let is_root = index == 0 && (component == "main" || component == "lib");
+
+        if is_last && (component == "mod" || is_root) {
+            continue;
+        }
+
+        segments.push(component.clone());
+    }
+
+    segments.join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use quote::quote;
+    use syn::parse::Parser;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const CLI_RS: &str = indoc! {r#"
+        use std::path::PathBuf;
+
+        use clap::{Args, Parser, Subcommand, ValueEnum};
+
+        #[derive(Debug, Parser)]
+        pub struct Cli {
+            #[arg(short, long)]
+            pub debug: bool,
+
+            #[command(subcommand)]
+            pub command: Commands,
+        }
+
+        #[derive(Debug, Subcommand)]
+        pub enum Commands {
+            /// Deploy a service
+            #[command(alias = "d", visible_aliases = ["ship"])]
+            Deploy(DeployArgs),
+
+            /// Manage the database
+            #[command(subcommand)]
+            Db(DbCommands),
+
+            /// Move a file
+            #[command(name = "move", hide = true)]
+            Move {
+                /// The source
+                from: PathBuf,
+            },
+        }
+
+        #[derive(Debug, Args)]
+        pub struct DeployArgs {
+            /// The environment to deploy to
+            #[arg(long, value_enum)]
+            pub environment: Environment,
+
+            #[arg(short = 'y', long)]
+            pub yes: bool,
+        }
+
+        #[derive(Debug, Subcommand)]
+        pub enum DbCommands {
+            /// Seed the database
+            Seed,
+        }
+
+        #[derive(Clone, Debug, ValueEnum)]
+        pub enum Environment {
+            Staging,
+        }
+    "#};
+
+    fn project(files: &[(&str, &str)]) -> TempDir {
+        let project = TempDir::new().unwrap();
+
+        for (path, content) in files {
+            let path = project.path().join(path);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            write(path, content).unwrap();
+        }
+
+        project
+    }
+
+    fn file<'a>(migration: &'a Migration, path: &str) -> &'a str {
+        migration
+            .files()
+            .iter()
+            .find(|(file, _)| file == Path::new(path))
+            .map(|(_, content)| content.as_str())
+            .unwrap_or_else(|| panic!("{path} was not planned"))
+    }
+
+    #[test]
+    fn plan_creates_a_command_for_every_variant() {
+        let project = project(&[("src/main.rs", "mod cli;\n"), ("src/cli.rs", CLI_RS)]);
+
+        let migration = Migration::plan(project.path()).unwrap();
+
+        let paths: Vec<_> = migration.files().iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            vec![
+                Path::new("src/commands.rs"),
+                Path::new("src/commands/deploy.rs"),
+                Path::new("src/commands/db/seed.rs"),
+                Path::new("src/commands/db.rs"),
+                Path::new("src/commands/move.rs"),
+            ],
+            paths
+        );
+
+        assert_eq!(
+            format!(
+                "mod deploy;\nmod db;\nmod r#move;\n\n{}",
+                COMMANDS_MODULE
+                    .replace("commands!()", "commands!(without_global_flags = [\"yes\"])")
+            ),
+            file(&migration, "src/commands.rs")
+        );
+        assert_eq!(
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                use crate::cli::Environment;
+
+                #[derive(Clone, Debug, Args)]
+                pub struct DeployArgs {
+                    /// The environment to deploy to
+                    #[arg(long, value_enum)]
+                    pub environment: Environment,
+
+                    #[arg(short = 'y', long)]
+                    pub yes: bool,
+                }
+
+                /// Deploy a service
+                #[command(alias = "d", alias = "ship")]
+                pub async fn deploy(args: DeployArgs, context: Context) -> CommandResult {
+                    // Command implementation goes here
+                    Ok(())
+                }
+            "#},
+            file(&migration, "src/commands/deploy.rs")
+        );
+        assert_eq!(
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                mod seed;
+
+                #[derive(Clone, Debug, Args)]
+                pub struct DbArgs {}
+
+                /// Manage the database
+                #[command(require_subcommand)]
+                pub async fn db(_args: DbArgs, _context: Context) -> CommandResult {
+                    Ok(())
+                }
+            "#},
+            file(&migration, "src/commands/db.rs")
+        );
+        assert!(
+            file(&migration, "src/commands/move.rs")
+                .contains("use std::path::PathBuf;\n\nuse clawless::prelude::*;\n")
+        );
+        assert!(
+            file(&migration, "src/commands/move.rs").contains(
+                "#[command(hide)]\npub async fn r#move(args: MoveArgs, context: Context)"
+            )
+        );
+    }
+
+    #[test]
+    fn plan_keeps_the_names_of_commands() {
+        let cli = indoc! {r#"
+            use clap::{Parser, Subcommand};
+
+            #[derive(Parser)]
+            pub struct Cli {
+                #[command(subcommand)]
+                pub command: Commands,
+            }
+
+            #[derive(Subcommand)]
+            pub enum Commands {
+                DryRun,
+                #[command(name = "ls")]
+                List,
+            }
+        "#};
+        let project = project(&[("src/main.rs", "mod cli;\n"), ("src/cli.rs", cli)]);
+
+        let migration = Migration::plan(project.path()).unwrap();
+
+        assert!(
+            file(&migration, "src/commands/dry_run.rs")
+                .contains("#[command(name = \"dry-run\")]\npub async fn dry_run(")
+        );
+        assert!(file(&migration, "src/commands/ls.rs").contains("#[command]\npub async fn ls("));
+    }
+
+    #[test]
+    fn plan_notes_what_was_not_migrated() {
+        let project = project(&[("src/main.rs", "mod cli;\n"), ("src/cli.rs", CLI_RS)]);
+
+        let migration = Migration::plan(project.path()).unwrap();
+
+        assert_eq!(3, migration.notes().len(), "{:?}", migration.notes());
+        assert!(migration.notes()[0].starts_with("`debug` in `Cli` (src/cli.rs:7)"));
+        assert!(migration.notes()[1].starts_with("The aliases `d` of `Commands::Deploy`"));
+        assert!(migration.notes()[2].starts_with("`--yes` of `Commands::Deploy`"));
+    }
+
+    #[test]
+    fn plan_requires_a_parser() {
+        let project = project(&[("src/main.rs", "fn main() {}\n")]);
+
+        let error = Migration::plan(project.path()).unwrap_err();
+
+        assert!(error.to_string().contains("derive(Parser)"), "{error}");
+    }
+
+    #[test]
+    fn write_refuses_to_overwrite_files() {
+        let project = project(&[
+            ("src/main.rs", "mod cli;\n"),
+            ("src/cli.rs", CLI_RS),
+            ("src/commands/db.rs", ""),
+        ]);
+
+        let migration = Migration::plan(project.path()).unwrap();
+        let error = migration.write(project.path()).unwrap_err();
+
+        assert!(error.to_string().contains("db.rs"), "{error}");
+        assert!(!project.path().join("src/commands.rs").exists());
+    }
+
+    #[test]
+    fn long_name_defaults_to_the_field_name() {
+        let field: Field = Field::parse_named
+            .parse2(quote! { #[arg(short, long)] dry_run: bool })
+            .unwrap();
+
+        assert_eq!(Some("dry-run".to_string()), long_name(&field));
+    }
+
+    #[test]
+    fn global_options_include_yes() {
+        let globals = global_options();
+
+        assert!(globals.contains("yes"));
+    }
+
+    #[test]
+    fn resolve_path_makes_paths_absolute() {
+        let segments = |path: &str| -> Vec<String> {
+            path.split("::")
+                .filter(|segment| !segment.is_empty())
+                .map(String::from)
+                .collect()
+        };
+
+        assert_eq!(
+            "crate::cli::types::Target",
+            resolve_path("crate::cli", &segments("self::types"), "Target")
+        );
+        assert_eq!(
+            "crate::Config",
+            resolve_path("crate::cli", &segments("super"), "Config")
+        );
+        assert_eq!(
+            "clawless::clap::ValueEnum",
+            resolve_path("crate::cli", &segments("clap"), "ValueEnum")
+        );
+        assert_eq!(
+            "std::path::PathBuf",
+            resolve_path("crate::cli", &segments("std::path"), "PathBuf")
+        );
+    }
+
+    #[test]
+    fn module_path_follows_the_module_tree() {
+        assert_eq!("crate", module_path(Path::new("main.rs")));
+        assert_eq!("crate::cli", module_path(Path::new("cli.rs")));
+        assert_eq!("crate::cli", module_path(Path::new("cli/mod.rs")));
+        assert_eq!("crate::cli::db", module_path(Path::new("cli/db.rs")));
+    }
+}
@@ -0,0 +1,287 @@
+//! The layout of Clawless projects
+//!
+//! Commands live in `src/commands/`, where every command is a module whose file is named after
+//! the command. Nested commands are modules in a directory that is named after their parent,
+//! e.g. `src/commands/db/migrate.rs` for `db migrate`.
+
+use std::fs::{read_dir, read_to_string, remove_dir};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use clawless::context::CurrentWorkingDirectory;
+use convert_case::{Case, Casing};
+
+use crate::input::CommandName;
+
+/// Find the root directory of the Clawless project that contains the working directory
+///
+/// The project is identified by a `main.rs` file that calls the `clawless::main!` macro.
+pub fn find_clawless_project(
+    current_working_directory: &CurrentWorkingDirectory,
+) -> Result<PathBuf> {
+    let main_rs_path = find_main_rs(current_working_directory)
+        .ok_or_else(|| anyhow!("failed to find a main.rs file in the current directory or any of its parent directories"))?;
+
+    check_main_rs(&main_rs_path)?;
+
+    let project_path = main_rs_path
+        .parent() // src
+        .and_then(Path::parent) // crate root
+        .ok_or_else(|| anyhow!("failed to determine the project directory from main.rs path"))?
+        .to_path_buf();
+
+    Ok(project_path)
+}
+
+fn find_main_rs(current_working_directory: &CurrentWorkingDirectory) -> Option<PathBuf> {
+    let mut dir = current_working_directory.get().to_path_buf();
+
+    loop {
+        // Check for src/main.rs first, then main.rs in the current directory
+        let src_main_rs = dir.join("src").join("main.rs");
+        if src_main_rs.exists() {
+            return Some(src_main_rs);
+        }
+
+        let main_rs = dir.join("main.rs");
+        if main_rs.exists() {
+            return Some(main_rs);
+        }
+
+        // Move up to parent directory, or return None if at filesystem root
+        dir = dir.parent()?.to_path_buf();
+    }
+}
+
+fn check_main_rs(path: &Path) -> Result<()> {
+    let content =
+        read_to_string(path).context(format!("failed to read main.rs at {}", path.display()))?;
+
+    if !content.contains("clawless::main!") {
+        anyhow::bail!(
+            "the main.rs file at '{}' does not contain the 'clawless::main!' macro, indicating that this is not a Clawless project",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the file of the module of a command, which is either `<command>.rs` or `<command>/mod.rs`
+pub fn find_command_module(project: &Path, command_name: &CommandName) -> Option<PathBuf> {
+    let file = command_name.path_from_project_root(project);
+    let directory_module = file.with_extension("").join("mod.rs");
+
+    [file, directory_module]
+        .into_iter()
+        .find(|path| path.exists())
+}
+
+/// Find the file of the module that declares a command
+pub fn find_parent_module(project: &Path, command_name: &CommandName) -> Result<PathBuf> {
+    let parent_modules = command_name.parent_modules();
+
+    // The parent module is either "commands" (if no nesting) or the last element in parent_modules
+    let commands_default = "commands".to_string();
+    let parent_name = parent_modules.last().unwrap_or(&commands_default);
+
+    // Determine the path to the parent module file (either as a file or mod.rs)
+    let mut base = project.join("src").join("commands");
+
+    // Navigate down to the parent's directory, excluding the parent itself
+    if parent_modules.len() > 1 {
+        for module in &parent_modules[..parent_modules.len() - 1] {
+            base = base.join(module.to_case(Case::Snake));
+        }
+    } else if parent_modules.is_empty() {
+        // Parent is src/commands itself
+        base = project.join("src");
+    }
+
+    let candidate_file = base.join(format!("{}.rs", parent_name.to_case(Case::Snake)));
+    let candidate_dir_mod = base.join(parent_name.to_case(Case::Snake)).join("mod.rs");
+
+    if candidate_file.exists() {
+        Ok(candidate_file)
+    } else if candidate_dir_mod.exists() {
+        Ok(candidate_dir_mod)
+    } else {
+        Err(anyhow!(
+            "parent module `{}` does not exist under `src/commands`; create it with `clawless generate command --parents`",
+            parent_name
+        ))
+    }
+}
+
+/// Remove empty directories from a directory up to `src/commands`
+///
+/// Returns the directories that were removed.
+pub fn remove_empty_directories(project: &Path, directory: &Path) -> Result<Vec<PathBuf>> {
+    let commands = project.join("src").join("commands");
+    let mut removed = Vec::new();
+    let mut directory = directory.to_path_buf();
+
+    while directory.starts_with(&commands) && directory != commands {
+        let is_empty = read_dir(&directory)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !is_empty {
+            break;
+        }
+
+        remove_dir(&directory).context(format!(
+            "failed to remove empty directory at {}",
+            directory.display()
+        ))?;
+        removed.push(directory.clone());
+
+        match directory.parent() {
+            Some(parent) => directory = parent.to_path_buf(),
+            None => break,
+        }
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, create_dir_all, write};
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn check_clawless_project_finds_main_in_src_directory() {
+        let cwd = TempDir::new().unwrap();
+
+        let src_directory = cwd.path().join("src");
+        create_dir(&src_directory).unwrap();
+
+        let main_rs_path = src_directory.join("main.rs");
+        write(&main_rs_path, "clawless::main!();").unwrap();
+
+        let check = find_clawless_project(&cwd.path().into()).unwrap();
+
+        assert_eq!(cwd.path(), &check);
+    }
+
+    #[test]
+    fn check_clawless_project_finds_main_in_directory() {
+        let cwd = TempDir::new().unwrap();
+
+        let src_directory = cwd.path().join("src");
+        create_dir(&src_directory).unwrap();
+
+        let main_rs_path = src_directory.join("main.rs");
+        write(&main_rs_path, "clawless::main!();").unwrap();
+
+        let check = find_clawless_project(&src_directory.as_path().into()).unwrap();
+
+        assert_eq!(cwd.path(), &check);
+    }
+
+    #[test]
+    fn check_clawless_project_finds_main_in_parent_directory() {
+        let cwd = TempDir::new().unwrap();
+
+        let src_directory = cwd.path().join("src");
+
+        // Create a subdirectory in which to call `check_clawless_project`
+        let sub_dir = src_directory.join("subdir");
+        create_dir_all(&sub_dir).unwrap();
+
+        let main_rs_path = src_directory.join("main.rs");
+        write(&main_rs_path, "clawless::main!();").unwrap();
+
+        let check = find_clawless_project(&sub_dir.as_path().into()).unwrap();
+
+        assert_eq!(cwd.path(), &check);
+    }
+
+    #[test]
+    fn check_clawless_project_fails_without_main_rs() {
+        let cwd = TempDir::new().unwrap();
+
+        let check = find_clawless_project(&cwd.path().into());
+
+        assert!(check.is_err());
+    }
+
+    #[test]
+    fn check_clawless_project_fails_without_clawless_macro() {
+        let cwd = TempDir::new().unwrap();
+
+        let main_rs_path = cwd.path().join("main.rs");
+        write(&main_rs_path, "fn main() {}").unwrap();
+
+        let check = find_clawless_project(&cwd.path().into());
+
+        assert!(check.is_err());
+    }
+
+    #[test]
+    fn find_parent_module_locates_file_module() {
+        let cwd = TempDir::new().unwrap();
+        create_dir_all(cwd.path().join("src").join("commands")).unwrap();
+
+        let commands_rs_path = cwd.path().join("src").join("commands.rs");
+        write(&commands_rs_path, "").unwrap();
+
+        let command_name = CommandName::builder()
+            .name("test".to_string())
+            .parent_modules(vec![])
+            .build();
+
+        let parent_module_path = find_parent_module(cwd.path(), &command_name).unwrap();
+
+        assert_eq!(parent_module_path, commands_rs_path);
+    }
+
+    #[test]
+    fn find_parent_module_locates_mod_rs_module() {
+        let cwd = TempDir::new().unwrap();
+        create_dir_all(cwd.path().join("src").join("commands")).unwrap();
+
+        let commands_mod_rs_path = cwd.path().join("src").join("commands").join("mod.rs");
+        write(&commands_mod_rs_path, "").unwrap();
+
+        let command_name = CommandName::builder()
+            .name("test".to_string())
+            .parent_modules(vec![])
+            .build();
+
+        let parent_module_path = find_parent_module(cwd.path(), &command_name).unwrap();
+
+        assert_eq!(parent_module_path, commands_mod_rs_path);
+    }
+
+    #[test]
+    fn find_command_module_locates_mod_rs_module() {
+        let cwd = TempDir::new().unwrap();
+        let db = cwd.path().join("src").join("commands").join("db");
+        create_dir_all(&db).unwrap();
+        write(db.join("mod.rs"), "").unwrap();
+
+        let command_name = CommandName::builder().name("db").build();
+
+        assert_eq!(
+            Some(db.join("mod.rs")),
+            find_command_module(cwd.path(), &command_name)
+        );
+    }
+
+    #[test]
+    fn remove_empty_directories_stops_at_commands_directory() {
+        let cwd = TempDir::new().unwrap();
+        let commands = cwd.path().join("src").join("commands");
+        let schema = commands.join("db").join("schema");
+        create_dir_all(&schema).unwrap();
+
+        let removed = remove_empty_directories(cwd.path(), &schema).unwrap();
+
+        assert_eq!(vec![schema, commands.join("db")], removed);
+        assert!(commands.exists());
+    }
+}
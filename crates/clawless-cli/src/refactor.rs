@@ -0,0 +1,462 @@
+//! Refactorings of the command tree of a Clawless project
+//!
+//! Commands can be removed, renamed, and moved to another command group. The refactorings keep
+//! the project compiling by updating the `mod` declarations in the parent modules, and by renaming
+//! the command function and its arguments struct. Renamed commands keep the replacements of their
+//! sibling commands pointing at them. All edits are prepared before the first file is
+//! changed, so that an error does not leave the project in a half-refactored state.
+
+use std::fmt::{Display, Formatter};
+use std::fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, rename, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::input::CommandName;
+use crate::project::{find_command_module, find_parent_module, remove_empty_directories};
+use crate::source::{
+    command_name, insert_mod, parse, remove_mod, rename_command, rename_mod, rename_replacement,
+};
+use syn::Item;
+use syn::ext::IdentExt;
+
+/// A change to the files of a project
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Change {
+    /// A file or directory was moved
+    Moved(PathBuf, PathBuf),
+
+    /// A file or directory was removed
+    Removed(PathBuf),
+}
+
+impl Change {
+    /// Returns the change with paths relative to the project
+    pub fn relative_to(self, project: &Path) -> Self {
+        let relative = |path: PathBuf| {
+            path.strip_prefix(project)
+                .map(Path::to_path_buf)
+                .unwrap_or(path)
+        };
+
+        match self {
+            Change::Moved(from, to) => Change::Moved(relative(from), relative(to)),
+            Change::Removed(path) => Change::Removed(relative(path)),
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Moved(from, to) => write!(f, "Moved {} to {}", from.display(), to.display()),
+            Change::Removed(path) => write!(f, "Removed {}", path.display()),
+        }
+    }
+}
+
+/// Remove a command, its subcommands, and its `mod` declaration
+///
+/// Commands with subcommands are only removed if `recursive` is set.
+pub fn destroy_command(
+    project: &Path,
+    command_name: &CommandName,
+    recursive: bool,
+) -> Result<Vec<Change>> {
+    let module = find_command_module(project, command_name)
+        .ok_or_else(|| anyhow!("the command `{command_name}` does not exist"))?;
+
+    let file = command_name.path_from_project_root(project);
+    let directory = file.with_extension("");
+
+    if has_subcommands(&directory)? && !recursive {
+        bail!(
+            "the command `{command_name}` has subcommands; use `--recursive` to remove them as well"
+        );
+    }
+
+    let parent = find_parent_module(project, command_name)?;
+    let parent_content = remove_mod(&read(&parent)?, &command_name.module_name())
+        .context(format!("failed to update {}", parent.display()))?;
+
+    let mut changes = Vec::new();
+
+    if module == file {
+        remove_file(&file).context(format!("failed to remove {}", file.display()))?;
+        changes.push(Change::Removed(file.clone()));
+    }
+
+    if directory.exists() {
+        remove_dir_all(&directory).context(format!("failed to remove {}", directory.display()))?;
+        changes.push(Change::Removed(directory));
+    }
+
+    write(&parent, parent_content)?;
+
+    changes.extend(remove_directories(project, &file)?);
+
+    Ok(changes)
+}
+
+/// Move a command to a new location, and rename it if its name changes
+///
+/// The subcommands of the command are moved with it.
+pub fn move_command(project: &Path, from: &CommandName, to: &CommandName) -> Result<Vec<Change>> {
+    if from == to {
+        bail!("the command `{from}` is already named `{to}`");
+    }
+
+    let mut to_path = to.parent_modules().clone();
+    to_path.push(to.name().clone());
+    let mut from_path = from.parent_modules().clone();
+    from_path.push(from.name().clone());
+    if to_path.starts_with(&from_path) {
+        bail!("the command `{from}` cannot be moved into itself");
+    }
+
+    let module = find_command_module(project, from)
+        .ok_or_else(|| anyhow!("the command `{from}` does not exist"))?;
+
+    let from_file = from.path_from_project_root(project);
+    let from_directory = from_file.with_extension("");
+    let to_file = to.path_from_project_root(project);
+    let to_directory = to_file.with_extension("");
+
+    if find_command_module(project, to).is_some() || to_directory.exists() {
+        bail!("the command `{to}` already exists");
+    }
+
+    // Prepare the new contents of all files before any of them is changed
+    let content = read(&module)?;
+    let from_command = command_name(&content, &from.module_name()).context(format!(
+        "failed to read the command in {}",
+        module.display()
+    ))?;
+    let content = if from.name() == to.name() {
+        content
+    } else {
+        rename_command(&content, &from.module_name(), &to.module_name()).context(format!(
+            "failed to rename the command in {}",
+            module.display()
+        ))?
+    };
+
+    let source_parent = find_parent_module(project, from)?;
+    let target_parent = find_parent_module(project, to)?;
+
+    let mut parents = if source_parent == target_parent {
+        let parent = rename_mod(
+            &read(&source_parent)?,
+            &from.module_name(),
+            &to.module_name(),
+        )
+        .context(format!("failed to update {}", source_parent.display()))?;
+
+        vec![(source_parent.clone(), parent)]
+    } else {
+        let source = remove_mod(&read(&source_parent)?, &from.module_name())
+            .context(format!("failed to update {}", source_parent.display()))?;
+        let target = insert_mod(&read(&target_parent)?, &to.module_name())
+            .context(format!("failed to update {}", target_parent.display()))?;
+
+        vec![(source_parent.clone(), source), (target_parent, target)]
+    };
+
+    // Replacements are relative to the parent, so they can only point into the same command group
+    if let Some(groups) = to
+        .parent_modules()
+        .strip_prefix(from.parent_modules().as_slice())
+    {
+        let to_command = command_name(&content, &to.module_name())?;
+        let replacement = groups
+            .iter()
+            .cloned()
+            .chain([to_command])
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if replacement != from_command {
+            for sibling in siblings(project, from, &source_parent)? {
+                // The sibling can be the new parent of the command, which already has pending edits
+                let pending = parents.iter_mut().find(|(path, _)| *path == sibling);
+                let sibling_content = match &pending {
+                    Some((_, content)) => content.clone(),
+                    None => read(&sibling)?,
+                };
+                let sibling_content =
+                    rename_replacement(&sibling_content, &from_command, &replacement)
+                        .context(format!("failed to update {}", sibling.display()))?;
+
+                match pending {
+                    Some((_, content)) => *content = sibling_content,
+                    None => parents.push((sibling, sibling_content)),
+                }
+            }
+        }
+    }
+
+    // Move the files of the command
+    if let Some(parent) = to_file.parent() {
+        create_dir_all(parent).context(format!("failed to create {}", parent.display()))?;
+    }
+
+    let mut changes = Vec::new();
+
+    if module == from_file {
+        move_path(&from_file, &to_file)?;
+        write(&to_file, &content)?;
+        changes.push(Change::Moved(from_file.clone(), to_file));
+    }
+
+    if from_directory.exists() {
+        move_path(&from_directory, &to_directory)?;
+        changes.push(Change::Moved(from_directory.clone(), to_directory.clone()));
+
+        if module != from_file {
+            write(to_directory.join("mod.rs"), content)?;
+        }
+    }
+
+    for (path, content) in parents {
+        write(&path, content)?;
+    }
+
+    changes.extend(remove_directories(project, &from_file)?);
+
+    Ok(changes)
+}
+
+/// Returns the modules of the other commands in the same command group as a command
+fn siblings(project: &Path, command_name: &CommandName, parent: &Path) -> Result<Vec<PathBuf>> {
+    let file = parse(&read(parent)?).context(format!("failed to parse {}", parent.display()))?;
+
+    Ok(file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Mod(module) if module.content.is_none() => Some(module.ident.unraw().to_string()),
+            _ => None,
+        })
+        .filter(|name| *name != command_name.module_name())
+        .filter_map(|name| {
+            let sibling = CommandName::builder()
+                .name(name)
+                .parent_modules(command_name.parent_modules().clone())
+                .build();
+
+            find_command_module(project, &sibling)
+        })
+        .collect())
+}
+
+/// Returns true if the directory of a command contains other modules than its `mod.rs`
+fn has_subcommands(directory: &Path) -> Result<bool> {
+    if !directory.exists() {
+        return Ok(false);
+    }
+
+    let entries = directory
+        .read_dir()
+        .context(format!("failed to read {}", directory.display()))?;
+
+    for entry in entries {
+        if entry?.file_name() != "mod.rs" {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Remove the command groups' directories that became empty after the file was removed
+fn remove_directories(project: &Path, file: &Path) -> Result<Vec<Change>> {
+    let Some(directory) = file.parent() else {
+        return Ok(Vec::new());
+    };
+
+    Ok(remove_empty_directories(project, directory)?
+        .into_iter()
+        .map(Change::Removed)
+        .collect())
+}
+
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    rename(from, to).context(format!(
+        "failed to move {} to {}",
+        from.display(),
+        to.display()
+    ))
+}
+
+fn read(path: &Path) -> Result<String> {
+    read_to_string(path).context(format!("failed to read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir_all;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const GREET: &str = "use clawless::prelude::*;\n\n#[derive(Debug, Args)]\npub struct GreetArgs {}\n\n#[command]\npub async fn greet(args: GreetArgs, context: Context) -> CommandResult {\n    Ok(())\n}\n";
+
+    fn project() -> TempDir {
+        let project = TempDir::new().unwrap();
+        let commands = project.path().join("src").join("commands");
+        create_dir_all(commands.join("greet")).unwrap();
+        create_dir_all(commands.join("admin")).unwrap();
+
+        write(
+            project.path().join("src").join("commands.rs"),
+            "mod admin;\nmod greet;\n\nclawless::commands!();\n",
+        )
+        .unwrap();
+        write(commands.join("admin.rs"), "use clawless::prelude::*;\n").unwrap();
+        write(
+            commands.join("greet.rs"),
+            GREET.replace("{}\n\n#[command]", "{}\n\nmod shout;\n\n#[command]"),
+        )
+        .unwrap();
+        write(commands.join("greet").join("shout.rs"), "").unwrap();
+
+        project
+    }
+
+    fn name(name: &str) -> CommandName {
+        CommandName::try_from(&name.to_string()).unwrap()
+    }
+
+    fn read_project_file(project: &TempDir, path: &str) -> String {
+        read_to_string(project.path().join(path)).unwrap()
+    }
+
+    #[test]
+    fn destroy_command_requires_recursive_for_subcommands() {
+        let project = project();
+
+        let error = destroy_command(project.path(), &name("greet"), false).unwrap_err();
+
+        assert_eq!(
+            "the command `greet` has subcommands; use `--recursive` to remove them as well",
+            error.to_string()
+        );
+        assert!(project.path().join("src/commands/greet.rs").exists());
+    }
+
+    #[test]
+    fn destroy_command_removes_files_and_declaration() {
+        let project = project();
+
+        let changes = destroy_command(project.path(), &name("greet/shout"), false).unwrap();
+
+        let commands = project.path().join("src").join("commands");
+        assert_eq!(
+            vec![
+                Change::Removed(commands.join("greet").join("shout.rs")),
+                Change::Removed(commands.join("greet")),
+            ],
+            changes
+        );
+        assert!(!read_project_file(&project, "src/commands/greet.rs").contains("mod shout;"));
+    }
+
+    #[test]
+    fn move_command_renames_function_and_subcommands() {
+        let project = project();
+
+        move_command(project.path(), &name("greet"), &name("admin/hello")).unwrap();
+
+        let hello = read_project_file(&project, "src/commands/admin/hello.rs");
+        assert!(hello.contains("pub struct HelloArgs {}"));
+        assert!(hello.contains("pub async fn hello(args: HelloArgs"));
+        assert!(
+            project
+                .path()
+                .join("src/commands/admin/hello/shout.rs")
+                .exists()
+        );
+        assert!(!project.path().join("src/commands/greet").exists());
+
+        assert_eq!(
+            "mod admin;\n\nclawless::commands!();\n",
+            read_project_file(&project, "src/commands.rs")
+        );
+        assert_eq!(
+            "use clawless::prelude::*;\n\nmod hello;\n",
+            read_project_file(&project, "src/commands/admin.rs")
+        );
+    }
+
+    #[test]
+    fn move_command_renames_declaration_in_same_parent() {
+        let project = project();
+
+        move_command(project.path(), &name("greet"), &name("hello")).unwrap();
+
+        assert_eq!(
+            "mod admin;\nmod hello;\n\nclawless::commands!();\n",
+            read_project_file(&project, "src/commands.rs")
+        );
+    }
+
+    #[test]
+    fn move_command_updates_replacements_of_siblings() {
+        let project = project();
+        write(
+            project.path().join("src/commands/admin.rs"),
+            "#[command(replaced_by = \"greet\")]\npub async fn admin() {}\n",
+        )
+        .unwrap();
+        write(
+            project.path().join("src/commands/greet.rs"),
+            GREET.replace("#[command]", "#[command(name = \"greet\")]"),
+        )
+        .unwrap();
+
+        move_command(project.path(), &name("greet"), &name("say_hello")).unwrap();
+
+        assert_eq!(
+            "#[command(replaced_by = \"say-hello\")]\npub async fn admin() {}\n",
+            read_project_file(&project, "src/commands/admin.rs")
+        );
+        assert!(
+            read_project_file(&project, "src/commands/say_hello.rs")
+                .contains("#[command(name = \"say-hello\")]")
+        );
+    }
+
+    #[test]
+    fn move_command_updates_replacements_in_new_parent() {
+        let project = project();
+        write(
+            project.path().join("src/commands/admin.rs"),
+            "#[command(replaced_by = \"greet shout\")]\npub async fn admin() {}\n",
+        )
+        .unwrap();
+
+        move_command(project.path(), &name("greet"), &name("admin/hello")).unwrap();
+
+        assert_eq!(
+            "mod hello;\n\n#[command(replaced_by = \"admin hello shout\")]\npub async fn admin() {}\n",
+            read_project_file(&project, "src/commands/admin.rs")
+        );
+    }
+
+    #[test]
+    fn move_command_fails_for_existing_command() {
+        let project = project();
+
+        let error = move_command(project.path(), &name("greet"), &name("admin")).unwrap_err();
+
+        assert_eq!("the command `admin` already exists", error.to_string());
+    }
+
+    #[test]
+    fn move_command_fails_for_move_into_itself() {
+        let project = project();
+
+        assert!(move_command(project.path(), &name("greet"), &name("greet/hello")).is_err());
+    }
+}
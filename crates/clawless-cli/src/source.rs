@@ -0,0 +1,869 @@
+//! Structural editing of Rust source files
+//!
+//! The generators modify the modules of a project, e.g. to declare a new command. The files are
+//! parsed with `syn` to find the right place for a change, but the change itself is applied to the
+//! original text so that comments and formatting are preserved.
+
+use anyhow::{Result, anyhow, bail};
+use convert_case::{Case, Casing};
+use proc_macro2::{LineColumn, Span, TokenStream, TokenTree};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, File, Item, ItemFn, ItemMod, Lit, MacroDelimiter, Meta, Token};
+
+/// Declare a module in the source of its parent module
+///
+/// The declaration is inserted after the last module declaration, or after the last `use`
+/// statement, or before the first item of the file. Nothing changes if the module is already
+/// declared, regardless of its visibility and attributes. A module that is defined inline with
+/// `mod name { ... }` cannot be declared again, and returns an error.
+pub fn insert_mod(content: &str, name: &str) -> Result<String> {
+    let file = parse(content)?;
+
+    if let Some(module) = find_mod(&file, name) {
+        if module.content.is_some() {
+            bail!("the module `{name}` is already defined inline");
+        }
+
+        return Ok(content.into());
+    }
+
+    let declaration = format!("mod {};\n", ident(name));
+
+    let last_mod = file
+        .items
+        .iter()
+        .rfind(|item| matches!(item, Item::Mod(module) if module.content.is_none()));
+    if let Some(item) = last_mod {
+        return Ok(insert_after_line(content, end_line(item), &declaration));
+    }
+
+    let last_use = file.items.iter().rfind(|item| matches!(item, Item::Use(_)));
+    if let Some(item) = last_use {
+        return Ok(insert_after_line(
+            content,
+            end_line(item),
+            &format!("\n{declaration}"),
+        ));
+    }
+
+    match file.items.first() {
+        Some(item) => Ok(insert_after_line(
+            content,
+            start_line(item) - 1,
+            &format!("{declaration}\n"),
+        )),
+        None => Ok(insert_after_line(
+            content,
+            content.lines().count(),
+            &declaration,
+        )),
+    }
+}
+
+/// Remove the declaration of a module from the source of its parent module
+///
+/// The lines of the declaration are removed together with its attributes. Nothing changes if the
+/// module is not declared, and a module that is defined inline returns an error.
+pub fn remove_mod(content: &str, name: &str) -> Result<String> {
+    let file = parse(content)?;
+
+    let Some(module) = find_mod(&file, name) else {
+        return Ok(content.into());
+    };
+    if module.content.is_some() {
+        bail!("the module `{name}` is defined inline");
+    }
+
+    let span = module.span();
+    Ok(remove_lines(content, span.start().line, span.end().line))
+}
+
+/// Rename the declaration of a module in the source of its parent module
+pub fn rename_mod(content: &str, name: &str, new_name: &str) -> Result<String> {
+    let file = parse(content)?;
+
+    let module =
+        find_mod(&file, name).ok_or_else(|| anyhow!("the module `{name}` is not declared"))?;
+
+    Ok(replace_spans(
+        content,
+        vec![(module.ident.span(), ident(new_name))],
+    ))
+}
+
+/// Rename the command function and its arguments struct in the source of a command
+///
+/// The function that is annotated with `#[command]` is renamed from `name` to `new_name`, and
+/// every reference to the struct `<Name>Args` is renamed to `<NewName>Args`. If the command sets
+/// its name on the command line with `name = "..."`, the name is changed to `new_name` in kebab
+/// case, or removed if the function already has that name.
+pub fn rename_command(content: &str, name: &str, new_name: &str) -> Result<String> {
+    let file = parse(content)?;
+
+    let function = find_command_function(&file, name)
+        .ok_or_else(|| anyhow!("the command function `{name}` was not found"))?;
+    let mut edits = vec![(function.sig.ident.span(), ident(new_name))];
+
+    if let Some(attribute) = command_attribute(function) {
+        let options = command_options(attribute)?;
+
+        if let Some(Meta::NameValue(option)) =
+            options.iter().find(|option| option.path().is_ident("name"))
+        {
+            let command_name = new_name.to_case(Case::Kebab);
+
+            if command_name == new_name {
+                let remaining: Vec<&str> = options
+                    .iter()
+                    .filter(|option| !option.path().is_ident("name"))
+                    .map(|option| span_text(content, option.span()))
+                    .collect();
+                let path = span_text(content, attribute.path().span());
+
+                let attribute_text = if remaining.is_empty() {
+                    format!("#[{path}]")
+                } else {
+                    format!("#[{path}({})]", remaining.join(", "))
+                };
+                edits.push((attribute.span(), attribute_text));
+            } else {
+                edits.push((option.value.span(), format!("\"{command_name}\"")));
+            }
+        }
+    }
+
+    let args = format!("{}Args", name.to_case(Case::Pascal));
+    let new_args = format!("{}Args", new_name.to_case(Case::Pascal));
+
+    let tokens: TokenStream = content
+        .parse()
+        .map_err(|error| anyhow!("failed to tokenize the source: {error}"))?;
+    find_idents(tokens, &args, &mut |span| {
+        edits.push((span, new_args.clone()))
+    });
+
+    Ok(replace_spans(content, edits))
+}
+
+/// Returns the name of a command on the command line
+///
+/// The name is set with `name = "..."` in the `#[command]` attribute, and defaults to the name of
+/// the function.
+pub fn command_name(content: &str, name: &str) -> Result<String> {
+    let file = parse(content)?;
+
+    let function = find_command_function(&file, name)
+        .ok_or_else(|| anyhow!("the command function `{name}` was not found"))?;
+    let options = match command_attribute(function) {
+        Some(attribute) => command_options(attribute)?,
+        None => Punctuated::new(),
+    };
+
+    Ok(options
+        .iter()
+        .find_map(|option| match option {
+            Meta::NameValue(option) if option.path.is_ident("name") => string(&option.value),
+            _ => None,
+        })
+        .unwrap_or_else(|| function.sig.ident.unraw().to_string()))
+}
+
+/// Update the `replaced_by` options that refer to a renamed command in the source of a module
+///
+/// Replacements are relative to the parent of a command, so the first word of a replacement is
+/// the name of a sibling command, e.g. `db` in `replaced_by = "db migrate"`.
+pub fn rename_replacement(content: &str, name: &str, new_name: &str) -> Result<String> {
+    let file = parse(content)?;
+    let mut edits = Vec::new();
+
+    for item in &file.items {
+        let Item::Fn(function) = item else {
+            continue;
+        };
+        let Some(attribute) = command_attribute(function) else {
+            continue;
+        };
+
+        for option in command_options(attribute)? {
+            let Meta::NameValue(option) = option else {
+                continue;
+            };
+            if !option.path.is_ident("replaced_by") {
+                continue;
+            }
+
+            let Some(replacement) = string(&option.value) else {
+                continue;
+            };
+            let mut words: Vec<&str> = replacement.split_whitespace().collect();
+            if words.first() == Some(&name) {
+                words[0] = new_name;
+                edits.push((option.value.span(), format!("\"{}\"", words.join(" "))));
+            }
+        }
+    }
+
+    Ok(replace_spans(content, edits))
+}
+
+/// Returns the identifier for a name, which is a raw identifier for keywords, e.g. `r#move`
+pub fn ident(name: &str) -> String {
+    if syn::parse_str::<syn::Ident>(name).is_ok() {
+        name.into()
+    } else {
+        format!("r#{name}")
+    }
+}
+
+/// Add an option to the call of `clawless::commands!` in the source of the commands module
+///
+/// The option, e.g. `config = crate::config::Config`, is appended to the existing options of the
+/// macro. An error is returned if the module does not call the macro, or if the macro already has
+/// an option with the same key.
+pub fn add_commands_option(content: &str, key: &str, value: &str) -> Result<String> {
+    let file = parse(content)?;
+
+    let mac = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Macro(item)
+                if item
+                    .mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "commands") =>
+            {
+                Some(&item.mac)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("the module does not call `clawless::commands!`"))?;
+
+    let options = mac
+        .parse_body_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        .map_err(|error| {
+            anyhow!("failed to parse the options of `clawless::commands!`: {error}")
+        })?;
+    if options.iter().any(|option| option.path().is_ident(key)) {
+        bail!("`clawless::commands!` already has the option `{key}`");
+    }
+
+    let option = format!("{key} = {value}");
+    let edit = match options.last() {
+        Some(last) => (
+            last.span(),
+            format!("{}, {option}", span_text(content, last.span())),
+        ),
+        None => {
+            let (span, open, close) = match &mac.delimiter {
+                MacroDelimiter::Paren(paren) => (paren.span.join(), '(', ')'),
+                MacroDelimiter::Brace(brace) => (brace.span.join(), '{', '}'),
+                MacroDelimiter::Bracket(bracket) => (bracket.span.join(), '[', ']'),
+            };
+            (span, format!("{open}{option}{close}"))
+        }
+    };
+
+    Ok(replace_spans(content, vec![edit]))
+}
+
+/// Remove a function from the source of a module
+///
+/// The lines of the function are removed together with its attributes and doc comments. Nothing
+/// changes if the function does not exist.
+pub fn remove_fn(content: &str, name: &str) -> Result<String> {
+    let file = parse(content)?;
+
+    let Some(function) = find_fn(&file, name) else {
+        return Ok(content.into());
+    };
+
+    let span = function.span();
+    Ok(remove_lines(content, span.start().line, span.end().line))
+}
+
+/// Returns the original text of a span, including comments and formatting
+pub fn span_text(content: &str, span: Span) -> &str {
+    let start = offset(content, span.start());
+    let end = offset(content, span.end());
+
+    &content[start..end]
+}
+
+/// Parse the source of a Rust file
+pub fn parse(content: &str) -> Result<File> {
+    syn::parse_file(content).map_err(|error| {
+        let start = error.span().start();
+        anyhow!(
+            "{error} at line {}, column {}",
+            start.line,
+            start.column + 1
+        )
+    })
+}
+
+/// Find the declaration or definition of a module among the items of a file
+pub fn find_mod<'a>(file: &'a File, name: &str) -> Option<&'a ItemMod> {
+    file.items.iter().find_map(|item| match item {
+        Item::Mod(module) if module.ident.unraw() == name => Some(module),
+        _ => None,
+    })
+}
+
+/// Find a function among the items of a file
+pub fn find_fn<'a>(file: &'a File, name: &str) -> Option<&'a ItemFn> {
+    file.items.iter().find_map(|item| match item {
+        Item::Fn(function) if function.sig.ident.unraw() == name => Some(function),
+        _ => None,
+    })
+}
+
+/// Returns the `#[command]` attribute of a function
+fn command_attribute(function: &ItemFn) -> Option<&Attribute> {
+    function.attrs.iter().find(|attribute| {
+        attribute
+            .path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "command")
+    })
+}
+
+/// Returns the options of a `#[command]` attribute, which has none if it is used without arguments
+fn command_options(attribute: &Attribute) -> Result<Punctuated<Meta, Token![,]>> {
+    match &attribute.meta {
+        Meta::List(_) => attribute
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .map_err(|error| anyhow!("failed to parse the options of `#[command]`: {error}")),
+        _ => Ok(Punctuated::new()),
+    }
+}
+
+/// Returns the value of a string literal
+fn string(expression: &Expr) -> Option<String> {
+    match expression {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(lit) => Some(lit.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Find the function that is annotated with `#[command]` and has the given name
+fn find_command_function<'a>(file: &'a File, name: &str) -> Option<&'a ItemFn> {
+    file.items.iter().find_map(|item| match item {
+        Item::Fn(function)
+            if function.sig.ident.unraw() == name && command_attribute(function).is_some() =>
+        {
+            Some(function)
+        }
+        _ => None,
+    })
+}
+
+/// Call a function with the span of every identifier with the given name in a token stream
+fn find_idents(tokens: TokenStream, name: &str, found: &mut impl FnMut(Span)) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) if ident == name => found(ident.span()),
+            TokenTree::Group(group) => find_idents(group.stream(), name, found),
+            _ => {}
+        }
+    }
+}
+
+/// Returns the line on which an item starts, including its attributes and doc comments
+fn start_line(item: &Item) -> usize {
+    item.span().start().line
+}
+
+/// Returns the line on which an item ends
+fn end_line(item: &Item) -> usize {
+    item.span().end().line
+}
+
+/// Insert text at the beginning of the line after the given line, where lines start at 1
+fn insert_after_line(content: &str, line: usize, text: &str) -> String {
+    let offset = if line == 0 {
+        0
+    } else {
+        content
+            .match_indices('\n')
+            .nth(line - 1)
+            .map(|(index, _)| index + 1)
+            .unwrap_or(content.len())
+    };
+
+    let mut content = content.to_string();
+    if offset == content.len() && !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+        content.push_str(text);
+    } else {
+        content.insert_str(offset, text);
+    }
+
+    content
+}
+
+/// Remove the lines from `first` to `last`, where lines start at 1
+///
+/// A blank line that directly follows another blank line after the removal is removed as well, so
+/// that removing a declaration does not leave a gap in the file.
+fn remove_lines(content: &str, first: usize, last: usize) -> String {
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let last = last.min(lines.len());
+    if first == 0 || first > last {
+        return content.into();
+    }
+
+    lines.drain(first - 1..last);
+
+    let index = first - 1;
+    let is_blank = |line: Option<&&str>| line.is_some_and(|line| line.trim().is_empty());
+    if index > 0
+        && is_blank(lines.get(index - 1))
+        && (is_blank(lines.get(index)) || index == lines.len())
+    {
+        lines.remove(index - 1);
+    }
+
+    lines.concat()
+}
+
+/// Replace the source at the given spans, which must not overlap
+fn replace_spans(content: &str, mut edits: Vec<(Span, String)>) -> String {
+    edits.sort_by_key(|(span, _)| std::cmp::Reverse(offset(content, span.start())));
+
+    let mut content = content.to_string();
+    for (span, text) in edits {
+        let start = offset(content.as_str(), span.start());
+        let end = offset(content.as_str(), span.end());
+        content.replace_range(start..end, &text);
+    }
+
+    content
+}
+
+/// Returns the byte offset of a position, whose line starts at 1 and whose column counts characters
+fn offset(content: &str, position: LineColumn) -> usize {
+    let line_start = if position.line <= 1 {
+        0
+    } else {
+        content
+            .match_indices('\n')
+            .nth(position.line - 2)
+            .map(|(index, _)| index + 1)
+            .unwrap_or(content.len())
+    };
+
+    content[line_start..]
+        .char_indices()
+        .nth(position.column)
+        .map(|(index, _)| line_start + index)
+        .unwrap_or(content.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn insert_mod_after_last_mod() {
+        let content = indoc! {r#"
+            //! Commands
+
+            mod deploy;
+            #[cfg(feature = "db")]
+            mod migrate;
+
+            clawless::commands!();
+        "#};
+
+        assert_eq!(
+            indoc! {r#"
+                //! Commands
+
+                mod deploy;
+                #[cfg(feature = "db")]
+                mod migrate;
+                mod greet;
+
+                clawless::commands!();
+            "#},
+            insert_mod(content, "greet").unwrap()
+        );
+    }
+
+    #[test]
+    fn insert_mod_after_last_use() {
+        let content = indoc! {r#"
+            use clawless::prelude::*;
+
+            #[command]
+            pub async fn greet(args: GreetArgs, context: Context) -> CommandResult {
+                Ok(())
+            }
+        "#};
+
+        assert_eq!(
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                mod shout;
+
+                #[command]
+                pub async fn greet(args: GreetArgs, context: Context) -> CommandResult {
+                    Ok(())
+                }
+            "#},
+            insert_mod(content, "shout").unwrap()
+        );
+    }
+
+    #[test]
+    fn insert_mod_before_first_item() {
+        let content = "//! Commands\n\nclawless::commands!();\n";
+
+        assert_eq!(
+            "//! Commands\n\nmod greet;\n\nclawless::commands!();\n",
+            insert_mod(content, "greet").unwrap()
+        );
+    }
+
+    #[test]
+    fn insert_mod_into_empty_file() {
+        assert_eq!("mod greet;\n", insert_mod("", "greet").unwrap());
+        assert_eq!(
+            "//! Commands\nmod greet;\n",
+            insert_mod("//! Commands", "greet").unwrap()
+        );
+    }
+
+    #[test]
+    fn insert_mod_ignores_comments_and_strings() {
+        let content = indoc! {r#"
+            // mod greet;
+            mod deploy;
+
+            const HELP: &str = "mod greet;";
+        "#};
+
+        assert_eq!(
+            indoc! {r#"
+                // mod greet;
+                mod deploy;
+                mod greet;
+
+                const HELP: &str = "mod greet;";
+            "#},
+            insert_mod(content, "greet").unwrap()
+        );
+    }
+
+    #[test]
+    fn insert_mod_ignores_inline_modules_as_anchor() {
+        let content = indoc! {r#"
+            mod deploy;
+
+            clawless::commands!();
+
+            #[cfg(test)]
+            mod tests {
+                mod helpers;
+            }
+        "#};
+
+        let content = insert_mod(content, "greet").unwrap();
+
+        assert!(content.starts_with("mod deploy;\nmod greet;\n"));
+    }
+
+    #[test]
+    fn insert_mod_keeps_existing_declaration() {
+        let content = "pub mod greet;\n#[cfg(unix)]\nmod shout;\n";
+
+        assert_eq!(content, insert_mod(content, "greet").unwrap());
+        assert_eq!(content, insert_mod(content, "shout").unwrap());
+    }
+
+    #[test]
+    fn insert_mod_fails_for_inline_module() {
+        let error = insert_mod("mod greet {}\n", "greet").unwrap_err();
+
+        assert_eq!(
+            "the module `greet` is already defined inline",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn insert_mod_fails_for_invalid_source() {
+        assert!(insert_mod("mod greet", "shout").is_err());
+    }
+
+    #[test]
+    fn remove_mod_removes_declaration_and_attributes() {
+        let content = indoc! {r#"
+            mod deploy;
+            #[cfg(feature = "db")]
+            pub mod migrate;
+            mod seed;
+
+            clawless::commands!();
+        "#};
+
+        assert_eq!(
+            "mod deploy;\nmod seed;\n\nclawless::commands!();\n",
+            remove_mod(content, "migrate").unwrap()
+        );
+    }
+
+    #[test]
+    fn remove_mod_collapses_blank_lines() {
+        let content = "use clawless::prelude::*;\n\nmod shout;\n\n#[command]\nfn greet() {}\n";
+
+        assert_eq!(
+            "use clawless::prelude::*;\n\n#[command]\nfn greet() {}\n",
+            remove_mod(content, "shout").unwrap()
+        );
+    }
+
+    #[test]
+    fn remove_mod_keeps_undeclared_module() {
+        let content = "mod deploy;\n";
+
+        assert_eq!(content, remove_mod(content, "greet").unwrap());
+    }
+
+    #[test]
+    fn remove_fn_removes_function_and_attributes() {
+        let content = indoc! {r#"
+            mod cli;
+
+            /// Runs the application
+            #[tokio::main]
+            async fn main() {
+                cli::run().await;
+            }
+
+            fn helper() {}
+        "#};
+
+        assert_eq!(
+            "mod cli;\n\nfn helper() {}\n",
+            remove_fn(content, "main").unwrap()
+        );
+    }
+
+    #[test]
+    fn rename_mod_keeps_position_and_visibility() {
+        let content = "mod deploy;\npub mod greet; // says hello\n";
+
+        assert_eq!(
+            "mod deploy;\npub mod hello; // says hello\n",
+            rename_mod(content, "greet", "hello").unwrap()
+        );
+    }
+
+    #[test]
+    fn rename_command_renames_function_and_arguments() {
+        let content = indoc! {r#"
+            use clawless::prelude::*;
+
+            /// Greet the user (greet)
+            #[derive(Debug, Args)]
+            pub struct GreetArgs {
+                name: String,
+            }
+
+            impl GreetArgs {
+                fn greet(&self) {}
+            }
+
+            #[command(alias = "g")]
+            pub async fn greet(args: GreetArgs, context: Context) -> CommandResult {
+                Ok(())
+            }
+        "#};
+
+        assert_eq!(
+            indoc! {r#"
+                use clawless::prelude::*;
+
+                /// Greet the user (greet)
+                #[derive(Debug, Args)]
+                pub struct SayHelloArgs {
+                    name: String,
+                }
+
+                impl SayHelloArgs {
+                    fn greet(&self) {}
+                }
+
+                #[command(alias = "g")]
+                pub async fn say_hello(args: SayHelloArgs, context: Context) -> CommandResult {
+                    Ok(())
+                }
+            "#},
+            rename_command(content, "greet", "say_hello").unwrap()
+        );
+    }
+
+    #[test]
+    fn rename_command_rewrites_name_option() {
+        let content = "#[command(name = \"greet-user\", alias = \"g\")]\nfn greet_user() {}\n";
+
+        assert_eq!(
+            "#[command(name = \"say-hello\", alias = \"g\")]\nfn say_hello() {}\n",
+            rename_command(content, "greet_user", "say_hello").unwrap()
+        );
+    }
+
+    #[test]
+    fn rename_command_removes_redundant_name_option() {
+        for (content, expected) in [
+            (
+                "#[command(name = \"greet-user\", alias = \"g\")]\nfn greet_user() {}\n",
+                "#[command(alias = \"g\")]\nfn greet() {}\n",
+            ),
+            (
+                "#[command(name = \"greet-user\")]\nfn greet_user() {}\n",
+                "#[command]\nfn greet() {}\n",
+            ),
+        ] {
+            assert_eq!(
+                expected,
+                rename_command(content, "greet_user", "greet").unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn rename_command_uses_raw_identifier_for_keywords() {
+        let content = "#[command]\nfn greet() {}\n";
+
+        assert_eq!(
+            "#[command]\nfn r#move() {}\n",
+            rename_command(content, "greet", "move").unwrap()
+        );
+    }
+
+    #[test]
+    fn insert_mod_uses_raw_identifier_for_keywords() {
+        assert_eq!("mod r#move;\n", insert_mod("", "move").unwrap());
+        assert_eq!(
+            "mod r#move;\n",
+            rename_mod("mod greet;\n", "greet", "move").unwrap()
+        );
+    }
+
+    #[test]
+    fn command_name_reads_name_option() {
+        let content =
+            "#[command(name = \"greet-user\")]\nfn greet_user() {}\n\n#[command]\nfn r#move() {}\n";
+
+        assert_eq!("greet-user", command_name(content, "greet_user").unwrap());
+        assert_eq!("move", command_name(content, "move").unwrap());
+    }
+
+    #[test]
+    fn rename_replacement_updates_first_word() {
+        let content = indoc! {r#"
+            #[command(replaced_by = "greet", alias = "g")]
+            fn hello() {}
+
+            #[command(replaced_by = "greet shout")]
+            fn shout() {}
+
+            #[command(replaced_by = "greeter")]
+            fn welcome() {}
+        "#};
+
+        assert_eq!(
+            indoc! {r#"
+                #[command(replaced_by = "say-hello", alias = "g")]
+                fn hello() {}
+
+                #[command(replaced_by = "say-hello shout")]
+                fn shout() {}
+
+                #[command(replaced_by = "greeter")]
+                fn welcome() {}
+            "#},
+            rename_replacement(content, "greet", "say-hello").unwrap()
+        );
+    }
+
+    #[test]
+    fn add_commands_option_appends_to_existing_options() {
+        let content = "mod greet;\n\nclawless::commands!(infer_subcommands,);\n";
+
+        assert_eq!(
+            "mod greet;\n\nclawless::commands!(infer_subcommands, config = crate::Config,);\n",
+            add_commands_option(content, "config", "crate::Config").unwrap()
+        );
+    }
+
+    #[test]
+    fn add_commands_option_supports_all_delimiters() {
+        for (content, expected) in [
+            ("commands!();\n", "commands!(config = crate::Config);\n"),
+            ("commands! {}\n", "commands! {config = crate::Config}\n"),
+            ("commands![];\n", "commands![config = crate::Config];\n"),
+        ] {
+            assert_eq!(
+                expected,
+                add_commands_option(content, "config", "crate::Config").unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn add_commands_option_ignores_comments_and_strings() {
+        let content = indoc! {r#"
+            // clawless::commands!(infer_subcommands)
+            const DOC: &str = "clawless::commands!()";
+
+            clawless::commands!(theme = crate::theme);
+        "#};
+
+        assert!(
+            add_commands_option(content, "config", "crate::Config")
+                .unwrap()
+                .ends_with("clawless::commands!(theme = crate::theme, config = crate::Config);\n")
+        );
+    }
+
+    #[test]
+    fn add_commands_option_fails_for_existing_option() {
+        let error = add_commands_option(
+            "clawless::commands!(config = crate::Settings);\n",
+            "config",
+            "crate::Config",
+        )
+        .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("already has the option `config`")
+        );
+    }
+
+    #[test]
+    fn add_commands_option_fails_without_macro() {
+        assert!(add_commands_option("mod greet;\n", "config", "crate::Config").is_err());
+    }
+
+    #[test]
+    fn rename_command_fails_without_command_function() {
+        assert!(rename_command("fn greet() {}\n", "greet", "hello").is_err());
+    }
+}
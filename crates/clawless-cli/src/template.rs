@@ -0,0 +1,558 @@
+//! Templates for new Clawless projects
+//!
+//! A template is a set of files that are written into a new project, replacing the files that
+//! `cargo new` created. The CLI ships with built-in templates, and users can provide their own
+//! templates as directories, e.g. to share a company template with preconfigured logging and CI.
+//!
+//! The paths and contents of the files can contain variables such as `{{crate_name}}`, which are
+//! replaced when the template is rendered. Text in double braces that is not a known variable is
+//! kept as is, so that templates can include files like GitHub workflows that use the same syntax.
+
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, read, read_dir, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use convert_case::{Case, Casing};
+use getset::Getters;
+use indoc::indoc;
+use typed_builder::TypedBuilder;
+
+/// The names of the built-in templates
+pub const BUILT_IN_TEMPLATES: [&str; 3] = ["minimal", "full", "workspace"];
+
+/// The template that is used if no other template is selected
+pub const DEFAULT_TEMPLATE: &str = "minimal";
+
+/// The commands module of a project without commands, e.g. a crate that is converted with `init`
+pub const COMMANDS_MODULE: &str = indoc! {r#"
+    // Collect the commands of the application
+    //
+    // This macro collects all the command functions defined in this module and its sub-modules,
+    // and registers them with the Clawless runtime so they can be invoked from the command line.
+    clawless::commands!();
+"#};
+
+const MINIMAL: &[(&str, &str)] = &[
+    (
+        "src/main.rs",
+        include_str!("../templates/minimal/src/main.rs"),
+    ),
+    (
+        "src/commands.rs",
+        include_str!("../templates/minimal/src/commands.rs"),
+    ),
+    (
+        "src/commands/greet.rs",
+        include_str!("../templates/minimal/src/commands/greet.rs"),
+    ),
+];
+
+const FULL: &[(&str, &str)] = &[
+    (
+        ".github/workflows/ci.yml",
+        include_str!("../templates/full/.github/workflows/ci.yml"),
+    ),
+    (
+        "Cargo.toml",
+        include_str!("../templates/full/Cargo.toml.template"),
+    ),
+    ("README.md", include_str!("../templates/full/README.md")),
+    ("src/main.rs", include_str!("../templates/full/src/main.rs")),
+    (
+        "src/config.rs",
+        include_str!("../templates/full/src/config.rs"),
+    ),
+    (
+        "src/commands.rs",
+        include_str!("../templates/full/src/commands.rs"),
+    ),
+    (
+        "src/commands/greet.rs",
+        include_str!("../templates/full/src/commands/greet.rs"),
+    ),
+];
+
+const WORKSPACE: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("../templates/workspace/Cargo.toml.template"),
+    ),
+    (
+        "README.md",
+        include_str!("../templates/workspace/README.md"),
+    ),
+    (
+        "crates/{{crate_name}}-core/Cargo.toml",
+        include_str!("../templates/workspace/crates/{{crate_name}}-core/Cargo.toml.template"),
+    ),
+    (
+        "crates/{{crate_name}}-core/src/lib.rs",
+        include_str!("../templates/workspace/crates/{{crate_name}}-core/src/lib.rs"),
+    ),
+    (
+        "src/main.rs",
+        include_str!("../templates/workspace/src/main.rs"),
+    ),
+    (
+        "src/commands.rs",
+        include_str!("../templates/workspace/src/commands.rs"),
+    ),
+    (
+        "src/commands/greet.rs",
+        include_str!("../templates/workspace/src/commands/greet.rs"),
+    ),
+];
+
+/// A template for a new project
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Template {
+    /// A template that is compiled into the CLI
+    BuiltIn(&'static [(&'static str, &'static str)]),
+
+    /// A template in a directory
+    Directory(PathBuf),
+}
+
+/// The values of the variables in a template
+#[derive(Clone, Eq, PartialEq, Debug, Getters, TypedBuilder)]
+pub struct Variables {
+    /// The name of the crate, e.g. `my-app`
+    #[builder(setter(into))]
+    #[getset(get = "pub")]
+    crate_name: String,
+
+    /// The author of the project
+    #[builder(setter(into))]
+    #[getset(get = "pub")]
+    author: String,
+
+    /// A short description of the project
+    #[builder(setter(into))]
+    #[getset(get = "pub")]
+    description: String,
+}
+
+impl Template {
+    /// Find a template by its name or path
+    ///
+    /// The name can be a built-in template, a template from the user's configuration, or the path
+    /// of a directory, which is resolved relative to the working directory. The paths of the
+    /// templates from the configuration are expected to be resolved with [`resolve_path`].
+    pub fn find(
+        name: &str,
+        user_templates: &BTreeMap<String, PathBuf>,
+        working_directory: &Path,
+    ) -> Result<Self> {
+        match name {
+            "minimal" => return Ok(Template::BuiltIn(MINIMAL)),
+            "full" => return Ok(Template::BuiltIn(FULL)),
+            "workspace" => return Ok(Template::BuiltIn(WORKSPACE)),
+            _ => {}
+        }
+
+        let path = match user_templates.get(name) {
+            Some(path) => path.clone(),
+            None => resolve_path(Path::new(name), working_directory),
+        };
+
+        if path.is_dir() {
+            return Ok(Template::Directory(path));
+        }
+
+        let mut names: Vec<&str> = BUILT_IN_TEMPLATES.to_vec();
+        names.extend(user_templates.keys().map(String::as_str));
+
+        Err(anyhow!(
+            "the template `{name}` does not exist; use one of {} or the path of a template directory",
+            names
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+
+    /// Returns the manifest of the template, or `None` if it keeps the manifest of `cargo new`
+    pub fn manifest(&self) -> Result<Option<String>> {
+        let manifest = match self {
+            Template::BuiltIn(files) => files
+                .iter()
+                .find(|(path, _)| *path == "Cargo.toml")
+                .map(|(_, content)| content.to_string()),
+            Template::Directory(directory) => {
+                let path = directory.join("Cargo.toml");
+                if path.is_file() {
+                    Some(
+                        read_to_string(&path)
+                            .context(format!("failed to read {}", path.display()))?,
+                    )
+                } else {
+                    None
+                }
+            }
+        };
+
+        Ok(manifest)
+    }
+
+    /// Returns whether the template creates a Cargo workspace
+    ///
+    /// Cargo does not support nested workspaces, so these templates cannot be used for a new member
+    /// of an existing workspace.
+    pub fn creates_workspace(&self) -> Result<bool> {
+        Ok(self.manifest()?.is_some_and(|manifest| {
+            manifest
+                .lines()
+                .map(str::trim)
+                .any(|line| line == "[workspace]" || line.starts_with("[workspace."))
+        }))
+    }
+
+    /// Returns whether the paths or contents of the files of the template use a variable
+    pub fn uses_variable(&self, name: &str) -> Result<bool> {
+        Ok(self.files()?.iter().any(|(path, content)| {
+            let content = String::from_utf8_lossy(content);
+
+            [path.to_string_lossy(), content]
+                .iter()
+                .any(|text| placeholders(text).contains(&name))
+        }))
+    }
+
+    /// Render the template into a directory, and return the paths of the files that were written
+    pub fn render(&self, destination: &Path, variables: &Variables) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+
+        for (path, content) in self.files()? {
+            let path = destination.join(variables.replace(&path.to_string_lossy()));
+
+            // Binary files such as images are copied without replacing variables
+            let content = match String::from_utf8(content) {
+                Ok(text) => variables.replace(&text).into_bytes(),
+                Err(error) => error.into_bytes(),
+            };
+
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent).context(format!("failed to create {}", parent.display()))?;
+            }
+
+            write(&path, content).context(format!("failed to write {}", path.display()))?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+
+    /// Returns the paths and contents of the files of the template
+    fn files(&self) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+        match self {
+            Template::BuiltIn(files) => Ok(files
+                .iter()
+                .map(|(path, content)| (PathBuf::from(path), content.as_bytes().to_vec()))
+                .collect()),
+            Template::Directory(directory) => read_template_directory(directory),
+        }
+    }
+}
+
+impl Variables {
+    /// Returns the value of a variable, or `None` if the variable is unknown
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "crate_name" => Some(self.crate_name.clone()),
+            "crate_ident" => Some(self.crate_name.to_case(Case::Snake)),
+            "author" => Some(self.author.clone()),
+            "description" => Some(self.description.clone()),
+            _ => None,
+        }
+    }
+
+    /// Replace the variables in a text, e.g. `{{crate_name}}` or `{{ crate_name }}`
+    pub fn replace(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+
+            let placeholder = &rest[start..start + end + 2];
+            let name = placeholder[2..placeholder.len() - 2].trim();
+
+            result.push_str(&rest[..start]);
+            match self.get(name) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(placeholder),
+            }
+
+            rest = &rest[start + end + 2..];
+        }
+
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Returns the names of the variables in a text, e.g. `crate_name` for `{{ crate_name }}`
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+
+        names.push(rest[start + 2..start + end].trim());
+        rest = &rest[start + end + 2..];
+    }
+
+    names
+}
+
+/// Read the files of a template directory, with paths relative to the directory
+fn read_template_directory(directory: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut directories = vec![directory.to_path_buf()];
+
+    while let Some(current) = directories.pop() {
+        let entries = read_dir(&current).context(format!(
+            "failed to read the template at {}",
+            current.display()
+        ))?;
+
+        for entry in entries {
+            let path = entry?.path();
+
+            // The history of a template is not part of the projects that are created from it
+            if path.file_name().is_some_and(|name| name == ".git") {
+                continue;
+            }
+
+            if path.is_dir() {
+                directories.push(path);
+                continue;
+            }
+
+            let content = read(&path).context(format!("failed to read {}", path.display()))?;
+            let relative = path
+                .strip_prefix(directory)
+                .map(Path::to_path_buf)
+                .unwrap_or(path);
+
+            files.push((relative, content));
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// Resolve the path of a template directory
+///
+/// A leading `~` is replaced with the home directory of the user, and relative paths are resolved
+/// against the base directory, e.g. the directory of the configuration file that declares them.
+pub fn resolve_path(path: &Path, base: &Path) -> PathBuf {
+    let path = match (path.strip_prefix("~"), std::env::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    };
+
+    base.join(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn variables() -> Variables {
+        Variables::builder()
+            .crate_name("my-app")
+            .author("Jane Doe")
+            .description("An app")
+            .build()
+    }
+
+    #[test]
+    fn replace_substitutes_known_variables() {
+        let text = "name = \"{{crate_name}}\"\nuse {{ crate_ident }}_core;\nrun: ${{ matrix.os }}";
+
+        assert_eq!(
+            "name = \"my-app\"\nuse my_app_core;\nrun: ${{ matrix.os }}",
+            variables().replace(text)
+        );
+    }
+
+    #[test]
+    fn find_returns_built_in_template() {
+        let template = Template::find("full", &BTreeMap::new(), Path::new(".")).unwrap();
+
+        assert_eq!(Template::BuiltIn(FULL), template);
+    }
+
+    #[test]
+    fn find_returns_template_from_configuration() {
+        let directory = TempDir::new().unwrap();
+        create_dir_all(directory.path().join("acme")).unwrap();
+
+        let user_templates =
+            BTreeMap::from([("company".to_string(), directory.path().join("acme"))]);
+        let template =
+            Template::find("company", &user_templates, Path::new("/nonexistent")).unwrap();
+
+        assert_eq!(Template::Directory(directory.path().join("acme")), template);
+    }
+
+    #[test]
+    fn find_resolves_path_relative_to_working_directory() {
+        let directory = TempDir::new().unwrap();
+        create_dir_all(directory.path().join("acme")).unwrap();
+
+        let template = Template::find("acme", &BTreeMap::new(), directory.path()).unwrap();
+
+        assert_eq!(Template::Directory(directory.path().join("acme")), template);
+    }
+
+    #[test]
+    fn resolve_path_joins_relative_paths_to_base() {
+        assert_eq!(
+            PathBuf::from("/home/user/.config/clawless-cli/templates/company"),
+            resolve_path(
+                Path::new("templates/company"),
+                Path::new("/home/user/.config/clawless-cli")
+            )
+        );
+    }
+
+    #[test]
+    fn resolve_path_keeps_absolute_paths() {
+        assert_eq!(
+            PathBuf::from("/opt/templates/company"),
+            resolve_path(Path::new("/opt/templates/company"), Path::new("/project"))
+        );
+    }
+
+    #[test]
+    fn resolve_path_expands_home_directory() {
+        let Some(home) = std::env::home_dir() else {
+            return;
+        };
+
+        assert_eq!(
+            home.join("templates/company"),
+            resolve_path(Path::new("~/templates/company"), Path::new("/project"))
+        );
+    }
+
+    #[test]
+    fn find_fails_for_unknown_template() {
+        let error =
+            Template::find("nope", &BTreeMap::new(), Path::new("/nonexistent")).unwrap_err();
+
+        assert_eq!(
+            "the template `nope` does not exist; use one of `minimal`, `full`, `workspace` or the path of a template directory",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn creates_workspace_detects_workspace_manifest() {
+        let template = TempDir::new().unwrap();
+        write(
+            template.path().join("Cargo.toml"),
+            "[workspace.package]\nedition = \"2024\"\n",
+        )
+        .unwrap();
+
+        assert!(Template::BuiltIn(WORKSPACE).creates_workspace().unwrap());
+        assert!(!Template::BuiltIn(FULL).creates_workspace().unwrap());
+        assert!(!Template::BuiltIn(MINIMAL).creates_workspace().unwrap());
+        assert!(
+            Template::Directory(template.path().to_path_buf())
+                .creates_workspace()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn manifest_returns_manifest_of_template() {
+        assert!(Template::BuiltIn(FULL).manifest().unwrap().is_some());
+        assert_eq!(None, Template::BuiltIn(MINIMAL).manifest().unwrap());
+    }
+
+    #[test]
+    fn uses_variable_finds_variables_in_paths_and_contents() {
+        let template = TempDir::new().unwrap();
+        create_dir_all(template.path().join("{{crate_name}}")).unwrap();
+        write(
+            template.path().join("{{crate_name}}").join("README.md"),
+            "# {{ description }}\n",
+        )
+        .unwrap();
+        let template = Template::Directory(template.path().to_path_buf());
+
+        assert!(template.uses_variable("crate_name").unwrap());
+        assert!(template.uses_variable("description").unwrap());
+        assert!(!template.uses_variable("author").unwrap());
+        assert!(Template::BuiltIn(FULL).uses_variable("author").unwrap());
+        assert!(!Template::BuiltIn(MINIMAL).uses_variable("author").unwrap());
+    }
+
+    #[test]
+    fn render_writes_built_in_template() {
+        let destination = TempDir::new().unwrap();
+
+        Template::BuiltIn(WORKSPACE)
+            .render(destination.path(), &variables())
+            .unwrap();
+
+        let manifest =
+            read_to_string(destination.path().join("crates/my-app-core/Cargo.toml")).unwrap();
+        assert!(manifest.contains("name = \"my-app-core\""));
+
+        let greet = read_to_string(destination.path().join("src/commands/greet.rs")).unwrap();
+        assert!(greet.contains("my_app_core::greeting"));
+    }
+
+    #[test]
+    fn render_copies_template_directory() {
+        let template = TempDir::new().unwrap();
+        create_dir_all(template.path().join("src").join(".git")).unwrap();
+        create_dir_all(template.path().join(".git")).unwrap();
+        write(template.path().join(".git").join("HEAD"), "ref").unwrap();
+        write(
+            template.path().join("src").join("main.rs"),
+            "// {{crate_name}} by {{author}}: {{description}}\n",
+        )
+        .unwrap();
+        write(template.path().join("logo.png"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let destination = TempDir::new().unwrap();
+        let written = Template::Directory(template.path().to_path_buf())
+            .render(destination.path(), &variables())
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                destination.path().join("logo.png"),
+                destination.path().join("src/main.rs"),
+            ],
+            written
+        );
+        assert_eq!(
+            "// my-app by Jane Doe: An app\n",
+            read_to_string(destination.path().join("src/main.rs")).unwrap()
+        );
+        assert_eq!(
+            vec![0xff, 0xfe, 0x00],
+            read(destination.path().join("logo.png")).unwrap()
+        );
+    }
+}
@@ -0,0 +1,8 @@
+mod greet;
+
+// Collect the commands of the application
+//
+// This macro collects all the command functions defined in this module and its sub-modules,
+// and registers them with the Clawless runtime so they can be invoked from the command line.
+// The configuration adds the built-in `config` command, and validates the configuration files.
+clawless::commands!(config = crate::config::Config);
@@ -0,0 +1,27 @@
+use clawless::prelude::*;
+
+use crate::config::Config;
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
+pub struct GreetArgs {
+    /// Name of the person to greet
+    #[arg(default_value = "World")]
+    name: String,
+}
+
+/// Greet the user
+///
+/// The greeting can be changed in the configuration, e.g. with `config set greeting Howdy`.
+#[command]
+pub async fn greet(args: GreetArgs, context: Context) -> CommandResult {
+    let config: Config = context.config().deserialize()?;
+
+    // Details are only printed when the user passes `--verbose`
+    context
+        .output()
+        .verbose(format!("Using the greeting `{}`", config.greeting));
+
+    println!("{}, {}!", config.greeting, args.name);
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of the application
+///
+/// Users can change the settings with the built-in `config` command, and editors can use the
+/// schema from `config schema` to autocomplete the configuration file.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The greeting that is printed before the name
+    pub greeting: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            greeting: "Hello".into(),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! The business logic of {{crate_name}}
+
+/// Returns the greeting for a name
+pub fn greeting(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greeting_includes_name() {
+        assert_eq!("Hello, World!", greeting("World"));
+    }
+}
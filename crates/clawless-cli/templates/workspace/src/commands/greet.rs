@@ -0,0 +1,17 @@
+use clawless::prelude::*;
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
+pub struct GreetArgs {
+    /// Name of the person to greet
+    #[arg(default_value = "World")]
+    name: String,
+}
+
+/// Greet the user
+#[command]
+pub async fn greet(args: GreetArgs, _context: Context) -> CommandResult {
+    // Commands parse the input and print the output, while the logic lives in the core crate
+    println!("{}", {{crate_ident}}_core::greeting(&args.name));
+
+    Ok(())
+}
@@ -0,0 +1,4 @@
+mod greet;
+mod db;
+
+clawless::commands!();
@@ -0,0 +1,4 @@
+mod deploy;
+mod greet;
+
+clawless::commands!();
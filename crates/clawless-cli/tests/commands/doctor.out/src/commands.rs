@@ -0,0 +1,4 @@
+mod greet;
+mod status;
+
+clawless::commands!();
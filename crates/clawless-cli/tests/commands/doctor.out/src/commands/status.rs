@@ -0,0 +1,23 @@
+use clawless::prelude::*;
+
+#[derive(Clone, Debug, Args)]
+pub struct StatusArgs {
+    /// TODO: Describe `--env`
+    #[arg(long)]
+    env: String,
+
+    /// TODO: Describe `--dry-run`
+    #[arg(long)]
+    dry_run: bool,
+
+    /// TODO: Describe `--force`
+    #[arg(long)]
+    force: bool,
+}
+
+/// TODO: Describe the command
+#[command(alias = "s")]
+pub async fn status(args: StatusArgs, context: Context) -> CommandResult {
+    // Command implementation goes here
+    Ok(())
+}
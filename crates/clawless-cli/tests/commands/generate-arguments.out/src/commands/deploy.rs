@@ -0,0 +1,23 @@
+use clawless::prelude::*;
+
+#[derive(Clone, Debug, Args)]
+pub struct DeployArgs {
+    /// TODO: Describe `--env`
+    #[arg(long)]
+    env: String,
+
+    /// TODO: Describe `--dry-run`
+    #[arg(long)]
+    dry_run: bool,
+
+    /// TODO: Describe `--force`
+    #[arg(long)]
+    force: bool,
+}
+
+/// TODO: Describe the command
+#[command(alias = "d")]
+pub async fn deploy(args: DeployArgs, context: Context) -> CommandResult {
+    // Command implementation goes here
+    Ok(())
+}
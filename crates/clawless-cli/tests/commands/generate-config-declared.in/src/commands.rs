@@ -0,0 +1,3 @@
+mod greet;
+
+clawless::commands!(config = crate::Settings);
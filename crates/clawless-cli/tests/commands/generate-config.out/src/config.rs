@@ -0,0 +1,12 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of the application
+///
+/// Users can change the settings with the built-in `config` command, and editors can use the
+/// schema from `config schema` to autocomplete the configuration file.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // Define settings here
+}
@@ -0,0 +1,4 @@
+mod commands;
+mod config;
+
+clawless::main!();
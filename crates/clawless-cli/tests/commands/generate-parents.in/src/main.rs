@@ -0,0 +1,3 @@
+mod commands;
+
+clawless::main!();
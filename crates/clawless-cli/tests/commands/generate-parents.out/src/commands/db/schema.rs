@@ -0,0 +1,11 @@
+use clawless::prelude::*;
+
+mod migrate;
+
+#[derive(Clone, Debug, Args)]
+pub struct SchemaArgs {}
+
+#[command(require_subcommand)]
+pub async fn schema(_args: SchemaArgs, _context: Context) -> CommandResult {
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use clawless::prelude::*;
+
 mod shout;
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
@@ -1,10 +1,11 @@
 use clawless::prelude::*;
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
+#[derive(Clone, Debug, Args)]
 pub struct ShoutArgs {
     // Define command arguments here
 }
 
+/// TODO: Describe the command
 #[command]
 pub async fn shout(args: ShoutArgs, context: Context) -> CommandResult {
     // Command implementation goes here
@@ -0,0 +1,10 @@
+use std::env;
+
+fn main() {
+    println!("Hello, world!");
+}
+
+/// Returns the API token from the environment
+fn token() -> Option<String> {
+    env::var("TOOL_TOKEN").ok()
+}
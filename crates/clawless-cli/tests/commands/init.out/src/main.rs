@@ -0,0 +1,14 @@
+use std::env;
+
+mod commands;
+
+/// Returns the API token from the environment
+fn token() -> Option<String> {
+    env::var("TOOL_TOKEN").ok()
+}
+
+// Initialize and start the Clawless application
+//
+// This macro sets up the Clawless runtime, parses the command-line arguments, and then
+// calls the appropriate command function based on the user's input.
+clawless::main!();
@@ -0,0 +1,5 @@
+mod greet;
+mod db;
+mod deploy;
+
+clawless::commands!();
@@ -0,0 +1,32 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Manage the deployments of our services
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Deploy a service
+    #[command(alias = "d")]
+    Deploy(DeployArgs),
+
+    /// Show the status of all services
+    Status,
+}
+
+#[derive(Debug, Args)]
+pub struct DeployArgs {
+    /// The environment to deploy to
+    #[arg(long, value_enum)]
+    pub environment: Environment,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum Environment {
+    Staging,
+    Production,
+}
@@ -0,0 +1,14 @@
+use clap::Parser;
+
+use crate::cli::{Cli, Commands};
+
+mod cli;
+
+fn main() {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Deploy(args) => println!("Deploying to {:?}", args.environment),
+        Commands::Status => println!("All services are running"),
+    }
+}
@@ -0,0 +1,17 @@
+use clawless::prelude::*;
+
+use crate::cli::Environment;
+
+#[derive(Clone, Debug, Args)]
+pub struct DeployArgs {
+    /// The environment to deploy to
+    #[arg(long, value_enum)]
+    pub environment: Environment,
+}
+
+/// Deploy a service
+#[command(alias = "d")]
+pub async fn deploy(args: DeployArgs, context: Context) -> CommandResult {
+    // Command implementation goes here
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use clawless::prelude::*;
+
+#[derive(Clone, Debug, Args)]
+pub struct StatusArgs {}
+
+/// Show the status of all services
+#[command]
+pub async fn status(args: StatusArgs, context: Context) -> CommandResult {
+    // Command implementation goes here
+    Ok(())
+}
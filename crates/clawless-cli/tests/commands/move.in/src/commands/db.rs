@@ -0,0 +1,11 @@
+use clawless::prelude::*;
+
+mod schema;
+
+#[derive(Clone, Debug, Args)]
+pub struct DbArgs {}
+
+#[command(require_subcommand)]
+pub async fn db(_args: DbArgs, _context: Context) -> CommandResult {
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use clawless::prelude::*;
+
+#[derive(Clone, Debug, Args)]
+pub struct MigrateArgs {
+    // Define command arguments here
+}
+
+/// TODO: Describe the command
+#[command]
+pub async fn migrate(args: MigrateArgs, context: Context) -> CommandResult {
+    // Command implementation goes here
+    Ok(())
+}
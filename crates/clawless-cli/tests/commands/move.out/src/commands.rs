@@ -0,0 +1,5 @@
+mod greet;
+mod db;
+mod migrate;
+
+clawless::commands!();
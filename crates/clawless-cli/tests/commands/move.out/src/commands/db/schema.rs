@@ -0,0 +1,9 @@
+use clawless::prelude::*;
+
+#[derive(Clone, Debug, Args)]
+pub struct SchemaArgs {}
+
+#[command(require_subcommand)]
+pub async fn schema(_args: SchemaArgs, _context: Context) -> CommandResult {
+    Ok(())
+}
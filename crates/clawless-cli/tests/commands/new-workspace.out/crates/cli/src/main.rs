@@ -0,0 +1,7 @@
+mod commands;
+
+// Initialize and start the Clawless application
+//
+// This macro sets up the Clawless runtime, parses the command-line arguments, and then
+// calls the appropriate command function based on the user's input.
+clawless::main!();
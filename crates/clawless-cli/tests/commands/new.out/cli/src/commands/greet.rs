@@ -7,8 +7,9 @@ pub struct GreetArgs {
     name: String,
 }
 
+/// Greet the user
 #[command]
-pub async fn greet(args: GreetArgs) -> CommandResult {
+pub async fn greet(args: GreetArgs, _context: Context) -> CommandResult {
     // Print the greeting to the console
     println!("Hello, {}!", args.name);
 
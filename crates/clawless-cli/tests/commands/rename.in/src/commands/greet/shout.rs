@@ -0,0 +1,13 @@
+use clawless::prelude::*;
+
+#[derive(Clone, Debug, Args)]
+pub struct ShoutArgs {
+    // Define command arguments here
+}
+
+/// TODO: Describe the command
+#[command]
+pub async fn shout(args: ShoutArgs, context: Context) -> CommandResult {
+    // Command implementation goes here
+    Ok(())
+}
@@ -0,0 +1,3 @@
+mod hello;
+
+clawless::commands!();
@@ -0,0 +1,15 @@
+use clawless::prelude::*;
+
+mod shout;
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Args)]
+pub struct HelloArgs {
+    #[arg(default_value = "World")]
+    name: String,
+}
+
+#[command]
+pub async fn hello(args: HelloArgs) -> CommandResult {
+    println!("Hello, {}!", args.name);
+    Ok(())
+}
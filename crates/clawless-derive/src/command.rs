@@ -2,7 +2,8 @@ use darling::FromMeta;
 use darling::ast::NestedMeta;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{Expr, FnArg, Ident, ItemFn, Lit, Meta, PatType, Type};
+use syn::ext::IdentExt;
+use syn::{Expr, FnArg, Ident, ItemFn, Lit, LitStr, Meta, PatType, Path, Type};
 
 use crate::inventory::inventory_name;
 
@@ -12,15 +13,39 @@ pub struct CommandGenerator {
     ident: Ident,
 }
 
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, FromMeta, Default)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, FromMeta, Default)]
 struct Attributes {
     /// Require a subcommand; show help if invoked without one
     #[darling(default)]
     require_subcommand: bool,
     #[darling(default)]
     root: bool,
+    /// Name of the command on the command line, instead of the name of the function
+    #[darling(default)]
+    name: Option<String>,
     #[darling(default, multiple)]
     alias: Vec<String>,
+    /// Hide the command from the help and from the suggestions for mistyped commands
+    #[darling(default)]
+    hide: bool,
+    /// Command that replaces this deprecated command, relative to its parent
+    #[darling(default)]
+    replaced_by: Option<String>,
+    /// Accept unambiguous prefixes of subcommands for this command and its descendants
+    #[darling(default)]
+    infer_subcommands: bool,
+    /// Dispatch unknown subcommands to plugins on the `PATH`
+    #[darling(default)]
+    external_subcommands: bool,
+    /// Function that returns the theme whose styles are used for the help of this command
+    #[darling(default)]
+    theme: Option<Path>,
+    /// Configuration struct of the application, which enables the built-in `config` command
+    #[darling(default)]
+    config: Option<Path>,
+    /// Long names of the global flags that are not added to the root command
+    #[darling(default)]
+    without_global_flags: Vec<LitStr>,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -45,6 +70,17 @@ impl CommandGenerator {
         self.ident.clone()
     }
 
+    /// Returns the name of the command on the command line
+    ///
+    /// The command is named after its function, unless the `name` attribute overrides it. Raw
+    /// identifiers such as `r#move` are used without their prefix.
+    pub fn command_name(&self) -> String {
+        self.attrs
+            .name
+            .clone()
+            .unwrap_or_else(|| self.ident.unraw().to_string())
+    }
+
     pub fn is_root(&self) -> bool {
         self.attrs.root
     }
@@ -57,11 +93,35 @@ impl CommandGenerator {
         format_ident!("{}_exec", self.ident)
     }
 
+    pub fn replacement_function_name(&self) -> Ident {
+        format_ident!("{}_replacement", self.ident)
+    }
+
+    pub fn prompts_function_name(&self) -> Ident {
+        format_ident!("{}_prompts", self.ident)
+    }
+
+    pub fn replaced_by(&self) -> Option<&str> {
+        self.attrs.replaced_by.as_deref()
+    }
+
     pub fn initialization_function(&self) -> TokenStream {
         let function_name = self.initialization_function_name();
         let command_new = self.command_new();
         let inventory_name = inventory_name();
 
+        // The global flags are added last, so that flags that are used by commands can be skipped
+        let without_global_flags = &self.attrs.without_global_flags;
+        let global_args = if self.is_root() && without_global_flags.is_empty() {
+            quote! { clawless::context::GlobalArgs::augment_command(command) }
+        } else if self.is_root() {
+            quote! {
+                clawless::context::GlobalArgs::augment_command_without(command, &[#(#without_global_flags),*])
+            }
+        } else {
+            quote! { command }
+        };
+
         quote! {
             pub fn #function_name() -> clawless::clap::Command {
                 let mut command = #command_new;
@@ -70,31 +130,84 @@ impl CommandGenerator {
                     command = command.subcommand((subcommand.init)());
                 }
 
-                command
+                #global_args
             }
         }
     }
 
     pub fn wrapper_function(&self) -> TokenStream {
         let wrapper_function_name = self.wrapper_function_name();
+        let external_subcommand_dispatch = self.external_subcommand_dispatch();
         let wrapper_function_body = self.wrapper_function_body();
+        let config_subcommand_dispatch = self.config_subcommand_dispatch();
+        let manifest_dispatch = self.manifest_dispatch();
         let inventory_name = inventory_name();
 
         quote! {
             pub async fn #wrapper_function_name(args: clawless::clap::ArgMatches, context: clawless::context::Context) -> clawless::CommandResult {
+                #manifest_dispatch
+
+                #config_subcommand_dispatch
+
                 for subcommand in clawless::inventory::iter::<#inventory_name> {
                     if let Some(matches) = args.subcommand_matches(subcommand.name) {
                         return (subcommand.func)(matches.clone(), context).await;
                     }
                 }
 
+                #external_subcommand_dispatch
+
                 #wrapper_function_body
             }
         }
     }
 
+    /// Returns a function that finds the replacement of a deprecated subcommand by its path
+    pub fn replacement_function(&self) -> TokenStream {
+        let function_name = self.replacement_function_name();
+        let inventory_name = inventory_name();
+
+        quote! {
+            pub fn #function_name(path: &[&str]) -> Option<&'static str> {
+                let (name, path) = path.split_first()?;
+                let subcommand = clawless::inventory::iter::<#inventory_name>
+                    .into_iter()
+                    .find(|subcommand| subcommand.name == *name)?;
+
+                if path.is_empty() {
+                    subcommand.replaced_by
+                } else {
+                    (subcommand.replacement)(path)
+                }
+            }
+        }
+    }
+
+    /// Returns a function that finds the prompts of the arguments of a command by its path
+    pub fn prompts_function(&self) -> TokenStream {
+        let function_name = self.prompts_function_name();
+        let args_type = extract_function_argument_type(&self.input);
+        let inventory_name = inventory_name();
+
+        quote! {
+            pub fn #function_name(path: &[&str]) -> Vec<(String, String)> {
+                let Some((name, path)) = path.split_first() else {
+                    return clawless::runtime::collect_prompts(|| {
+                        #args_type::augment_args(clawless::clap::Command::new(""));
+                    });
+                };
+
+                clawless::inventory::iter::<#inventory_name>
+                    .into_iter()
+                    .find(|subcommand| subcommand.name == *name)
+                    .map(|subcommand| (subcommand.prompts)(path))
+                    .unwrap_or_default()
+            }
+        }
+    }
+
     fn command_new(&self) -> TokenStream {
-        let command_name = self.ident.to_string();
+        let command_name = self.command_name();
         let args_type = extract_function_argument_type(&self.input);
         let docs = extract_function_documentation(&self.input);
 
@@ -104,7 +217,9 @@ impl CommandGenerator {
 
         if self.is_root() {
             command = quote! {
-                #command.about(clawless::clap::crate_description!())
+                #command
+                    .about(clawless::clap::crate_description!())
+                    .arg(clawless::manifest::flag())
             };
         } else if let Some(docs) = docs {
             let Documentation { short, long } = docs;
@@ -120,6 +235,37 @@ impl CommandGenerator {
             };
         }
 
+        // Deprecated commands are hidden, so that users discover their replacement instead
+        if self.attrs.hide || self.attrs.replaced_by.is_some() {
+            command = quote! {
+                #command.hide(true)
+            };
+        }
+
+        if self.attrs.infer_subcommands {
+            command = quote! {
+                #command.infer_subcommands(true)
+            };
+        }
+
+        if self.attrs.external_subcommands {
+            command = quote! {
+                #command.allow_external_subcommands(true)
+            };
+        }
+
+        if let Some(theme) = &self.attrs.theme {
+            command = quote! {
+                #command.styles(#theme().styles())
+            };
+        }
+
+        if self.attrs.config.is_some() {
+            command = quote! {
+                #command.subcommand(clawless::config::command::command())
+            };
+        }
+
         if !self.attrs.alias.is_empty() {
             let aliases = &self.attrs.alias;
             command = quote! {
@@ -130,16 +276,85 @@ impl CommandGenerator {
         command
     }
 
+    fn external_subcommand_dispatch(&self) -> TokenStream {
+        if !self.attrs.external_subcommands {
+            return quote! {};
+        }
+
+        let initialization_function_name = self.initialization_function_name();
+        let replacement_function_name = self.replacement_function_name();
+
+        // Subcommands that were not matched by the inventory are provided by plugins
+        quote! {
+            if let Some((name, matches)) = args.subcommand() {
+                return clawless::plugins::dispatch(
+                    env!("CARGO_PKG_NAME"),
+                    name,
+                    matches,
+                    &context,
+                    #initialization_function_name,
+                    #replacement_function_name,
+                ).await;
+            }
+        }
+    }
+
+    fn manifest_dispatch(&self) -> TokenStream {
+        if !self.is_root() {
+            return quote! {};
+        }
+
+        // The manifest is printed before the configuration is validated, like `--help`
+        quote! {
+            if clawless::manifest::is_requested(&args) {
+                return clawless::manifest::print(&context);
+            }
+        }
+    }
+
+    fn config_subcommand_dispatch(&self) -> TokenStream {
+        let Some(config) = &self.attrs.config else {
+            return quote! {};
+        };
+
+        // The `config` command must work with invalid files so that users can fix them, while
+        // every other command requires a valid configuration
+        quote! {
+            if let Some(matches) = args.subcommand_matches(clawless::config::command::COMMAND_NAME) {
+                return clawless::config::command::run::<#config>(matches, &context, env!("CARGO_PKG_NAME")).await;
+            }
+
+            context.config().validate::<#config>()?;
+        }
+    }
+
     fn wrapper_function_body(&self) -> TokenStream {
         let args_type = extract_function_argument_type(&self.input);
         let command = self.ident();
+        let deprecation_warning = self.deprecation_warning();
 
         quote! {
             use clawless::clap::FromArgMatches;
             let args = #args_type::from_arg_matches(&args).unwrap();
+            #deprecation_warning
             #command(args, context).await
         }
     }
+
+    fn deprecation_warning(&self) -> TokenStream {
+        let Some(replacement) = &self.attrs.replaced_by else {
+            return quote! {};
+        };
+
+        let warning = format!(
+            "`{}` is deprecated, use `{replacement}` instead",
+            self.command_name()
+        );
+
+        quote! {
+            context.output().warning(#warning);
+        }
+    }
 }
 
 fn parse_attributes(attrs: TokenStream) -> Attributes {
@@ -376,6 +591,317 @@ mod tests {
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn command_new_with_snake_case_name() {
+        let input = quote! {
+            fn check_updates(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(TokenStream::new(), input_function);
+
+        let actual = generator.command_new();
+        let expected = quote! {
+            Args::augment_args(clawless::clap::Command::new("check_updates"))
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn command_new_with_name() {
+        let attrs = quote! {
+            name = "check-updates"
+        };
+
+        let input = quote! {
+            fn check_updates(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.command_new();
+        let expected = quote! {
+            Args::augment_args(clawless::clap::Command::new("check-updates"))
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn command_name_without_raw_prefix() {
+        let input = quote! {
+            fn r#move(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(TokenStream::new(), input_function);
+
+        assert_eq!("move", generator.command_name());
+    }
+
+    #[test]
+    fn command_new_with_infer_subcommands() {
+        let attrs = quote! {
+            require_subcommand, infer_subcommands
+        };
+
+        let input = quote! {
+            fn foo(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.command_new();
+        let expected = quote! {
+            Args::augment_args(clawless::clap::Command::new("foo")).arg_required_else_help(true).infer_subcommands(true)
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn command_new_with_theme() {
+        let attrs = quote! {
+            theme = brand::theme
+        };
+
+        let input = quote! {
+            fn foo(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.command_new();
+        let expected = quote! {
+            Args::augment_args(clawless::clap::Command::new("foo")).styles(brand::theme().styles())
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn initialization_function_adds_global_args_to_root() {
+        let input = quote! {
+            fn clawless(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let root = CommandGenerator::new(quote! { root = true }, input_function);
+
+        let global_args = quote! { clawless::context::GlobalArgs::augment_command(command) };
+
+        assert!(
+            root.initialization_function()
+                .to_string()
+                .contains(&global_args.to_string())
+        );
+        assert!(
+            !generator_with_args()
+                .initialization_function()
+                .to_string()
+                .contains(&global_args.to_string())
+        );
+    }
+
+    #[test]
+    fn initialization_function_leaves_out_global_flags() {
+        let input = quote! {
+            fn clawless(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let root = CommandGenerator::new(
+            quote! { root = true, without_global_flags = ["output", "verbose"] },
+            input_function,
+        );
+
+        let global_args = quote! {
+            clawless::context::GlobalArgs::augment_command_without(command, &["output", "verbose"])
+        };
+
+        assert!(
+            root.initialization_function()
+                .to_string()
+                .contains(&global_args.to_string())
+        );
+    }
+
+    #[test]
+    fn command_new_with_external_subcommands() {
+        let attrs = quote! {
+            external_subcommands
+        };
+
+        let input = quote! {
+            fn foo(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.command_new();
+        let expected = quote! {
+            Args::augment_args(clawless::clap::Command::new("foo")).allow_external_subcommands(true)
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn command_new_with_config() {
+        let attrs = quote! {
+            config = crate::settings::Settings
+        };
+
+        let input = quote! {
+            fn foo(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.command_new();
+        let expected = quote! {
+            Args::augment_args(clawless::clap::Command::new("foo")).subcommand(clawless::config::command::command())
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn config_subcommand_dispatch_with_config() {
+        let attrs = quote! {
+            config = Settings
+        };
+
+        let input = quote! {
+            fn foo(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.config_subcommand_dispatch();
+        let expected = quote! {
+            if let Some(matches) = args.subcommand_matches(clawless::config::command::COMMAND_NAME) {
+                return clawless::config::command::run::<Settings>(matches, &context, env!("CARGO_PKG_NAME")).await;
+            }
+
+            context.config().validate::<Settings>()?;
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn manifest_dispatch_only_for_root() {
+        let input = quote! {
+            fn clawless(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let root = CommandGenerator::new(quote! { root = true }, input_function);
+
+        let expected = quote! {
+            if clawless::manifest::is_requested(&args) {
+                return clawless::manifest::print(&context);
+            }
+        };
+
+        assert_eq!(root.manifest_dispatch().to_string(), expected.to_string());
+        assert!(generator_with_args().manifest_dispatch().is_empty());
+    }
+
+    #[test]
+    fn config_subcommand_dispatch_without_attribute() {
+        let generator = generator_with_args();
+
+        assert!(generator.config_subcommand_dispatch().is_empty());
+    }
+
+    #[test]
+    fn external_subcommand_dispatch_passes_command_and_replacements() {
+        let input = quote! {
+            fn clawless(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(quote! { external_subcommands }, input_function);
+
+        let actual = generator.external_subcommand_dispatch();
+        let expected = quote! {
+            if let Some((name, matches)) = args.subcommand() {
+                return clawless::plugins::dispatch(
+                    env!("CARGO_PKG_NAME"),
+                    name,
+                    matches,
+                    &context,
+                    clawless_init,
+                    clawless_replacement,
+                ).await;
+            }
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn external_subcommand_dispatch_without_attribute() {
+        let generator = generator_with_args();
+
+        assert!(generator.external_subcommand_dispatch().is_empty());
+    }
+
+    #[test]
+    fn command_new_with_replaced_by() {
+        let attrs = quote! {
+            replaced_by = "deploy"
+        };
+
+        let input = quote! {
+            fn publish(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.command_new();
+        let expected = quote! {
+            Args::augment_args(clawless::clap::Command::new("publish")).hide(true)
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+        assert_eq!(Some("deploy"), generator.replaced_by());
+    }
+
+    #[test]
+    fn wrapper_function_body_with_replaced_by() {
+        let attrs = quote! {
+            replaced_by = "deploy"
+        };
+
+        let input = quote! {
+            fn publish(args: Args, context: Context) {}
+        };
+
+        let input_function = syn::parse2::<ItemFn>(input).unwrap();
+        let generator = CommandGenerator::new(attrs, input_function);
+
+        let actual = generator.wrapper_function_body();
+        let expected = quote! {
+            use clawless::clap::FromArgMatches;
+            let args = Args::from_arg_matches(&args).unwrap();
+            context.output().warning("`publish` is deprecated, use `deploy` instead");
+            publish(args, context).await
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
     #[test]
     fn wrapper_function_body() {
         let generator = generator_with_args();
@@ -0,0 +1,208 @@
+use darling::FromMeta;
+use darling::ast::NestedMeta;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{LitStr, Path};
+
+pub struct CommandsGenerator {
+    options: Options,
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug, FromMeta, Default)]
+struct Options {
+    /// Accept unambiguous prefixes of subcommands and their aliases
+    #[darling(default)]
+    infer_subcommands: bool,
+    /// Dispatch unknown subcommands to executables named `<app>-<subcommand>` on the `PATH`
+    #[darling(default)]
+    external_subcommands: bool,
+    /// Function that returns the `Theme` of the application
+    #[darling(default)]
+    theme: Option<Path>,
+    /// Configuration struct of the application, which enables the built-in `config` command
+    #[darling(default)]
+    config: Option<Path>,
+    /// Long names of the global flags that are not added, so that commands can use the names
+    #[darling(default)]
+    without_global_flags: Vec<LitStr>,
+}
+
+impl CommandsGenerator {
+    pub fn new(input: TokenStream) -> darling::Result<Self> {
+        let argument_list = NestedMeta::parse_meta_list(input)?;
+        let options = Options::from_list(&argument_list)?;
+
+        Ok(Self { options })
+    }
+
+    pub fn commands(&self) -> TokenStream {
+        let root_attributes = self.root_attributes();
+        let theme = self.theme();
+
+        quote! {
+            use clawless::prelude::*;
+            #[derive(Debug, clawless::clap::Args)]
+            struct ClawlessEntryPoint {}
+
+            #[clawless::command(#root_attributes)]
+            async fn clawless(_args: ClawlessEntryPoint, _context: clawless::context::Context) -> clawless::CommandResult {
+                Ok(())
+            }
+
+            clawless::inventory::submit!(clawless::registry::RootCommand { init: clawless_init });
+
+            #theme
+        }
+    }
+
+    fn theme(&self) -> TokenStream {
+        let theme = match &self.options.theme {
+            Some(path) => quote! { #path() },
+            None => quote! { clawless::theme::Theme::default() },
+        };
+
+        quote! {
+            pub fn clawless_theme() -> clawless::theme::Theme {
+                #theme
+            }
+        }
+    }
+
+    fn root_attributes(&self) -> TokenStream {
+        let mut attributes = quote! {
+            require_subcommand, root = true
+        };
+
+        if self.options.infer_subcommands {
+            attributes = quote! {
+                #attributes, infer_subcommands
+            };
+        }
+
+        if self.options.external_subcommands {
+            attributes = quote! {
+                #attributes, external_subcommands
+            };
+        }
+
+        if let Some(theme) = &self.options.theme {
+            attributes = quote! {
+                #attributes, theme = #theme
+            };
+        }
+
+        if let Some(config) = &self.options.config {
+            attributes = quote! {
+                #attributes, config = #config
+            };
+        }
+
+        if !self.options.without_global_flags.is_empty() {
+            let flags = &self.options.without_global_flags;
+            attributes = quote! {
+                #attributes, without_global_flags = [#(#flags),*]
+            };
+        }
+
+        attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_attributes_without_options() {
+        let generator = CommandsGenerator::new(TokenStream::new()).unwrap();
+
+        let actual = generator.root_attributes();
+        let expected = quote! {
+            require_subcommand, root = true
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn root_attributes_with_infer_subcommands() {
+        let generator = CommandsGenerator::new(quote! { infer_subcommands }).unwrap();
+
+        let actual = generator.root_attributes();
+        let expected = quote! {
+            require_subcommand, root = true, infer_subcommands
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn root_attributes_with_multiple_options() {
+        let generator =
+            CommandsGenerator::new(quote! { infer_subcommands, external_subcommands }).unwrap();
+
+        let actual = generator.root_attributes();
+        let expected = quote! {
+            require_subcommand, root = true, infer_subcommands, external_subcommands
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn root_attributes_with_theme() {
+        let generator = CommandsGenerator::new(quote! { theme = crate::brand::theme }).unwrap();
+
+        let actual = generator.root_attributes();
+        let expected = quote! {
+            require_subcommand, root = true, theme = crate::brand::theme
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn root_attributes_with_config() {
+        let generator =
+            CommandsGenerator::new(quote! { config = crate::settings::Settings }).unwrap();
+
+        let actual = generator.root_attributes();
+        let expected = quote! {
+            require_subcommand, root = true, config = crate::settings::Settings
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn root_attributes_with_without_global_flags() {
+        let generator =
+            CommandsGenerator::new(quote! { without_global_flags = ["output"] }).unwrap();
+
+        let actual = generator.root_attributes();
+        let expected = quote! {
+            require_subcommand, root = true, without_global_flags = ["output"]
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn theme_defaults_to_default_theme() {
+        let generator = CommandsGenerator::new(TokenStream::new()).unwrap();
+
+        let actual = generator.theme();
+        let expected = quote! {
+            pub fn clawless_theme() -> clawless::theme::Theme {
+                clawless::theme::Theme::default()
+            }
+        };
+
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn new_rejects_unknown_options() {
+        assert!(CommandsGenerator::new(quote! { unknown_option }).is_err());
+    }
+}
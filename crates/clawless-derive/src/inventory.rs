@@ -23,6 +23,9 @@ impl<'a> InventoryGenerator<'a> {
                 name: &'static str,
                 init: fn() -> clawless::clap::Command,
                 func: fn(clawless::clap::ArgMatches, clawless::context::Context) -> std::pin::Pin<Box<dyn std::future::Future<Output = clawless::CommandResult>>>,
+                replaced_by: Option<&'static str>,
+                replacement: fn(&[&str]) -> Option<&'static str>,
+                prompts: fn(&[&str]) -> Vec<(String, String)>,
             }
             clawless::inventory::collect!(#inventory_name);
         }
@@ -34,15 +37,24 @@ impl<'a> InventoryGenerator<'a> {
         }
 
         let inventory_name = inventory_name();
-        let command = self.command_generator.ident().to_string();
+        let command = self.command_generator.command_name();
         let init_fn_name = self.command_generator.initialization_function_name();
         let wrapper_fn_name = self.command_generator.wrapper_function_name();
+        let replacement_fn_name = self.command_generator.replacement_function_name();
+        let prompts_fn_name = self.command_generator.prompts_function_name();
+        let replaced_by = match self.command_generator.replaced_by() {
+            Some(replacement) => quote! { Some(#replacement) },
+            None => quote! { None },
+        };
 
         quote! {
             clawless::inventory::submit!(super::#inventory_name {
                 name: #command,
                 init: #init_fn_name,
                 func: |args, context| Box::pin(#wrapper_fn_name(args, context)),
+                replaced_by: #replaced_by,
+                replacement: #replacement_fn_name,
+                prompts: #prompts_fn_name,
             });
         }
     }
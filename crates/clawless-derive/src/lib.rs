@@ -6,9 +6,11 @@ use quote::quote;
 use syn::{ItemFn, parse_macro_input};
 
 use crate::command::CommandGenerator;
+use crate::commands::CommandsGenerator;
 use crate::inventory::InventoryGenerator;
 
 mod command;
+mod commands;
 mod inventory;
 
 /// Set up the commands module for a Clawless application
@@ -17,6 +19,9 @@ mod inventory;
 /// be registered under it. It should be called inside `src/commands.rs` or `src/commands/mod.rs` to
 /// follow Clawless's convention.
 ///
+/// The root command is also registered with `clawless::registry()`, which exposes the command tree
+/// of the application at runtime.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -26,19 +31,34 @@ mod inventory;
 ///
 /// clawless::commands!();
 /// ```
+///
+/// # Options
+///
+/// - `infer_subcommands` - Accept unambiguous prefixes of subcommands and their aliases, e.g.
+///   `mycli dep` for `mycli deploy`. The setting applies to all levels of the command tree.
+/// - `external_subcommands` - Dispatch unknown subcommands to executables named
+///   `<app>-<subcommand>` on the `PATH`, like `cargo` and `git` do. Installed plugins are listed
+///   in the help of the application.
+/// - `theme = path::to::function` - Style the help and the output of the application with the
+///   `clawless::theme::Theme` that the function returns.
+/// - `config = path::to::Struct` - Declare the configuration struct of the application, and add a
+///   built-in `config` command that lets users inspect and edit their configuration. The struct
+///   must implement `Default`, `serde::Serialize`, `serde::Deserialize`, and
+///   `schemars::JsonSchema`. The configuration files are validated against the struct before any
+///   other command runs. See `clawless::config::command` for details.
+///
+/// ```rust,ignore
+/// // src/commands.rs
+/// mod deploy;
+///
+/// clawless::commands!(infer_subcommands);
+/// ```
 #[proc_macro]
-pub fn commands(_input: TokenStream) -> TokenStream {
-    let output = quote! {
-        use clawless::prelude::*;
-        #[derive(Debug, clawless::clap::Args)]
-        struct ClawlessEntryPoint {}
-
-        #[clawless::command(require_subcommand, root = true)]
-        async fn clawless(_args: ClawlessEntryPoint, _context: clawless::context::Context) -> clawless::CommandResult {
-            Ok(())
-        }
-    };
-    output.into()
+pub fn commands(input: TokenStream) -> TokenStream {
+    match CommandsGenerator::new(input.into()) {
+        Ok(generator) => generator.commands().into(),
+        Err(error) => error.write_errors().into(),
+    }
 }
 
 /// Initialize and run a Clawless application
@@ -46,6 +66,9 @@ pub fn commands(_input: TokenStream) -> TokenStream {
 /// This macro generates the `main` function for a Clawless application.
 /// It should be called in `src/main.rs` after declaring the `commands` module.
 ///
+/// Before the command line is parsed, the configuration of the application is loaded and the
+/// user-defined aliases from its `[alias]` table are expanded. See `clawless::aliases` for details.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -58,13 +81,38 @@ pub fn commands(_input: TokenStream) -> TokenStream {
 pub fn main(_input: TokenStream) -> TokenStream {
     let output = quote! {
         fn main() -> Result<(), Box<dyn std::error::Error>> {
-            let context = clawless::context::Context::try_new()?;
+            let app = commands::clawless_init();
+            let output = clawless::runtime::output(env!("CARGO_PKG_NAME"), commands::clawless_theme());
+
+            // Errors in the configuration are reported when the context is created, so that the
+            // help can still be shown
+            let config = clawless::runtime::load_config(env!("CARGO_PKG_NAME"));
+            let mut aliases = match &config {
+                Ok(config) => clawless::aliases::Aliases::from_config(config),
+                Err(_) => clawless::aliases::Aliases::default(),
+            };
+            let args = aliases.expand(&app, std::env::args_os().collect())?;
+
+            let matches = clawless::runtime::get_matches(
+                app,
+                args,
+                env!("CARGO_PKG_NAME"),
+                commands::clawless_replacement,
+                commands::clawless_prompts,
+                &output,
+            );
+            let context = clawless::context::Context::try_from_matches(
+                &matches,
+                env!("CARGO_PKG_NAME"),
+                commands::clawless_theme(),
+                config?,
+            )?;
+            for warning in aliases.warnings() {
+                context.output().warning(warning);
+            }
 
             let rt = clawless::tokio::runtime::Runtime::new()?;
-            rt.block_on(async {
-                let app = commands::clawless_init();
-                commands::clawless_exec(app.get_matches(), context.clone()).await
-            })?;
+            rt.block_on(async { commands::clawless_exec(matches, context).await })?;
 
             Ok(())
         }
@@ -85,8 +133,14 @@ pub fn main(_input: TokenStream) -> TokenStream {
 ///
 /// # Attributes
 ///
+/// - `name = "name"` - Use a different name for the command on the command line, e.g.
+///   `name = "check-updates"` for the function `check_updates`.
 /// - `alias = "name"` - Add a visible alias for the command. Can be repeated for multiple aliases.
 /// - `require_subcommand` - Require a subcommand; show help if the command is invoked without one.
+/// - `hide` - Hide the command from the help, and never suggest it for mistyped commands.
+/// - `replaced_by = "name"` - Deprecate the command in favor of another command of the same parent.
+///   The command is hidden, prints a warning when it is used, and mistyped commands that are
+///   similar to its name suggest the replacement instead.
 ///
 /// # Requiring Subcommands
 ///
@@ -158,6 +212,8 @@ pub fn command(attrs: TokenStream, input: TokenStream) -> TokenStream {
 
     let initialization_function_for_command = command_generator.initialization_function();
     let wrapper_function_for_command = command_generator.wrapper_function();
+    let replacement_function_for_command = command_generator.replacement_function();
+    let prompts_function_for_command = command_generator.prompts_function();
 
     let output = quote! {
         #inventory_struct_for_subcommands
@@ -168,6 +224,10 @@ pub fn command(attrs: TokenStream, input: TokenStream) -> TokenStream {
 
         #wrapper_function_for_command
 
+        #replacement_function_for_command
+
+        #prompts_function_for_command
+
         #submit_command_to_inventory
     };
 
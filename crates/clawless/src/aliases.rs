@@ -0,0 +1,445 @@
+//! Command aliases that are defined by users
+//!
+//! In addition to the aliases that developers declare with `#[command(alias = "...")]`, users can
+//! define their own aliases in the configuration file of an application, similar to `cargo`:
+//!
+//! ```toml
+//! [alias]
+//! dp = "deploy --env prod"
+//! st = ["status", "--verbose"]
+//! ```
+//!
+//! Aliases are expanded before the command line is parsed, so `mycli dp --dry-run` runs
+//! `mycli deploy --env prod --dry-run`. An alias can refer to another alias, but aliases cannot
+//! shadow the commands of the application.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::Command;
+use toml::Value;
+
+use crate::config::Config;
+
+/// The table in the configuration file that contains the aliases
+const ALIAS_TABLE: &str = "alias";
+
+/// User-defined aliases for commands
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Aliases {
+    aliases: BTreeMap<String, Vec<String>>,
+    warnings: Vec<String>,
+}
+
+impl Aliases {
+    /// Read the aliases from the `[alias]` table of the configuration of an application
+    ///
+    /// An alias can be either a string, which is split on whitespace, or an array of strings.
+    /// Aliases are defined in the same files as the rest of the configuration, and aliases of the
+    /// project override aliases of the user with the same name. Invalid aliases are ignored with a
+    /// warning, so that a typo in one alias does not break every command of the application.
+    pub fn from_config(config: &Config) -> Aliases {
+        let Some(entry) = config.get(ALIAS_TABLE) else {
+            return Aliases::default();
+        };
+
+        let Value::Table(alias_table) = entry.value() else {
+            return Aliases {
+                aliases: BTreeMap::new(),
+                warnings: vec![format!(
+                    "`{ALIAS_TABLE}`{} must be a table, ignoring all user-defined aliases",
+                    location(entry.path().as_deref())
+                )],
+            };
+        };
+
+        let mut aliases = BTreeMap::new();
+        let mut warnings = Vec::new();
+
+        for (name, value) in alias_table {
+            match expansion(name, value) {
+                Ok(expansion) => {
+                    aliases.insert(name.clone(), expansion);
+                }
+                Err(error) => {
+                    let path = config
+                        .get(&format!("{ALIAS_TABLE}.{name}"))
+                        .and_then(|entry| entry.path().clone());
+
+                    warnings.push(format!(
+                        "{error}{}, ignoring the alias",
+                        location(path.as_deref())
+                    ));
+                }
+            }
+        }
+
+        Aliases { aliases, warnings }
+    }
+
+    /// Returns the expansion of an alias
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.aliases.get(name).map(Vec::as_slice)
+    }
+
+    /// Returns the warnings about aliases that were ignored
+    ///
+    /// The warnings are collected while the aliases are loaded and expanded, and are printed
+    /// through the output of the context once the global flags, e.g. `--color`, have been parsed.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Expand the aliases in the arguments of a command line
+    ///
+    /// The first argument is the name of the binary, just like in `std::env::args_os()`. The
+    /// subcommand is looked up after any flags that are defined on the root command. If it is an
+    /// alias, it is replaced by the expansion of the alias, which is repeated until the subcommand
+    /// is no longer an alias. Commands of the application always take precedence over aliases, and
+    /// aliases that are shadowed by a command are added to the warnings.
+    pub fn expand(&mut self, command: &Command, mut args: Vec<OsString>) -> Result<Vec<OsString>> {
+        let mut expanded: Vec<String> = Vec::new();
+
+        loop {
+            let Some(index) = subcommand_index(command, &args) else {
+                return Ok(args);
+            };
+
+            let Some(name) = args[index].to_str().map(String::from) else {
+                return Ok(args);
+            };
+
+            let Some(expansion) = self.aliases.get(&name) else {
+                return Ok(args);
+            };
+
+            if is_builtin(command, &name) {
+                // Aliases that expand to a command are fine, but the user should know when an alias
+                // that they typed is never used
+                if expanded.is_empty() {
+                    self.warnings.push(format!(
+                        "user-defined alias `{name}` is ignored, because it is shadowed by a built-in command"
+                    ));
+                }
+                return Ok(args);
+            }
+
+            if expanded.contains(&name) {
+                expanded.push(name);
+                anyhow::bail!(
+                    "alias `{}` is defined recursively: {}",
+                    expanded[0],
+                    expanded.join(" -> ")
+                );
+            }
+
+            args.splice(index..=index, expansion.iter().map(OsString::from));
+            expanded.push(name);
+        }
+    }
+}
+
+/// Returns the arguments that an alias expands to
+fn expansion(name: &str, value: &Value) -> Result<Vec<String>> {
+    let expansion: Vec<String> = match value {
+        Value::String(string) => string.split_whitespace().map(String::from).collect(),
+        Value::Array(array) => array
+            .iter()
+            .map(|item| item.as_str().map(String::from))
+            .collect::<Option<_>>()
+            .context(format!("alias `{name}` must only contain strings"))?,
+        _ => anyhow::bail!("alias `{name}` must be a string or an array of strings"),
+    };
+
+    if expansion.is_empty() {
+        anyhow::bail!("alias `{name}` must not be empty");
+    }
+
+    Ok(expansion)
+}
+
+/// Returns the location of a value of the configuration for messages, e.g. ` in ~/.myapp.toml`
+fn location(path: Option<&Path>) -> String {
+    path.map(|path| format!(" in {}", path.display()))
+        .unwrap_or_default()
+}
+
+/// Returns true if a name is used by a command of the application, either as a name or an alias
+fn is_builtin(command: &Command, name: &str) -> bool {
+    name == "help" || command.find_subcommand(name).is_some()
+}
+
+/// Find the position of the subcommand in the arguments of a command line
+///
+/// The arguments before the subcommand can be flags of the root command, which might take a value
+/// that is passed as a separate argument, e.g. `--profile prod`.
+fn subcommand_index(command: &Command, args: &[OsString]) -> Option<usize> {
+    positional_index(command, args, 1)
+}
+
+/// Find the position of the next argument after `start` that is not a flag of a command
+///
+/// `None` is returned if there is no such argument, if an argument is not valid UTF-8, or if the
+/// remaining arguments follow `--`.
+pub(crate) fn positional_index(
+    command: &Command,
+    args: &[OsString],
+    start: usize,
+) -> Option<usize> {
+    let mut index = start;
+
+    while index < args.len() {
+        let arg = args[index].to_str()?;
+
+        if arg == "--" {
+            return None;
+        }
+
+        let takes_separate_value = if let Some(long) = arg.strip_prefix("--") {
+            !long.contains('=') && long_takes_value(command, long)
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|shorts| !shorts.is_empty()) {
+            short_takes_separate_value(command, shorts)
+        } else {
+            return Some(index);
+        };
+
+        index += if takes_separate_value { 2 } else { 1 };
+    }
+
+    None
+}
+
+fn long_takes_value(command: &Command, long: &str) -> bool {
+    command.get_arguments().any(|arg| {
+        let matches_name = arg.get_long() == Some(long)
+            || arg
+                .get_all_aliases()
+                .is_some_and(|aliases| aliases.contains(&long));
+
+        matches_name && arg.get_action().takes_values()
+    })
+}
+
+fn short_takes_separate_value(command: &Command, shorts: &str) -> bool {
+    for (position, short) in shorts.char_indices() {
+        let takes_value = command
+            .get_arguments()
+            .any(|arg| arg.get_short() == Some(short) && arg.get_action().takes_values());
+
+        // The value of a short flag is either attached (`-pprod`) or the next argument (`-p prod`)
+        if takes_value {
+            return position + short.len_utf8() == shorts.len();
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::{Arg, ArgAction};
+    use toml::Table;
+
+    use super::*;
+    use crate::config::ConfigFile;
+
+    fn command() -> Command {
+        Command::new("app")
+            .arg(Arg::new("profile").long("profile").short('p'))
+            .arg(
+                Arg::new("verbose")
+                    .long("verbose")
+                    .short('v')
+                    .action(ArgAction::SetTrue),
+            )
+            .subcommand(Command::new("deploy").visible_alias("d"))
+            .subcommand(Command::new("status"))
+    }
+
+    fn file(path: &str, toml: &str) -> ConfigFile {
+        ConfigFile::new(PathBuf::from(path), toml.parse::<Table>().unwrap())
+    }
+
+    fn aliases(toml: &str) -> Aliases {
+        let config = Config::builder()
+            .user_file(file("/home/user/.config/app/config.toml", toml))
+            .build();
+
+        Aliases::from_config(&config)
+    }
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn from_config_accepts_strings_and_arrays() {
+        let aliases = aliases(
+            r#"
+            [alias]
+            dp = "deploy --env prod"
+            st = ["status", "--verbose"]
+            "#,
+        );
+
+        assert_eq!(
+            Some(&["deploy".to_string(), "--env".into(), "prod".into()][..]),
+            aliases.get("dp")
+        );
+        assert_eq!(
+            Some(&["status".to_string(), "--verbose".into()][..]),
+            aliases.get("st")
+        );
+    }
+
+    #[test]
+    fn from_config_without_alias_table() {
+        assert_eq!(Aliases::default(), aliases("[other]\nkey = 1"));
+        assert_eq!(None, aliases("alias = 1").get("alias"));
+        assert_eq!(1, aliases("alias = 1").warnings().len());
+    }
+
+    #[test]
+    fn from_config_skips_invalid_values() {
+        let aliases = aliases("[alias]\ndp = 1\nempty = []\nst = \"status\"");
+
+        assert_eq!(None, aliases.get("dp"));
+        assert_eq!(None, aliases.get("empty"));
+        assert_eq!(Some(&["status".to_string()][..]), aliases.get("st"));
+        assert_eq!(
+            [
+                "alias `dp` must be a string or an array of strings in /home/user/.config/app/config.toml, ignoring the alias",
+                "alias `empty` must not be empty in /home/user/.config/app/config.toml, ignoring the alias",
+            ],
+            aliases.warnings()
+        );
+    }
+
+    #[test]
+    fn from_config_merges_user_and_project_aliases() {
+        let config = Config::builder()
+            .user_file(file(
+                "/home/user/.config/app/config.toml",
+                "[alias]\ndp = \"deploy\"\nst = \"status\"",
+            ))
+            .project_file(file(
+                "/project/.app.toml",
+                "[alias]\ndp = \"deploy --env prod\"",
+            ))
+            .build();
+
+        let aliases = Aliases::from_config(&config);
+
+        assert_eq!(
+            Some(&["deploy".to_string(), "--env".into(), "prod".into()][..]),
+            aliases.get("dp")
+        );
+        assert_eq!(Some(&["status".to_string()][..]), aliases.get("st"));
+    }
+
+    #[test]
+    fn expansion_rejects_invalid_values() {
+        assert_eq!(
+            "alias `dp` must be a string or an array of strings",
+            expansion("dp", &Value::Integer(1)).unwrap_err().to_string()
+        );
+        assert_eq!(
+            "alias `dp` must only contain strings",
+            expansion("dp", &Value::Array(vec![Value::Integer(1)]))
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn expand_replaces_alias() {
+        let mut aliases = aliases("[alias]\ndp = \"deploy --env prod\"");
+
+        let expanded = aliases
+            .expand(&command(), args(&["app", "dp", "--dry-run"]))
+            .unwrap();
+
+        assert_eq!(
+            args(&["app", "deploy", "--env", "prod", "--dry-run"]),
+            expanded
+        );
+    }
+
+    #[test]
+    fn expand_skips_flags_of_root_command() {
+        let mut aliases = aliases("[alias]\ndp = \"deploy\"");
+
+        let expanded = aliases
+            .expand(&command(), args(&["app", "-v", "--profile", "prod", "dp"]))
+            .unwrap();
+
+        assert_eq!(
+            args(&["app", "-v", "--profile", "prod", "deploy"]),
+            expanded
+        );
+    }
+
+    #[test]
+    fn expand_follows_nested_aliases() {
+        let mut aliases = aliases("[alias]\ndp = \"d --env prod\"\nship = \"dp\"");
+
+        let expanded = aliases.expand(&command(), args(&["app", "ship"])).unwrap();
+
+        assert_eq!(args(&["app", "d", "--env", "prod"]), expanded);
+    }
+
+    #[test]
+    fn expand_detects_recursion() {
+        let mut aliases = aliases("[alias]\na = \"b\"\nb = \"a\"");
+
+        let error = aliases.expand(&command(), args(&["app", "a"])).unwrap_err();
+
+        assert_eq!(
+            "alias `a` is defined recursively: a -> b -> a",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn expand_does_not_shadow_builtin_commands() {
+        let mut aliases = aliases("[alias]\nstatus = \"deploy\"\nd = \"deploy --env prod\"");
+
+        let status = aliases
+            .expand(&command(), args(&["app", "status"]))
+            .unwrap();
+        let d = aliases.expand(&command(), args(&["app", "d"])).unwrap();
+
+        assert_eq!(args(&["app", "status"]), status);
+        assert_eq!(args(&["app", "d"]), d);
+        assert_eq!(2, aliases.warnings().len());
+        assert_eq!(
+            "user-defined alias `status` is ignored, because it is shadowed by a built-in command",
+            aliases.warnings()[0]
+        );
+    }
+
+    #[test]
+    fn expand_does_not_warn_about_expansions_to_commands() {
+        let mut aliases = aliases("[alias]\nst = \"status\"\nstatus = \"deploy\"");
+
+        let expanded = aliases.expand(&command(), args(&["app", "st"])).unwrap();
+
+        assert_eq!(args(&["app", "status"]), expanded);
+        assert!(aliases.warnings().is_empty());
+    }
+
+    #[test]
+    fn expand_ignores_arguments_after_separator() {
+        let mut aliases = aliases("[alias]\ndp = \"deploy\"");
+
+        let expanded = aliases
+            .expand(&command(), args(&["app", "--", "dp"]))
+            .unwrap();
+
+        assert_eq!(args(&["app", "--", "dp"]), expanded);
+    }
+}
@@ -0,0 +1,620 @@
+//! Configuration files of Clawless applications
+//!
+//! Clawless applications can be configured by their users with TOML files. The configuration is
+//! loaded in layers, where each layer overrides the values of the layers before it:
+//!
+//! 1. The default values of the application's configuration struct
+//! 2. The user's configuration file, e.g. `~/.config/<app>/config.toml` on Linux
+//! 3. The project's configuration file `.<app>.toml`, which is searched for in the working
+//!    directory and its parents
+//!
+//! Commands read the merged configuration from the context, and can deserialize it into the
+//! configuration struct of the application:
+//!
+//! ```rust,ignore
+//! #[derive(Default, Deserialize, Serialize)]
+//! #[serde(default)]
+//! pub struct Settings {
+//!     api_url: String,
+//!     timeout: u64,
+//! }
+//!
+//! #[command]
+//! pub async fn deploy(_args: DeployArgs, context: Context) -> CommandResult {
+//!     let settings: Settings = context.config().deserialize()?;
+//!     // ...
+//!     Ok(())
+//! }
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use getset::Getters;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use typed_builder::TypedBuilder;
+
+use crate::context::Dirs;
+
+pub mod command;
+pub mod schema;
+
+/// The name of the configuration file inside the configuration directory of an application
+pub(crate) const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Keys in the configuration files that are used by Clawless itself
+///
+/// These keys are removed before the configuration is deserialized into the configuration struct
+/// of the application.
+const RESERVED_KEYS: &[&str] = &["alias", "default_profile", "profile"];
+
+/// The layer of the configuration that a value comes from
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Deserialize, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigScope {
+    /// The default values of the application
+    #[default]
+    Default,
+
+    /// The configuration file in the user's configuration directory
+    User,
+
+    /// The configuration file of the project in the working directory or one of its parents
+    Project,
+}
+
+impl Display for ConfigScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigScope::Default => write!(f, "default"),
+            ConfigScope::User => write!(f, "user"),
+            ConfigScope::Project => write!(f, "project"),
+        }
+    }
+}
+
+/// A configuration file that has been loaded from disk
+#[derive(Clone, Debug, Getters)]
+pub struct ConfigFile {
+    /// The path of the file
+    #[getset(get = "pub")]
+    path: PathBuf,
+
+    /// The parsed contents of the file
+    #[getset(get = "pub")]
+    table: Table,
+
+    /// The source of the file, which is used to report the lines of invalid values
+    #[getset(get = "pub")]
+    content: String,
+}
+
+/// The layered configuration of an application
+///
+/// The configuration combines the default values of the application with the configuration files
+/// of the user and the project. Values in later layers override the values in earlier layers, and
+/// tables are merged recursively.
+#[derive(Clone, Debug, Default, Getters, TypedBuilder)]
+pub struct Config {
+    /// The default values of the configuration
+    #[builder(default)]
+    #[getset(get = "pub")]
+    defaults: Table,
+
+    /// The configuration file in the user's configuration directory
+    #[builder(default, setter(strip_option))]
+    user_file: Option<ConfigFile>,
+
+    /// The configuration file of the project
+    #[builder(default, setter(strip_option))]
+    project_file: Option<ConfigFile>,
+}
+
+/// A value of the configuration and the layer that it comes from
+#[derive(Clone, PartialEq, Debug, Getters, Serialize)]
+pub struct ConfigEntry {
+    /// The key of the value, with the names of nested tables separated by dots
+    #[getset(get = "pub")]
+    key: String,
+
+    /// The value
+    #[getset(get = "pub")]
+    value: Value,
+
+    /// The layer that the value comes from
+    #[getset(get = "pub")]
+    scope: ConfigScope,
+
+    /// The file that the value comes from, unless it is a default value
+    #[getset(get = "pub")]
+    path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Load the configuration files of an application
+    ///
+    /// The user's configuration file is read from the configuration directory of the application,
+    /// and the project's configuration file is searched for in the directory and its parents.
+    pub fn load(app_name: &str, dirs: &Dirs, directory: &Path) -> Result<Self> {
+        let user_file = match dirs.config_file() {
+            Some(path) => ConfigFile::load(&path)?,
+            None => None,
+        };
+        let project_file = match find_project_config_file(app_name, directory) {
+            Some(path) => ConfigFile::load(&path)?,
+            None => None,
+        };
+
+        Ok(Self {
+            defaults: Table::new(),
+            user_file,
+            project_file,
+        })
+    }
+
+    /// Return a copy of the configuration with the default values of a configuration struct
+    pub fn with_defaults<T>(mut self, defaults: &T) -> Result<Self>
+    where
+        T: Serialize,
+    {
+        self.defaults =
+            Table::try_from(defaults).context("failed to serialize the default configuration")?;
+        Ok(self)
+    }
+
+    /// Returns the configuration file in the user's configuration directory, if it exists
+    pub fn user_file(&self) -> Option<&ConfigFile> {
+        self.user_file.as_ref()
+    }
+
+    /// Returns the configuration file of the project, if it exists
+    pub fn project_file(&self) -> Option<&ConfigFile> {
+        self.project_file.as_ref()
+    }
+
+    /// Returns the configuration file of a layer, if it exists
+    pub fn file(&self, scope: ConfigScope) -> Option<&ConfigFile> {
+        match scope {
+            ConfigScope::Default => None,
+            ConfigScope::User => self.user_file(),
+            ConfigScope::Project => self.project_file(),
+        }
+    }
+
+    /// Replace the configuration file of a layer
+    pub(crate) fn set_file(&mut self, scope: ConfigScope, file: ConfigFile) {
+        match scope {
+            ConfigScope::Default => self.defaults = file.table,
+            ConfigScope::User => self.user_file = Some(file),
+            ConfigScope::Project => self.project_file = Some(file),
+        }
+    }
+
+    /// Returns the value for a key, e.g. `server.port`, and the layer that it comes from
+    pub fn get(&self, key: &str) -> Option<ConfigEntry> {
+        let value = lookup(&self.merged(), key)?.clone();
+        let (scope, path) = self
+            .layers()
+            .filter(|(_, _, table)| lookup(table, key).is_some())
+            .map(|(scope, path, _)| (scope, path.map(Path::to_path_buf)))
+            .last()?;
+
+        Some(ConfigEntry {
+            key: key.to_string(),
+            value,
+            scope,
+            path,
+        })
+    }
+
+    /// Returns all values of the configuration, sorted by their keys
+    ///
+    /// Tables are flattened into their values, while arrays are returned as a single value.
+    pub fn entries(&self) -> Vec<ConfigEntry> {
+        let mut keys = Vec::new();
+        flatten_keys("", &self.merged(), &mut keys);
+
+        keys.iter().filter_map(|key| self.get(key)).collect()
+    }
+
+    /// Returns the merged configuration of all layers
+    pub fn merged(&self) -> Table {
+        let mut merged = Table::new();
+
+        for (_, _, table) in self.layers() {
+            merge(&mut merged, table);
+        }
+
+        merged
+    }
+
+    /// Deserialize the merged configuration into the configuration struct of the application
+    ///
+    /// The keys that Clawless uses itself, e.g. `alias` and `profile`, are ignored.
+    pub fn deserialize<T>(&self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let mut merged = self.merged();
+        for key in RESERVED_KEYS {
+            merged.remove(*key);
+        }
+
+        Value::Table(merged)
+            .try_into()
+            .context("invalid configuration")
+    }
+
+    /// Validate the configuration files against the configuration struct of the application
+    ///
+    /// The invalid values of all files are reported at once, with the file and the line in which
+    /// they are defined. See `clawless::config::schema` for details.
+    pub fn validate<T>(&self) -> Result<()>
+    where
+        T: DeserializeOwned,
+    {
+        let files = [self.user_file(), self.project_file()];
+        schema::validate::<T, _>(&self.defaults, files.into_iter().flatten())
+    }
+
+    fn layers(&self) -> impl Iterator<Item = (ConfigScope, Option<&Path>, &Table)> {
+        let files = [
+            (ConfigScope::User, self.user_file()),
+            (ConfigScope::Project, self.project_file()),
+        ];
+
+        std::iter::once((ConfigScope::Default, None, &self.defaults)).chain(
+            files.into_iter().filter_map(|(scope, file)| {
+                file.map(|file| (scope, Some(file.path().as_path()), file.table()))
+            }),
+        )
+    }
+}
+
+impl ConfigFile {
+    /// Returns the fields by which files are compared
+    ///
+    /// The table is derived from the content, which unlike the table can be ordered and hashed.
+    fn key(&self) -> (&Path, &str) {
+        (&self.path, &self.content)
+    }
+
+    /// Create a configuration file from its path and parsed contents
+    pub fn new(path: PathBuf, table: Table) -> Self {
+        let content = table.to_string();
+
+        Self {
+            path,
+            table,
+            content,
+        }
+    }
+
+    /// Parse the contents of a configuration file
+    pub fn parse(path: &Path, content: String) -> Result<ConfigFile> {
+        let table = content.parse::<Table>().context(format!(
+            "failed to parse configuration file at {}",
+            path.display()
+        ))?;
+
+        Ok(ConfigFile {
+            path: path.to_path_buf(),
+            table,
+            content,
+        })
+    }
+
+    /// Load and parse a configuration file
+    ///
+    /// Returns `None` if the file does not exist, and an error if it cannot be read or is not
+    /// valid TOML.
+    pub fn load(path: &Path) -> Result<Option<ConfigFile>> {
+        let content = match read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).context(format!(
+                    "failed to read configuration file at {}",
+                    path.display()
+                ));
+            }
+        };
+
+        Self::parse(path, content).map(Some)
+    }
+}
+
+// TOML values can be floats, which are neither `Eq` nor `Ord`. Configurations are compared by their
+// TOML representation instead, so that the context that contains them can be compared and hashed.
+
+impl Config {
+    fn key(&self) -> (String, Option<&ConfigFile>, Option<&ConfigFile>) {
+        (
+            self.defaults.to_string(),
+            self.user_file.as_ref(),
+            self.project_file.as_ref(),
+        )
+    }
+}
+
+impl PartialEq for Config {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Config {}
+
+impl PartialOrd for Config {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Config {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for Config {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialEq for ConfigFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ConfigFile {}
+
+impl PartialOrd for ConfigFile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ConfigFile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for ConfigFile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// Returns the name of the configuration file of a project, e.g. `.myapp.toml`
+pub fn project_config_file_name(app_name: &str) -> String {
+    format!(".{app_name}.toml")
+}
+
+/// Find the configuration file of a project in a directory or one of its parents
+fn find_project_config_file(app_name: &str, directory: &Path) -> Option<PathBuf> {
+    if app_name.is_empty() {
+        return None;
+    }
+
+    let file_name = project_config_file_name(app_name);
+
+    directory
+        .ancestors()
+        .map(|directory| directory.join(&file_name))
+        .find(|path| path.is_file())
+}
+
+/// Returns the value at a dotted key in a table
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+
+    Some(value)
+}
+
+/// Merge a table into another table, recursively merging nested tables
+fn merge(base: &mut Table, overrides: &Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(overrides)) => merge(base, overrides),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Collect the dotted keys of all values in a table that are not tables themselves
+fn flatten_keys(prefix: &str, table: &Table, keys: &mut Vec<String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match value {
+            Value::Table(table) => flatten_keys(&key, table, keys),
+            _ => keys.push(key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn file(path: &str, content: &str) -> ConfigFile {
+        ConfigFile::new(PathBuf::from(path), content.parse().unwrap())
+    }
+
+    fn config() -> Config {
+        Config::builder()
+            .defaults(
+                "timeout = 30\n[server]\nhost = \"localhost\"\nport = 80\n"
+                    .parse()
+                    .unwrap(),
+            )
+            .user_file(file(
+                "user.toml",
+                "[server]\nport = 8080\n[alias]\ndp = \"deploy\"\n",
+            ))
+            .project_file(file("project.toml", "timeout = 60\n"))
+            .build()
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Settings {
+        timeout: u64,
+        server: Server,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[test]
+    fn get_returns_value_of_highest_layer() {
+        let config = config();
+
+        let port = config.get("server.port").unwrap();
+        let host = config.get("server.host").unwrap();
+        let timeout = config.get("timeout").unwrap();
+
+        assert_eq!(&Value::Integer(8080), port.value());
+        assert_eq!(ConfigScope::User, *port.scope());
+        assert_eq!(ConfigScope::Default, *host.scope());
+        assert_eq!(&None, host.path());
+        assert_eq!(ConfigScope::Project, *timeout.scope());
+        assert_eq!(&Some(PathBuf::from("project.toml")), timeout.path());
+        assert!(config.get("server.missing").is_none());
+    }
+
+    #[test]
+    fn entries_flattens_tables() {
+        let keys: Vec<String> = config()
+            .entries()
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        assert_eq!(
+            vec!["alias.dp", "server.host", "server.port", "timeout"],
+            keys
+        );
+    }
+
+    #[test]
+    fn deserialize_ignores_reserved_keys() {
+        let settings: Settings = config().deserialize().unwrap();
+
+        assert_eq!(
+            Settings {
+                timeout: 60,
+                server: Server {
+                    host: "localhost".into(),
+                    port: 8080,
+                },
+            },
+            settings
+        );
+    }
+
+    #[test]
+    fn with_defaults_serializes_struct() {
+        #[derive(Serialize)]
+        struct Defaults {
+            timeout: u64,
+        }
+
+        let config = Config::default()
+            .with_defaults(&Defaults { timeout: 30 })
+            .unwrap();
+
+        assert_eq!(Some(&Value::Integer(30)), config.defaults().get("timeout"));
+    }
+
+    #[test]
+    fn eq_compares_toml_representation() {
+        let with_nan = || {
+            Config::builder()
+                .user_file(file("user.toml", "ratio = nan\n"))
+                .build()
+        };
+
+        assert_eq!(config(), config());
+        assert_eq!(with_nan(), with_nan());
+        assert_ne!(config(), with_nan());
+    }
+
+    #[test]
+    fn find_project_config_file_walks_up() {
+        let directory = TempDir::new().unwrap();
+        let nested = directory.path().join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        write(directory.path().join(".my-app.toml"), "").unwrap();
+
+        assert_eq!(
+            Some(directory.path().join(".my-app.toml")),
+            find_project_config_file("my-app", &nested)
+        );
+        assert_eq!(None, find_project_config_file("other-app", &nested));
+    }
+
+    #[test]
+    fn load_parses_file() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("config.toml");
+
+        write(&path, "[alias]\ndp = \"deploy\"\n").unwrap();
+
+        let config_file = ConfigFile::load(&path).unwrap().unwrap();
+
+        assert_eq!(&path, config_file.path());
+        assert!(config_file.table().contains_key("alias"));
+    }
+
+    #[test]
+    fn load_returns_none_for_missing_file() {
+        let directory = TempDir::new().unwrap();
+
+        let config_file = ConfigFile::load(&directory.path().join("config.toml")).unwrap();
+
+        assert!(config_file.is_none());
+    }
+
+    #[test]
+    fn load_fails_for_invalid_toml() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("config.toml");
+
+        write(&path, "this is not toml").unwrap();
+
+        assert!(ConfigFile::load(&path).is_err());
+    }
+}
@@ -0,0 +1,553 @@
+//! The built-in `config` command of Clawless applications
+//!
+//! Applications that declare a configuration struct with `commands!(config = path::to::Config)`
+//! get a `config` command, which lets users inspect and edit their configuration similar to
+//! `git config`:
+//!
+//! ```text
+//! mycli config get server.port
+//! mycli config set server.port 8080
+//! mycli config set --project server.port 3000
+//! mycli config list --show-origin
+//! mycli config edit
+//! mycli config path
+//! ```
+//!
+//! Values are validated against the configuration struct before they are written to disk, so that
+//! users cannot save a configuration that the application fails to load.
+//!
+//! The hidden `config schema` command prints the JSON Schema of the configuration, which editors
+//! can use to provide autocompletion for the configuration file.
+
+use std::fmt::{Display, Formatter};
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use clap::{ArgMatches, Args, Command, FromArgMatches, Subcommand};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer};
+use serde_json::Value as JsonValue;
+use toml::Value;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+use super::{Config, ConfigEntry, ConfigFile, ConfigScope, project_config_file_name, schema};
+use crate::CommandResult;
+use crate::context::Context;
+
+/// The name of the built-in command
+#[doc(hidden)]
+pub const COMMAND_NAME: &str = "config";
+
+/// Inspect and edit the configuration
+///
+/// The configuration is merged from the default values of the application, the user's
+/// configuration file, and the configuration file of the project. Values in the project's file
+/// take precedence over values in the user's file.
+#[derive(Clone, Debug, Subcommand)]
+enum ConfigCommand {
+    /// Print the value of a setting
+    Get {
+        /// The key of the setting, e.g. `server.port`
+        key: String,
+    },
+
+    /// Set the value of a setting in a configuration file
+    Set {
+        /// The key of the setting, e.g. `server.port`
+        key: String,
+
+        /// The value of the setting, which is parsed as TOML or used as a string otherwise
+        value: String,
+
+        #[command(flatten)]
+        scope: ScopeArgs,
+    },
+
+    /// List all settings and their values
+    List {
+        /// Show the layer and the file that each value comes from
+        #[arg(long)]
+        show_origin: bool,
+    },
+
+    /// Open a configuration file in the editor
+    Edit {
+        #[command(flatten)]
+        scope: ScopeArgs,
+    },
+
+    /// Print the path of a configuration file
+    Path {
+        #[command(flatten)]
+        scope: ScopeArgs,
+    },
+
+    /// Print the JSON Schema of the configuration
+    #[command(hide = true)]
+    Schema,
+}
+
+#[derive(Clone, Debug, Args)]
+struct ScopeArgs {
+    /// Use the configuration file of the project instead of the user's
+    #[arg(long)]
+    project: bool,
+}
+
+impl ScopeArgs {
+    fn scope(&self) -> ConfigScope {
+        if self.project {
+            ConfigScope::Project
+        } else {
+            ConfigScope::User
+        }
+    }
+}
+
+/// The value of a setting, which prints strings without quotes
+struct SettingValue<'a>(&'a Value);
+
+impl Display for SettingValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Value::String(string) => write!(f, "{string}"),
+            value => write!(f, "{value}"),
+        }
+    }
+}
+
+impl Serialize for SettingValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The JSON Schema that is printed by `config schema`, which is pretty-printed for humans
+struct Schema(JsonValue);
+
+impl Display for Schema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl Serialize for Schema {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The settings that are printed by `config list`
+struct Settings {
+    entries: Vec<ConfigEntry>,
+    show_origin: bool,
+}
+
+impl Display for Settings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let setting = format!("{} = {}", entry.key(), entry.value());
+
+                if self.show_origin {
+                    format!("{}\t{setting}", origin(entry))
+                } else {
+                    setting
+                }
+            })
+            .collect();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Serialize for Settings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.entries.serialize(serializer)
+    }
+}
+
+/// Returns the `config` command and its subcommands
+#[doc(hidden)]
+pub fn command() -> Command {
+    ConfigCommand::augment_subcommands(Command::new(COMMAND_NAME))
+        .about("Inspect and edit the configuration")
+        .long_about(
+            "Inspect and edit the configuration\n\nThe configuration is merged from the default \
+             values of the application, the user's configuration file, and the configuration \
+             file of the project. Values in the project's file take precedence over values in \
+             the user's file.",
+        )
+        .arg_required_else_help(true)
+}
+
+/// Run the `config` command for the configuration struct of an application
+#[doc(hidden)]
+pub async fn run<T>(matches: &ArgMatches, context: &Context, app_name: &str) -> CommandResult
+where
+    T: Serialize + DeserializeOwned + Default + JsonSchema,
+{
+    let command = ConfigCommand::from_arg_matches(matches)?;
+    let config = context.config().clone().with_defaults(&T::default())?;
+
+    match command {
+        ConfigCommand::Get { key } => {
+            let entry = config
+                .get(&key)
+                .ok_or_else(|| anyhow!("`{key}` is not set"))?;
+
+            context.output().print(&SettingValue(entry.value()))?;
+        }
+        ConfigCommand::Set { key, value, scope } => {
+            let path = file_path(&config, context, app_name, scope.scope())?;
+            set::<T>(config, &path, scope.scope(), &key, &value)?;
+
+            context
+                .output()
+                .success(format!("Set `{key}` in {}", path.display()));
+        }
+        ConfigCommand::List { show_origin } => {
+            context.output().print(&Settings {
+                entries: config.entries(),
+                show_origin,
+            })?;
+        }
+        ConfigCommand::Edit { scope } => {
+            let path = file_path(&config, context, app_name, scope.scope())?;
+            edit::<T>(config, &path, scope.scope(), context).await?;
+        }
+        ConfigCommand::Path { scope } => {
+            let path = file_path(&config, context, app_name, scope.scope())?;
+            context.output().print(&path.display().to_string())?;
+        }
+        ConfigCommand::Schema => {
+            context.output().print(&Schema(schema::schema::<T>()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the path of the configuration file of a layer, even if it does not exist yet
+///
+/// A new project configuration file is created in the working directory.
+fn file_path(
+    config: &Config,
+    context: &Context,
+    app_name: &str,
+    scope: ConfigScope,
+) -> Result<PathBuf> {
+    if let Some(file) = config.file(scope) {
+        return Ok(file.path().clone());
+    }
+
+    match scope {
+        ConfigScope::Project => Ok(context
+            .current_working_directory()
+            .get()
+            .join(project_config_file_name(app_name))),
+        _ => context
+            .dirs()
+            .config_file()
+            .ok_or_else(|| anyhow!("the configuration directory is not available")),
+    }
+}
+
+/// Set a value in a configuration file, after validating it against the configuration struct
+fn set<T>(mut config: Config, path: &Path, scope: ConfigScope, key: &str, value: &str) -> Result<()>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    let mut document = read_document(path)?;
+    set_value(&mut document, key, parse_value(value))?;
+
+    let content = document.to_string();
+    let file = ConfigFile::parse(path, content.clone())?;
+    schema::validate::<T, _>(config.defaults(), [&file])
+        .context(format!("invalid value for `{key}`"))?;
+
+    config.set_file(scope, file);
+    config
+        .deserialize::<T>()
+        .context(format!("invalid value for `{key}`"))?;
+
+    write_file(path, &content)
+}
+
+/// Open a configuration file in the editor, and validate it afterwards
+async fn edit<T>(
+    mut config: Config,
+    path: &Path,
+    scope: ConfigScope,
+    context: &Context,
+) -> Result<()>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    if !path.exists() {
+        write_file(path, "")?;
+    }
+
+    let editor = editor(context);
+    let mut words = editor.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| anyhow!("the editor must not be empty"))?;
+
+    let status = tokio::process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .current_dir(context.current_working_directory().get())
+        .status()
+        .await
+        .context(format!("failed to run the editor `{editor}`"))?;
+
+    if !status.success() {
+        bail!("the editor `{editor}` exited with {status}");
+    }
+
+    let file = ConfigFile::load(path)?
+        .ok_or_else(|| anyhow!("the configuration file at {} was removed", path.display()))?;
+    schema::validate::<T, _>(config.defaults(), [&file])?;
+
+    config.set_file(scope, file);
+    config
+        .deserialize::<T>()
+        .context(format!("invalid configuration in {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Returns the editor of the user, which is read from `VISUAL` and `EDITOR`
+fn editor(context: &Context) -> String {
+    let environment = context.environment();
+
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|key| environment.get(key))
+        .find(|editor| !editor.trim().is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".into()
+            } else {
+                "vi".into()
+            }
+        })
+}
+
+/// Returns the origin of a value, e.g. `user:/home/otter/.config/myapp/config.toml`
+fn origin(entry: &ConfigEntry) -> String {
+    match entry.path() {
+        Some(path) => format!("{}:{}", entry.scope(), path.display()),
+        None => entry.scope().to_string(),
+    }
+}
+
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    let content = if path.exists() {
+        read_to_string(path).context(format!(
+            "failed to read configuration file at {}",
+            path.display()
+        ))?
+    } else {
+        String::new()
+    };
+
+    content.parse().context(format!(
+        "failed to parse configuration file at {}",
+        path.display()
+    ))
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).context(format!(
+            "failed to create directory at {}",
+            parent.display()
+        ))?;
+    }
+
+    write(path, content).context(format!(
+        "failed to write configuration file at {}",
+        path.display()
+    ))
+}
+
+/// Parse a value from the command line as TOML, and fall back to a string
+fn parse_value(value: &str) -> toml_edit::Value {
+    match value.parse::<toml_edit::Value>() {
+        Ok(mut value) => {
+            value.decor_mut().clear();
+            value
+        }
+        Err(_) => value.into(),
+    }
+}
+
+/// Set the value at a dotted key in a document, creating tables as needed
+fn set_value(document: &mut DocumentMut, key: &str, value: toml_edit::Value) -> Result<()> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        bail!("invalid key `{key}`");
+    }
+
+    // `split` always returns at least one part
+    let name = parts.pop().unwrap();
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+
+    for part in parts {
+        let item = table.entry(part).or_insert_with(|| {
+            let mut table = toml_edit::Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        });
+
+        table = item
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("`{part}` in `{key}` is not a table"))?;
+    }
+
+    table.insert(name, Item::Value(value));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+    #[serde(default)]
+    struct AppConfig {
+        timeout: u64,
+        server: Server,
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+    #[serde(default)]
+    struct Server {
+        port: u16,
+    }
+
+    #[test]
+    fn set_value_preserves_document() {
+        let mut document: DocumentMut = "# Settings\ntimeout = 30 # seconds\n".parse().unwrap();
+
+        set_value(&mut document, "server.port", parse_value("8080")).unwrap();
+
+        assert_eq!(
+            "# Settings\ntimeout = 30 # seconds\n\n[server]\nport = 8080\n",
+            document.to_string()
+        );
+    }
+
+    #[test]
+    fn set_value_fails_for_value_that_is_not_a_table() {
+        let mut document: DocumentMut = "timeout = 30\n".parse().unwrap();
+
+        let error = set_value(&mut document, "timeout.seconds", parse_value("1")).unwrap_err();
+
+        assert_eq!(
+            "`timeout` in `timeout.seconds` is not a table",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_value_falls_back_to_string() {
+        assert_eq!("42", parse_value("42").to_string());
+        assert_eq!("true", parse_value("true").to_string());
+        assert_eq!("\"hello world\"", parse_value("hello world").to_string());
+    }
+
+    #[test]
+    fn set_writes_valid_value() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("config.toml");
+
+        set::<AppConfig>(
+            Config::default(),
+            &path,
+            ConfigScope::User,
+            "server.port",
+            "8080",
+        )
+        .unwrap();
+
+        assert_eq!("[server]\nport = 8080\n", read_to_string(&path).unwrap());
+    }
+
+    #[test]
+    fn set_rejects_invalid_value() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("config.toml");
+
+        let error = set::<AppConfig>(
+            Config::default(),
+            &path,
+            ConfigScope::User,
+            "timeout",
+            "soon",
+        )
+        .unwrap_err();
+
+        assert_eq!("invalid value for `timeout`", error.to_string());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn set_reports_line_of_invalid_value() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("config.toml");
+        write(&path, "# Settings\n\n[server]\nport = 8080\n").unwrap();
+
+        let error = set::<AppConfig>(
+            Config::default(),
+            &path,
+            ConfigScope::User,
+            "server.port",
+            "70000",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            format!(
+                "{}:4: `server.port`: invalid value: integer `70000`, expected u16",
+                path.display()
+            ),
+            error
+                .root_cause()
+                .to_string()
+                .lines()
+                .last()
+                .unwrap()
+                .trim()
+        );
+    }
+
+    #[test]
+    fn settings_show_origin() {
+        let config = Config::default()
+            .with_defaults(&AppConfig::default())
+            .unwrap();
+
+        let settings = Settings {
+            entries: config.entries(),
+            show_origin: true,
+        };
+
+        assert_eq!(
+            "default\tserver.port = 0\ndefault\ttimeout = 0",
+            settings.to_string()
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! JSON Schema and validation of the configuration of Clawless applications
+//!
+//! The schema is generated from the configuration struct of an application, which must implement
+//! [`JsonSchema`]. Editors like VS Code (with Even Better TOML) or Helix can use it to provide
+//! autocompletion and inline errors for the configuration file.
+//!
+//! Clawless validates the configuration files before a command is run by deserializing each file,
+//! on top of the default values, into the configuration struct. The first invalid value of every
+//! file is reported with its line:
+//!
+//! ```text
+//! Error: invalid configuration:
+//!   /home/user/.config/my-app/config.toml:4: `server.port`: invalid type: string "8080", expected u16
+//!   .my-app.toml:2: unknown field `sever`, expected `server`
+//! ```
+
+use std::ops::Range;
+
+use anyhow::{Result, bail};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value as JsonValue, json};
+use serde_path_to_error::Segment;
+use toml::de::{DeTable, DeValue};
+use toml::{Spanned, Table, Value};
+
+use super::{ConfigFile, RESERVED_KEYS, merge};
+
+/// Generate the JSON Schema for the configuration files of an application
+///
+/// The schema of the configuration struct is extended with the keys that Clawless reserves for
+/// itself, i.e. `alias`, `default_profile`, and `profile`.
+pub fn schema<T>() -> JsonValue
+where
+    T: JsonSchema,
+{
+    let mut schema = schemars::schema_for!(T).to_value();
+
+    if let Some(schema) = schema.as_object_mut() {
+        let properties = schema
+            .entry("properties")
+            .or_insert_with(|| JsonValue::Object(Map::new()));
+
+        if let Some(properties) = properties.as_object_mut() {
+            properties.insert(
+                "alias".into(),
+                json!({
+                    "description": "Aliases for commands",
+                    "type": "object",
+                    "additionalProperties": {
+                        "anyOf": [
+                            { "type": "string" },
+                            { "type": "array", "items": { "type": "string" } }
+                        ]
+                    }
+                }),
+            );
+            properties.insert(
+                "default_profile".into(),
+                json!({
+                    "description": "The profile that is used when no profile is selected",
+                    "type": "string"
+                }),
+            );
+            properties.insert(
+                "profile".into(),
+                json!({
+                    "description": "Profiles with settings for different environments",
+                    "type": "object",
+                    "additionalProperties": { "type": "object" }
+                }),
+            );
+        }
+    }
+
+    schema
+}
+
+/// Validate configuration files by deserializing them into a configuration struct
+///
+/// Every file is merged into the default values, so that it only needs to contain the values that
+/// it overrides. The errors of all files are collected into a single error, which lists the file
+/// and line of the first invalid value in each file.
+pub fn validate<'a, T, I>(defaults: &Table, files: I) -> Result<()>
+where
+    T: DeserializeOwned,
+    I: IntoIterator<Item = &'a ConfigFile>,
+{
+    let mut errors = Vec::new();
+
+    for file in files {
+        let mut table = defaults.clone();
+        merge(&mut table, file.table());
+        for key in RESERVED_KEYS {
+            table.remove(*key);
+        }
+
+        let Err(error) = serde_path_to_error::deserialize::<_, T>(Value::Table(table)) else {
+            continue;
+        };
+
+        let path: Vec<Segment> = error.path().iter().cloned().collect();
+        let line = DeTable::parse(file.content())
+            .ok()
+            .and_then(|document| span_in_table(document.get_ref(), &path))
+            .map(|span| file.content()[..span.start].matches('\n').count() + 1);
+        let location = match line {
+            Some(line) => format!("{}:{line}", file.path().display()),
+            None => file.path().display().to_string(),
+        };
+
+        // The message of an unknown field already names the field
+        let message = error.inner().message();
+        if path.is_empty() || message.starts_with("unknown field") {
+            errors.push(format!("{location}: {message}"));
+        } else {
+            errors.push(format!("{location}: `{}`: {message}", error.path()));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        bail!("invalid configuration:\n  {}", errors.join("\n  "))
+    }
+}
+
+/// Find the span of the key or array item at the end of a path
+fn span_in_table(table: &DeTable<'_>, path: &[Segment]) -> Option<Range<usize>> {
+    let (Segment::Map { key }, rest) = path.split_first()? else {
+        return None;
+    };
+
+    let (name, value) = table
+        .iter()
+        .find(|(name, _)| name.get_ref().as_ref() == key.as_str())?;
+
+    if rest.is_empty() {
+        Some(name.span())
+    } else {
+        span_in_value(value, rest)
+    }
+}
+
+fn span_in_value(value: &Spanned<DeValue<'_>>, path: &[Segment]) -> Option<Range<usize>> {
+    match (value.get_ref(), path.first()?) {
+        (DeValue::Table(table), Segment::Map { .. }) => span_in_table(table, path),
+        (DeValue::Array(items), Segment::Seq { index }) => {
+            let item = items.get(*index)?;
+
+            if path.len() == 1 {
+                Some(item.span())
+            } else {
+                span_in_value(item, &path[1..])
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use indoc::indoc;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Default, Deserialize, Serialize, JsonSchema)]
+    #[serde(default, deny_unknown_fields)]
+    struct Settings {
+        name: String,
+        level: Level,
+        timeout: Option<u32>,
+        server: Server,
+        mirrors: Vec<Server>,
+    }
+
+    #[derive(Default, Deserialize, Serialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Default, Deserialize, Serialize, JsonSchema)]
+    #[serde(rename_all = "lowercase")]
+    enum Level {
+        #[default]
+        Info,
+        Debug,
+    }
+
+    fn file(path: &str, content: &str) -> ConfigFile {
+        ConfigFile::parse(Path::new(path), content.into()).unwrap()
+    }
+
+    fn validate_files(files: &[ConfigFile]) -> Result<()> {
+        let defaults = Table::try_from(Settings::default()).unwrap();
+
+        validate::<Settings, _>(&defaults, files)
+    }
+
+    #[test]
+    fn schema_contains_reserved_keys() {
+        let schema = schema::<Settings>();
+
+        for key in ["alias", "default_profile", "profile", "server"] {
+            assert!(schema["properties"].get(key).is_some(), "missing {key}");
+        }
+    }
+
+    #[test]
+    fn validate_accepts_valid_file() {
+        let content = indoc! {r#"
+            name = "my-app"
+            level = "debug"
+            timeout = 30
+            default_profile = "staging"
+
+            [server]
+            port = 8080
+
+            [[mirrors]]
+            host = "example.com"
+            port = 443
+
+            [alias]
+            ship = ["deploy", "--env", "prod"]
+
+            [profile.staging]
+            url = "https://staging.example.com"
+        "#};
+
+        assert!(validate_files(&[file("config.toml", content)]).is_ok());
+    }
+
+    #[test]
+    fn validate_reports_lines_of_all_files() {
+        let user = file(
+            "config.toml",
+            "name = \"my-app\"\n\n[server]\nport = \"8080\"\n",
+        );
+        let project = file(
+            ".my-app.toml",
+            "level = \"debug\"\n\n[sever]\nport = 8080\n",
+        );
+
+        let error = validate_files(&[user, project]).unwrap_err();
+
+        assert_eq!(
+            indoc! {r#"
+                invalid configuration:
+                  config.toml:4: `server.port`: invalid type: string "8080", expected u16
+                  .my-app.toml:3: unknown field `sever`, expected one of `name`, `level`, `timeout`, `server`, `mirrors`"#},
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn validate_reports_nested_values() {
+        let content = indoc! {r#"
+            [[mirrors]]
+            host = "example.com"
+            port = 443
+
+            [[mirrors]]
+            host = "example.org"
+            port = 70000
+        "#};
+
+        let error = validate_files(&[file("config.toml", content)]).unwrap_err();
+
+        assert_eq!(
+            "invalid configuration:\n  config.toml:7: `mirrors[1].port`: invalid value: integer `70000`, expected u16",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn validate_reports_unknown_nested_fields() {
+        let content = "[server]\nhots = \"example.com\"\nport = 443\n";
+
+        let error = validate_files(&[file("config.toml", content)]).unwrap_err();
+
+        assert_eq!(
+            "invalid configuration:\n  config.toml:2: unknown field `hots`, expected `host` or `port`",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn validate_ignores_reserved_keys() {
+        let content = "default_profile = 1\n\n[alias]\nship = 1\n";
+
+        assert!(validate_files(&[file("config.toml", content)]).is_ok());
+    }
+
+    #[test]
+    fn validate_without_source_omits_line() {
+        let file = ConfigFile::new(
+            PathBuf::from("config.toml"),
+            toml::from_str("name = 1").unwrap(),
+        );
+
+        let error = validate_files(&[file]).unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("config.toml:1: `name`: invalid type: integer `1`, expected a string"),
+            "{error}"
+        );
+    }
+}
@@ -7,13 +7,36 @@
 //! For information on the context that is available to commands, see the fields and methods of the
 //! `Context` struct as well as the types defined in this module.
 
+use std::path::PathBuf;
+
 use anyhow::Result;
+use clap::ArgMatches;
 use getset::Getters;
 use typed_builder::TypedBuilder;
 
+use crate::config::Config;
+use crate::theme::Theme;
+
 pub use self::current_working_directory::CurrentWorkingDirectory;
+pub use self::dirs::Dirs;
+pub use self::environment::Environment;
+use self::environment::find_dotenv_files;
+pub use self::global_args::GlobalArgs;
+pub use self::output::{Output, OutputFormat};
+pub use self::profile::{DEFAULT_PROFILE, Profile};
+pub use self::progress::Progress;
+pub use self::prompt::{ArgPrompt, Prompt};
+pub use self::terminal::{ColorMode, Terminal};
 
 mod current_working_directory;
+mod dirs;
+mod environment;
+mod global_args;
+mod output;
+mod profile;
+mod progress;
+mod prompt;
+mod terminal;
 
 /// Context for Clawless commands
 ///
@@ -39,6 +62,52 @@ pub struct Context {
     #[builder(setter(into))]
     #[getset(get = "pub")]
     current_working_directory: CurrentWorkingDirectory,
+
+    /// A snapshot of the environment variables that were set when the application started,
+    /// including the variables from `.env` files
+    #[builder(default)]
+    #[getset(get = "pub")]
+    environment: Environment,
+
+    /// The configuration of the application, merged from its configuration files
+    #[builder(default)]
+    #[getset(get = "pub")]
+    config: Config,
+
+    /// The profile that the command targets, e.g. `staging` or `prod`
+    #[builder(default)]
+    #[getset(get = "pub")]
+    profile: Profile,
+
+    /// Directories for the configuration, data, and cache files of the application
+    #[builder(default)]
+    #[getset(get = "pub")]
+    dirs: Dirs,
+
+    /// Capabilities of the terminal, e.g. whether colors should be used
+    #[builder(default)]
+    #[getset(get = "pub")]
+    terminal: Terminal,
+
+    /// The colors and styles of the application
+    #[builder(default)]
+    #[getset(get = "pub")]
+    theme: Theme,
+
+    /// Interactive prompts that respect `--no-input` and `--yes`
+    #[builder(default)]
+    #[getset(get = "pub")]
+    prompt: Prompt,
+
+    /// Output of messages and results that respects `--quiet` and `--output`
+    #[builder(default)]
+    #[getset(get = "pub")]
+    output: Output,
+
+    /// Progress bars and spinners for long-running tasks
+    #[builder(default)]
+    #[getset(get = "pub")]
+    progress: Progress,
 }
 
 impl Context {
@@ -46,19 +115,146 @@ impl Context {
     ///
     /// This function initializes a new `Context` with default settings. Since some parts of the
     /// context might fail to initialize, this function returns a `Result`.
+    ///
+    /// The context captures the working directory, the environment variables, and the capabilities
+    /// of the terminal, but has no side effects: it neither reads `.env` or configuration files nor
+    /// changes how colors are used globally. Applications should use `try_from_matches` instead,
+    /// which does all of this for the parsed command line.
     pub fn try_new() -> Result<Self> {
         let current_working_directory = CurrentWorkingDirectory::try_from_env()?;
+        let environment = Environment::from_env("");
+        let terminal = Terminal::detect(ColorMode::default(), &environment);
+        let global_args = GlobalArgs::default();
+
+        Ok(Self::builder()
+            .current_working_directory(current_working_directory)
+            .prompt(Prompt::new(&global_args, &terminal))
+            .output(Output::new(&global_args, &terminal, Theme::default()))
+            .progress(Progress::new(&global_args, &terminal))
+            .environment(environment)
+            .terminal(terminal)
+            .build())
+    }
+
+    /// Create a new `Context` instance for the parsed command line
+    ///
+    /// The global flags that the `commands!` macro adds to every application, e.g. `--no-input`,
+    /// are read from the matches and used to configure the context. The name of the application is
+    /// the prefix of its environment variables, and the theme is used to style the output of
+    /// commands. The configuration is loaded before the command line is parsed, since it also
+    /// contains the user-defined aliases, and is passed in so that its files are only read once.
+    ///
+    /// The active profile is selected with `--profile`, the `<APP>_PROFILE` environment variable,
+    /// or the configuration, and its settings are read from the configuration.
+    ///
+    /// Environment variables are loaded from the files that are passed with `--env-file`, or from
+    /// the nearest `.env` and `.env.<profile>` files in the working directory or its parents unless
+    /// `--no-env-file` is passed. The files that were loaded are reported when `--verbose` is passed.
+    pub fn try_from_matches(
+        matches: &ArgMatches,
+        app_name: &str,
+        theme: Theme,
+        config: Config,
+    ) -> Result<Self> {
+        Self::try_from_global_args(&GlobalArgs::from_matches(matches), app_name, theme, config)
+    }
+
+    fn try_from_global_args(
+        global_args: &GlobalArgs,
+        app_name: &str,
+        theme: Theme,
+        config: Config,
+    ) -> Result<Self> {
+        let current_working_directory = CurrentWorkingDirectory::try_from_env()?;
+        let mut environment = Environment::from_env(app_name);
+        let dirs = Dirs::new(app_name, &environment);
+
+        let profile = Profile::resolve(global_args, &environment, &config)?;
+
+        environment.load_dotenv_files(&dotenv_files(
+            global_args,
+            &current_working_directory,
+            &profile,
+        ))?;
+
+        let terminal = Terminal::detect(global_args.color, &environment);
+        terminal.apply();
+
+        let prompt = Prompt::new(global_args, &terminal);
+        let output = Output::new(global_args, &terminal, theme);
+        let progress = Progress::new(global_args, &terminal);
+
+        if !profile.is_default() {
+            output.verbose(format!("Using profile {}", profile.name()));
+        }
+        for file in environment.dotenv_files() {
+            output.verbose(format!(
+                "Loaded environment variables from {}",
+                file.display()
+            ));
+        }
 
         Ok(Self {
             current_working_directory,
+            environment,
+            config,
+            profile,
+            dirs,
+            terminal,
+            theme,
+            prompt,
+            output,
+            progress,
         })
     }
 }
 
+/// Returns the `.env` files that should be loaded for the global flags
+fn dotenv_files(
+    global_args: &GlobalArgs,
+    current_working_directory: &CurrentWorkingDirectory,
+    profile: &Profile,
+) -> Vec<PathBuf> {
+    if global_args.no_env_file {
+        Vec::new()
+    } else if !global_args.env_file.is_empty() {
+        global_args.env_file.clone()
+    } else {
+        find_dotenv_files(current_working_directory.get(), profile.name())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn dotenv_files_respects_global_args() {
+        let cwd = CurrentWorkingDirectory::new(PathBuf::from("/"));
+        let explicit = GlobalArgs {
+            env_file: vec![PathBuf::from("staging.env")],
+            ..GlobalArgs::default()
+        };
+        let disabled = GlobalArgs {
+            no_env_file: true,
+            ..GlobalArgs::default()
+        };
+
+        assert_eq!(
+            vec![PathBuf::from("staging.env")],
+            dotenv_files(&explicit, &cwd, &Profile::default())
+        );
+        assert!(dotenv_files(&disabled, &cwd, &Profile::default()).is_empty());
+    }
+
+    #[test]
+    fn try_new_does_not_load_files() {
+        let context = Context::try_new().unwrap();
+
+        assert!(context.environment().dotenv_files().is_empty());
+        assert_eq!(&Config::default(), context.config());
+    }
+
     #[test]
     fn trait_send() {
         fn assert_send<T: Send>() {}
@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result, anyhow};
+use etcetera::BaseStrategy;
+use typed_builder::TypedBuilder;
+
+use super::Environment;
+
+/// Directories in which an application stores its files
+///
+/// The directories follow the XDG Base Directory Specification on Linux and macOS, like most
+/// command-line tools, and the Known Folders on Windows. They are named after the application.
+/// For an application named `myapp`, the configuration directory is `$XDG_CONFIG_HOME/myapp`,
+/// which defaults to `~/.config/myapp`, and `%APPDATA%\myapp` on Windows.
+///
+/// Each directory can be overridden with an environment variable of the application, e.g.
+/// `MYAPP_CACHE_DIR`. The directories are created when they are first requested.
+///
+/// ```rust,ignore
+/// #[command]
+/// pub async fn login(args: LoginArgs, context: Context) -> CommandResult {
+///     let credentials = context.dirs().config_dir()?.join("credentials.toml");
+///     // ...
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, TypedBuilder)]
+pub struct Dirs {
+    #[builder(default, setter(strip_option, into))]
+    config: Option<PathBuf>,
+
+    #[builder(default, setter(strip_option, into))]
+    data: Option<PathBuf>,
+
+    #[builder(default, setter(strip_option, into))]
+    cache: Option<PathBuf>,
+
+    #[builder(default, setter(strip_option, into))]
+    state: Option<PathBuf>,
+
+    #[builder(default, setter(strip_option, into))]
+    runtime: Option<PathBuf>,
+}
+
+impl Dirs {
+    /// Determine the directories of an application
+    ///
+    /// Overrides are read from the environment, e.g. `MYAPP_CONFIG_DIR`. Directories that do not
+    /// exist on the platform or that cannot be determined, e.g. because the home directory of the
+    /// user is unknown, are not available.
+    pub fn new(app_name: &str, environment: &Environment) -> Self {
+        if app_name.is_empty() {
+            return Self::default();
+        }
+
+        // The base strategy uses XDG on macOS, instead of `~/Library/Application Support`
+        let strategy = etcetera::choose_base_strategy().ok();
+        let dir = |key: &str, platform: Option<PathBuf>| {
+            environment
+                .app_var(key)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| platform.map(|dir| dir.join(app_name)))
+        };
+
+        Self {
+            config: dir(
+                "CONFIG_DIR",
+                strategy.as_ref().map(|base| base.config_dir()),
+            ),
+            data: dir("DATA_DIR", strategy.as_ref().map(|base| base.data_dir())),
+            cache: dir("CACHE_DIR", strategy.as_ref().map(|base| base.cache_dir())),
+            state: dir(
+                "STATE_DIR",
+                strategy.as_ref().and_then(|base| base.state_dir()),
+            ),
+            runtime: dir(
+                "RUNTIME_DIR",
+                strategy.as_ref().and_then(|base| base.runtime_dir()),
+            ),
+        }
+    }
+
+    /// Returns the directory for configuration files, and creates it if it does not exist
+    pub fn config_dir(&self) -> Result<PathBuf> {
+        create(self.config.as_deref(), "configuration")
+    }
+
+    /// Returns the directory for data files, and creates it if it does not exist
+    pub fn data_dir(&self) -> Result<PathBuf> {
+        create(self.data.as_deref(), "data")
+    }
+
+    /// Returns the directory for cached files, and creates it if it does not exist
+    ///
+    /// Files in the cache can be deleted at any time without losing data.
+    pub fn cache_dir(&self) -> Result<PathBuf> {
+        create(self.cache.as_deref(), "cache")
+    }
+
+    /// Returns the directory for state files, and creates it if it does not exist
+    ///
+    /// State is data that should persist between runs but is not important enough to be backed
+    /// up, e.g. logs or history.
+    pub fn state_dir(&self) -> Result<PathBuf> {
+        create(self.state.as_deref(), "state")
+    }
+
+    /// Returns the directory for runtime files, and creates it if it does not exist
+    ///
+    /// Runtime files like sockets and lock files only exist while the application is running. The
+    /// directory is only available on platforms that support it, e.g. on Linux with
+    /// `XDG_RUNTIME_DIR`.
+    pub fn runtime_dir(&self) -> Result<PathBuf> {
+        create(self.runtime.as_deref(), "runtime")
+    }
+
+    /// Returns the path of the configuration file of the application, without creating it
+    pub fn config_file(&self) -> Option<PathBuf> {
+        self.config
+            .as_ref()
+            .map(|config| config.join(crate::config::CONFIG_FILE_NAME))
+    }
+}
+
+fn create(path: Option<&Path>, kind: &str) -> Result<PathBuf> {
+    let path = path.ok_or_else(|| anyhow!("the {kind} directory is not available"))?;
+
+    std::fs::create_dir_all(path).context(format!(
+        "failed to create the {kind} directory at {}",
+        path.display()
+    ))?;
+
+    Ok(path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn new_prefers_environment_overrides() {
+        let environment = Environment::new("my-app").with("MY_APP_CACHE_DIR", "/tmp/my-app-cache");
+
+        let dirs = Dirs::new("my-app", &environment);
+
+        assert_eq!(Some(PathBuf::from("/tmp/my-app-cache")), dirs.cache);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn new_uses_platform_directories() {
+        let dirs = Dirs::new("my-app", &Environment::new("my-app"));
+
+        assert!(dirs.config.unwrap().ends_with("my-app"));
+        assert!(dirs.data.unwrap().ends_with("my-app"));
+    }
+
+    #[test]
+    fn new_without_app_name() {
+        assert_eq!(Dirs::default(), Dirs::new("", &Environment::default()));
+    }
+
+    #[test]
+    fn cache_dir_is_created_lazily() {
+        let directory = TempDir::new().unwrap();
+        let cache = directory.path().join("cache");
+
+        let dirs = Dirs::builder().cache(&cache).build();
+        assert!(!cache.exists());
+
+        assert_eq!(cache, dirs.cache_dir().unwrap());
+        assert!(cache.is_dir());
+    }
+
+    #[test]
+    fn runtime_dir_fails_when_unavailable() {
+        let error = Dirs::default().runtime_dir().unwrap_err();
+
+        assert_eq!("the runtime directory is not available", error.to_string());
+    }
+
+    #[test]
+    fn config_file_is_inside_config_dir() {
+        let dirs = Dirs::builder().config("/etc/my-app").build();
+
+        assert_eq!(
+            Some(PathBuf::from("/etc/my-app/config.toml")),
+            dirs.config_file()
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Context as _, Result, anyhow};
+
+/// The name of the file with environment variables that is discovered from the working directory
+const DOTENV_FILE_NAME: &str = ".env";
+
+/// A snapshot of the environment variables of the application
+///
+/// The environment variables are captured when the application starts, and commands read them
+/// from the `Context` instead of calling `std::env::var`. This makes commands easy to test, since
+/// tests can inject the variables that a command needs without modifying the environment of the
+/// process that runs the tests.
+///
+/// Variables that are specific to the application use its name as a prefix, e.g. `MYAPP_TOKEN`
+/// for an application named `myapp`. These variables can be looked up without the prefix.
+///
+/// Variables can also be loaded from `.env` files. They never override the variables that were
+/// set in the environment of the process.
+///
+/// ```rust,ignore
+/// let environment = Environment::new("myapp")
+///     .with("MYAPP_TOKEN", "secret")
+///     .with("MYAPP_TIMEOUT", "30");
+///
+/// assert_eq!(Some("secret"), environment.app_var("TOKEN"));
+/// assert_eq!(Some(30), environment.parse_app_var::<u64>("TIMEOUT")?);
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Environment {
+    prefix: String,
+    variables: BTreeMap<String, String>,
+    dotenv_files: Vec<PathBuf>,
+}
+
+impl Environment {
+    /// Create an empty environment for an application
+    pub fn new(app_name: &str) -> Self {
+        Self {
+            prefix: prefix(app_name),
+            ..Self::default()
+        }
+    }
+
+    /// Capture the environment variables of the current process
+    ///
+    /// Variables whose name or value is not valid Unicode are ignored.
+    pub fn from_env(app_name: &str) -> Self {
+        let variables = std::env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+
+        Self {
+            prefix: prefix(app_name),
+            variables,
+            ..Self::default()
+        }
+    }
+
+    /// Return a copy of the environment with a variable set to a value
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.variables.insert(key.into(), value.into());
+        self
+    }
+
+    /// Load variables from `.env` files
+    ///
+    /// Variables in later files take precedence over those in earlier files, but variables that
+    /// are already set are never overridden. This ensures that the environment of the process
+    /// always wins over the files.
+    pub(crate) fn load_dotenv_files(&mut self, files: &[PathBuf]) -> Result<()> {
+        let mut variables = BTreeMap::new();
+
+        for file in files {
+            let error = || {
+                format!(
+                    "failed to load environment variables from {}",
+                    file.display()
+                )
+            };
+
+            for variable in dotenvy::from_path_iter(file).with_context(error)? {
+                let (key, value) = variable.with_context(error)?;
+                variables.insert(key, value);
+            }
+        }
+
+        for (key, value) in variables {
+            self.variables.entry(key).or_insert(value);
+        }
+        self.dotenv_files.extend_from_slice(files);
+
+        Ok(())
+    }
+
+    /// Returns the `.env` files from which variables were loaded
+    pub fn dotenv_files(&self) -> &[PathBuf] {
+        &self.dotenv_files
+    }
+
+    /// Returns the prefix of the variables that are specific to the application, e.g. `MYAPP_`
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the value of a variable
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.variables.get(key).map(String::as_str)
+    }
+
+    /// Returns true if a variable is set
+    pub fn contains(&self, key: &str) -> bool {
+        self.variables.contains_key(key)
+    }
+
+    /// Parse the value of a variable into a type
+    ///
+    /// Returns `None` if the variable is not set, and an error if its value cannot be parsed.
+    pub fn parse<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(key)
+            .map(|value| {
+                value.parse().map_err(|error| {
+                    anyhow!("invalid value \"{value}\" for environment variable {key}: {error}")
+                })
+            })
+            .transpose()
+    }
+
+    /// Returns the value of a variable of the application, e.g. `MYAPP_TOKEN` for `TOKEN`
+    pub fn app_var(&self, key: &str) -> Option<&str> {
+        self.get(&format!("{}{key}", self.prefix))
+    }
+
+    /// Parse the value of a variable of the application into a type
+    pub fn parse_app_var<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.parse(&format!("{}{key}", self.prefix))
+    }
+
+    /// Iterate over the variables of the application, with the prefix removed from their names
+    pub fn app_vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.variables.iter().filter_map(|(key, value)| {
+            let key = key.strip_prefix(&self.prefix)?;
+            (!key.is_empty()).then_some((key, value.as_str()))
+        })
+    }
+
+    /// Iterate over all variables
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.variables
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+/// Find the `.env` files for a directory and a profile
+///
+/// The directory and its parents are searched for a `.env` or `.env.<profile>` file, and the files
+/// in the directory that is closest are returned. The file of the profile comes last, so that its
+/// variables take precedence.
+pub(crate) fn find_dotenv_files(directory: &Path, profile: &str) -> Vec<PathBuf> {
+    let names = [
+        DOTENV_FILE_NAME.to_string(),
+        format!("{DOTENV_FILE_NAME}.{profile}"),
+    ];
+
+    directory
+        .ancestors()
+        .map(|directory| {
+            names
+                .iter()
+                .map(|name| directory.join(name))
+                .filter(|file| file.is_file())
+                .collect::<Vec<_>>()
+        })
+        .find(|files| !files.is_empty())
+        .unwrap_or_default()
+}
+
+/// Returns the prefix of the environment variables of an application
+///
+/// The prefix is the name of the application in uppercase, with dashes replaced by underscores,
+/// e.g. `HELLO_WORLD_` for `hello-world`.
+fn prefix(app_name: &str) -> String {
+    if app_name.is_empty() {
+        return String::new();
+    }
+
+    format!("{}_", app_name.to_uppercase().replace('-', "_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn environment() -> Environment {
+        Environment::new("my-app")
+            .with("HOME", "/home/otter")
+            .with("MY_APP_TOKEN", "secret")
+            .with("MY_APP_TIMEOUT", "30")
+    }
+
+    #[test]
+    fn prefix_from_app_name() {
+        assert_eq!("MY_APP_", environment().prefix());
+        assert_eq!("", Environment::default().prefix());
+    }
+
+    #[test]
+    fn get_returns_value() {
+        assert_eq!(Some("/home/otter"), environment().get("HOME"));
+        assert_eq!(None, environment().get("SHELL"));
+    }
+
+    #[test]
+    fn parse_returns_typed_value() {
+        assert_eq!(
+            Some(30),
+            environment().parse::<u32>("MY_APP_TIMEOUT").unwrap()
+        );
+        assert_eq!(None, environment().parse::<u32>("MY_APP_RETRIES").unwrap());
+    }
+
+    #[test]
+    fn parse_fails_for_invalid_value() {
+        let error = environment().parse::<u32>("MY_APP_TOKEN").unwrap_err();
+
+        assert_eq!(
+            "invalid value \"secret\" for environment variable MY_APP_TOKEN: invalid digit found in string",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn app_var_adds_prefix() {
+        assert_eq!(Some("secret"), environment().app_var("TOKEN"));
+        assert_eq!(
+            Some(30),
+            environment().parse_app_var::<u8>("TIMEOUT").unwrap()
+        );
+    }
+
+    #[test]
+    fn app_vars_filters_by_prefix() {
+        let environment = environment();
+
+        let vars: Vec<(&str, &str)> = environment.app_vars().collect();
+
+        assert_eq!(vec![("TIMEOUT", "30"), ("TOKEN", "secret")], vars);
+    }
+
+    #[test]
+    fn from_env_captures_process_environment() {
+        let environment = Environment::from_env("my-app");
+
+        assert_eq!(
+            std::env::var("PATH").ok().as_deref(),
+            environment.get("PATH")
+        );
+    }
+
+    #[test]
+    fn load_dotenv_files_does_not_override_variables() {
+        let directory = TempDir::new().unwrap();
+        let file = directory.path().join(".env");
+        std::fs::write(&file, "MY_APP_TOKEN=from-file\nMY_APP_REGION=eu-west-1\n").unwrap();
+
+        let mut environment = environment();
+        environment
+            .load_dotenv_files(std::slice::from_ref(&file))
+            .unwrap();
+
+        assert_eq!(Some("secret"), environment.app_var("TOKEN"));
+        assert_eq!(Some("eu-west-1"), environment.app_var("REGION"));
+        assert_eq!(&[file], environment.dotenv_files());
+    }
+
+    #[test]
+    fn load_dotenv_files_prefers_later_files() {
+        let directory = TempDir::new().unwrap();
+        let first = directory.path().join(".env");
+        let second = directory.path().join("local.env");
+        std::fs::write(&first, "MY_APP_REGION=eu-west-1\n").unwrap();
+        std::fs::write(&second, "MY_APP_REGION=us-east-1\n").unwrap();
+
+        let mut environment = environment();
+        environment.load_dotenv_files(&[first, second]).unwrap();
+
+        assert_eq!(Some("us-east-1"), environment.app_var("REGION"));
+    }
+
+    #[test]
+    fn load_dotenv_files_fails_for_missing_file() {
+        let directory = TempDir::new().unwrap();
+        let file = directory.path().join("missing.env");
+
+        let error = environment()
+            .load_dotenv_files(std::slice::from_ref(&file))
+            .unwrap_err();
+
+        assert_eq!(
+            format!(
+                "failed to load environment variables from {}",
+                file.display()
+            ),
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn find_dotenv_files_walks_up() {
+        let directory = TempDir::new().unwrap();
+        let nested = directory.path().join("crates").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(directory.path().join(".env"), "").unwrap();
+
+        assert_eq!(
+            vec![directory.path().join(".env")],
+            find_dotenv_files(&nested, "default")
+        );
+    }
+
+    #[test]
+    fn find_dotenv_files_includes_profile() {
+        let directory = TempDir::new().unwrap();
+        std::fs::write(directory.path().join(".env"), "").unwrap();
+        std::fs::write(directory.path().join(".env.prod"), "").unwrap();
+        std::fs::write(directory.path().join(".env.staging"), "").unwrap();
+
+        assert_eq!(
+            vec![
+                directory.path().join(".env"),
+                directory.path().join(".env.prod")
+            ],
+            find_dotenv_files(directory.path(), "prod")
+        );
+    }
+}
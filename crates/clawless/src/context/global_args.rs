@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use clap::builder::Resettable;
+use clap::{Arg, ArgMatches, Args, Command};
+
+use super::{ColorMode, OutputFormat};
+
+/// Global flags that are available on every command of a Clawless application
+///
+/// The `commands!` macro adds these flags to the root command of the application and marks them as
+/// global, so that users can pass them before or after any subcommand. The Clawless runtime reads
+/// them to configure the `Context` that is passed to commands.
+///
+/// The flags only have long names, so that all short flags are left to the application. Their long
+/// names are reserved: `--no-input`, `--yes`, `--quiet`, `--verbose`, `--output`, `--color`,
+/// `--profile`, `--env-file`, and `--no-env-file`. An application that needs one of these names for
+/// its own argument opts out of the global flag with `commands!(without_global_flags = [...])`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Args)]
+#[command(about = None, long_about = None, next_help_heading = "Global Options")]
+pub struct GlobalArgs {
+    /// Never prompt for input, and fail if a prompt has no default value
+    #[arg(id = "clawless_no_input", long = "no-input", global = true)]
+    pub no_input: bool,
+
+    /// Answer yes to all confirmations, and accept the defaults of all other prompts
+    #[arg(id = "clawless_yes", long = "yes", global = true)]
+    pub yes: bool,
+
+    /// Only print warnings, errors, and results
+    #[arg(id = "clawless_quiet", long = "quiet", global = true)]
+    pub quiet: bool,
+
+    /// Print more details, e.g. which `.env` files were loaded
+    #[arg(
+        id = "clawless_verbose",
+        long = "verbose",
+        global = true,
+        conflicts_with = "clawless_quiet"
+    )]
+    pub verbose: bool,
+
+    /// The format in which results are printed
+    #[arg(
+        id = "clawless_output",
+        long = "output",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t,
+        global = true
+    )]
+    pub output: OutputFormat,
+
+    /// When to use colors in the output
+    #[arg(
+        id = "clawless_color",
+        long = "color",
+        value_name = "WHEN",
+        value_enum,
+        default_value_t,
+        global = true
+    )]
+    pub color: ColorMode,
+
+    /// The profile to use, e.g. `staging` or `prod`
+    #[arg(
+        id = "clawless_profile",
+        long = "profile",
+        value_name = "NAME",
+        global = true
+    )]
+    pub profile: Option<String>,
+
+    /// Load environment variables from a file instead of the nearest `.env` file
+    #[arg(
+        id = "clawless_env_file",
+        long = "env-file",
+        value_name = "PATH",
+        global = true
+    )]
+    pub env_file: Vec<PathBuf>,
+
+    /// Do not load environment variables from `.env` files
+    #[arg(
+        id = "clawless_no_env_file",
+        long = "no-env-file",
+        global = true,
+        conflicts_with = "clawless_env_file"
+    )]
+    pub no_env_file: bool,
+}
+
+impl GlobalArgs {
+    /// Add the global flags to the root command of an application
+    ///
+    /// # Panics
+    ///
+    /// Panics if a command of the application defines an argument with the long name of a global
+    /// flag, like clap does for other duplicate arguments. See [`GlobalArgs::augment_command_without`]
+    /// to leave out global flags.
+    pub fn augment_command(command: Command) -> Command {
+        Self::augment_command_without(command, &[])
+    }
+
+    /// Add the global flags except the given ones to the root command of an application
+    ///
+    /// The flags are given by their long names, e.g. `output` for `--output`. They are added after
+    /// all subcommands have been added to the command, so that arguments of the application that
+    /// use the name of a global flag are found at startup instead of silently replacing the flag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a name is not a global flag, or if a command of the application defines an
+    /// argument with the long name of a global flag that is not left out.
+    pub fn augment_command_without(mut command: Command, without: &[&str]) -> Command {
+        let globals = Self::augment_args(Command::new(""));
+
+        if let Some(unknown) = without.iter().find(|name| {
+            !globals
+                .get_arguments()
+                .any(|arg| arg.get_long() == Some(name))
+        }) {
+            panic!("`{unknown}` is not a global flag of Clawless");
+        }
+
+        let (skipped, added): (Vec<&Arg>, Vec<&Arg>) = globals
+            .get_arguments()
+            .partition(|arg| arg.get_long().is_some_and(|long| without.contains(&long)));
+
+        let mut used = HashMap::new();
+        collect_long_names(&command, &mut Vec::new(), &mut used);
+
+        let collisions: Vec<String> = added
+            .iter()
+            .flat_map(|arg| long_names(arg))
+            .filter_map(|name| {
+                let path = used.get(name)?;
+                Some(format!(
+                    "`{path}` defines `--{name}`, which is a global flag of Clawless"
+                ))
+            })
+            .collect();
+        if !collisions.is_empty() {
+            panic!(
+                "{}; rename the arguments or leave out the global flags with \
+                 `clawless::commands!(without_global_flags = [...])`",
+                collisions.join(", ")
+            );
+        }
+
+        for arg in added {
+            let conflicts = globals.get_arg_conflicts_with(arg);
+
+            // Conflicts with skipped flags would refer to arguments that don't exist
+            let arg = if conflicts.iter().any(|conflict| skipped.contains(conflict)) {
+                conflicts
+                    .into_iter()
+                    .filter(|conflict| !skipped.contains(conflict))
+                    .fold(
+                        arg.clone().conflicts_with(Resettable::Reset),
+                        |arg, conflict| arg.conflicts_with(conflict.get_id()),
+                    )
+            } else {
+                arg.clone()
+            };
+
+            command = command.arg(arg);
+        }
+
+        command
+    }
+
+    /// Read the global flags from the matches of a command
+    ///
+    /// Flags that are not defined on the command are treated as not set, which allows creating a
+    /// context for commands that were not generated by the `commands!` macro.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        Self {
+            no_input: flag(matches, "clawless_no_input"),
+            yes: flag(matches, "clawless_yes"),
+            quiet: flag(matches, "clawless_quiet"),
+            verbose: flag(matches, "clawless_verbose"),
+            output: value(matches, "clawless_output"),
+            color: value(matches, "clawless_color"),
+            profile: matches
+                .try_get_one::<String>("clawless_profile")
+                .ok()
+                .flatten()
+                .cloned(),
+            env_file: values(matches, "clawless_env_file"),
+            no_env_file: flag(matches, "clawless_no_env_file"),
+        }
+    }
+}
+
+/// Collect the long names and aliases of the arguments of a command and all of its subcommands,
+/// together with the path of the command that defines them
+fn collect_long_names<'a>(
+    command: &'a Command,
+    path: &mut Vec<&'a str>,
+    names: &mut HashMap<&'a str, String>,
+) {
+    path.push(command.get_name());
+
+    for arg in command.get_arguments() {
+        for name in long_names(arg) {
+            names.entry(name).or_insert_with(|| path.join(" "));
+        }
+    }
+
+    for subcommand in command.get_subcommands() {
+        collect_long_names(subcommand, path, names);
+    }
+
+    path.pop();
+}
+
+fn long_names(arg: &Arg) -> impl Iterator<Item = &str> {
+    arg.get_long()
+        .into_iter()
+        .chain(arg.get_all_aliases().unwrap_or_default())
+}
+
+fn flag(matches: &ArgMatches, id: &str) -> bool {
+    value(matches, id)
+}
+
+fn value<T>(matches: &ArgMatches, id: &str) -> T
+where
+    T: Copy + Default + Send + Sync + 'static,
+{
+    matches
+        .try_get_one::<T>(id)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or_default()
+}
+
+fn values<T>(matches: &ArgMatches, id: &str) -> Vec<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    matches
+        .try_get_many::<T>(id)
+        .ok()
+        .flatten()
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{ArgAction, FromArgMatches};
+
+    use super::*;
+
+    fn command() -> Command {
+        GlobalArgs::augment_command(Command::new("app").subcommand(Command::new("deploy")))
+    }
+
+    #[test]
+    fn from_matches_reads_flags_after_subcommand() {
+        let matches = command().get_matches_from(["app", "deploy", "--no-input", "--yes"]);
+        let (_, deploy) = matches.subcommand().unwrap();
+
+        let args = GlobalArgs::from_matches(deploy);
+
+        assert!(args.no_input);
+        assert!(args.yes);
+    }
+
+    #[test]
+    fn from_matches_matches_derive() {
+        let matches = command().get_matches_from([
+            "app",
+            "--yes",
+            "--verbose",
+            "--output",
+            "json",
+            "--color",
+            "never",
+            "--profile",
+            "prod",
+            "--env-file",
+            ".env",
+            "--env-file",
+            ".env.local",
+        ]);
+
+        assert_eq!(
+            GlobalArgs::from_arg_matches(&matches).unwrap(),
+            GlobalArgs::from_matches(&matches)
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "`app deploy` defines `--verbose`, which is a global flag of Clawless"
+    )]
+    fn augment_command_rejects_flags_that_are_used_by_the_application() {
+        GlobalArgs::augment_command(
+            Command::new("app").subcommand(
+                Command::new("deploy").arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .action(ArgAction::SetTrue),
+                ),
+            ),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "`debug` is not a global flag of Clawless")]
+    fn augment_command_without_rejects_unknown_flags() {
+        GlobalArgs::augment_command_without(Command::new("app"), &["debug"]);
+    }
+
+    #[test]
+    fn augment_command_without_leaves_out_flags() {
+        let command = GlobalArgs::augment_command_without(
+            Command::new("app").subcommand(
+                Command::new("deploy").arg(
+                    Arg::new("verbose")
+                        .short('v')
+                        .long("verbose")
+                        .action(ArgAction::SetTrue),
+                ),
+            ),
+            &["verbose"],
+        );
+
+        let matches = command
+            .try_get_matches_from(["app", "--quiet", "deploy", "--verbose"])
+            .unwrap();
+        let (_, deploy) = matches.subcommand().unwrap();
+
+        assert!(deploy.get_flag("verbose"));
+        assert!(GlobalArgs::from_matches(deploy).quiet);
+        assert!(!GlobalArgs::from_matches(deploy).verbose);
+    }
+
+    #[test]
+    fn augment_command_removes_conflicts_with_skipped_flags() {
+        let command = GlobalArgs::augment_command_without(
+            Command::new("app").arg(Arg::new("quiet").long("quiet").action(ArgAction::SetTrue)),
+            &["quiet"],
+        );
+
+        let matches = command
+            .try_get_matches_from(["app", "--quiet", "--verbose"])
+            .unwrap();
+
+        assert!(matches.get_flag("quiet"));
+        assert!(GlobalArgs::from_matches(&matches).verbose);
+    }
+
+    #[test]
+    fn augment_command_does_not_add_short_flags() {
+        let command = command();
+
+        assert!(command.get_arguments().all(|arg| arg.get_short().is_none()));
+    }
+
+    #[test]
+    fn from_matches_ignores_undefined_flags() {
+        let matches = Command::new("app").get_matches_from(["app"]);
+
+        assert_eq!(GlobalArgs::default(), GlobalArgs::from_matches(&matches));
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use clap::builder::styling::Style;
+use serde::Serialize;
+
+use super::progress::multi_progress;
+use super::{GlobalArgs, Terminal};
+use crate::theme::Theme;
+
+/// The format in which commands print their results
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Human,
+
+    /// Machine-readable JSON
+    Json,
+}
+
+/// Output of commands to the terminal
+///
+/// Commands print messages and results through the output in the `Context`. Messages are printed
+/// to stderr and are meant for humans, while results are printed to stdout in the format that the
+/// user has chosen with `--output`. Informational messages are suppressed when the user passes
+/// `--quiet`, and additional details are only printed when the user passes `--verbose`.
+///
+/// Success messages, warnings, and errors are styled with the theme of the application, unless
+/// colors are disabled for stderr.
+///
+/// The output cooperates with the progress bars of the context, so that messages printed while a
+/// progress bar is drawn appear above the bar instead of corrupting it.
+///
+/// ```rust,ignore
+/// #[command]
+/// pub async fn status(_args: StatusArgs, context: Context) -> CommandResult {
+///     let output = context.output();
+///
+///     output.info("Checking services...");
+///     output.print(&status)?;
+///     output.success("All services are healthy");
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Output {
+    format: OutputFormat,
+    quiet: bool,
+    verbose: bool,
+    color: bool,
+    theme: Theme,
+}
+
+impl Output {
+    /// Create the output for the global flags and the theme of the application
+    pub fn new(global_args: &GlobalArgs, terminal: &Terminal, theme: Theme) -> Self {
+        Self {
+            format: global_args.output,
+            quiet: global_args.quiet,
+            verbose: global_args.verbose,
+            color: terminal.stderr_color(),
+            theme,
+        }
+    }
+
+    /// Returns the format in which results are printed
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Returns true if informational messages are suppressed
+    pub fn is_quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Returns true if additional details are printed
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// Print a message with additional details, if the output is verbose
+    pub fn verbose(&self, message: impl Display) {
+        if self.verbose {
+            self.eprintln(message);
+        }
+    }
+
+    /// Print an informational message, unless the output is quiet
+    pub fn info(&self, message: impl Display) {
+        if !self.quiet {
+            self.eprintln(message);
+        }
+    }
+
+    /// Print a message that reports the successful completion of a task, unless the output is quiet
+    pub fn success(&self, message: impl Display) {
+        if !self.quiet {
+            self.eprintln(self.paint(self.theme.success(), message));
+        }
+    }
+
+    /// Print a warning
+    pub fn warning(&self, message: impl Display) {
+        let label = self.paint(self.theme.warning(), "warning:");
+        self.eprintln(format!("{label} {message}"));
+    }
+
+    /// Print an error
+    pub fn error(&self, message: impl Display) {
+        let label = self.paint(self.theme.error(), "error:");
+        self.eprintln(format!("{label} {message}"));
+    }
+
+    /// Print the result of a command to stdout
+    ///
+    /// The result is printed with its `Display` implementation for humans, and serialized as JSON
+    /// when the user passes `--output json`.
+    pub fn print<T>(&self, result: &T) -> Result<()>
+    where
+        T: Display + Serialize,
+    {
+        let text = self.render(result)?;
+
+        multi_progress().suspend(|| println!("{text}"));
+
+        Ok(())
+    }
+
+    fn render<T>(&self, result: &T) -> Result<String>
+    where
+        T: Display + Serialize,
+    {
+        Ok(match self.format {
+            OutputFormat::Human => result.to_string(),
+            OutputFormat::Json => serde_json::to_string(result)?,
+        })
+    }
+
+    fn paint(&self, style: Style, text: impl Display) -> String {
+        if self.color {
+            Theme::paint(style, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn eprintln(&self, message: impl Display) {
+        multi_progress().suspend(|| eprintln!("{message}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Formatter;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Status {
+        healthy: bool,
+    }
+
+    impl Display for Status {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "healthy: {}", self.healthy)
+        }
+    }
+
+    #[test]
+    fn new_reads_global_args() {
+        let global_args = GlobalArgs {
+            quiet: true,
+            verbose: true,
+            output: OutputFormat::Json,
+            ..GlobalArgs::default()
+        };
+
+        let output = Output::new(&global_args, &Terminal::default(), Theme::default());
+
+        assert!(output.is_quiet());
+        assert!(output.is_verbose());
+        assert_eq!(OutputFormat::Json, output.format());
+    }
+
+    #[test]
+    fn render_human() {
+        let rendered = Output::default().render(&Status { healthy: true }).unwrap();
+
+        assert_eq!("healthy: true", rendered);
+    }
+
+    #[test]
+    fn render_json() {
+        let output = Output {
+            format: OutputFormat::Json,
+            ..Output::default()
+        };
+
+        let rendered = output.render(&Status { healthy: true }).unwrap();
+
+        assert_eq!(r#"{"healthy":true}"#, rendered);
+    }
+
+    #[test]
+    fn paint_respects_color() {
+        let style = Theme::default().success();
+        let colored = Output {
+            color: true,
+            ..Output::default()
+        };
+
+        assert_eq!("done", Output::default().paint(style, "done"));
+        assert_eq!(Theme::paint(style, "done"), colored.paint(style, "done"));
+    }
+}
@@ -0,0 +1,332 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use getset::Getters;
+use serde::de::DeserializeOwned;
+use toml::Table;
+use typed_builder::TypedBuilder;
+
+use super::{Environment, GlobalArgs};
+use crate::config::{Config, ConfigEntry};
+
+/// The name of the profile that is used when no profile is selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The profile, or environment, that a command targets
+///
+/// Applications that work with several environments, e.g. staging and production, can define a
+/// profile for each of them in their configuration file:
+///
+/// ```toml
+/// default_profile = "staging"
+///
+/// [profile.staging]
+/// url = "https://staging.example.com"
+///
+/// [profile.prod]
+/// url = "https://example.com"
+/// protected = true
+/// ```
+///
+/// The active profile is selected with the global `--profile` flag, the `<APP>_PROFILE`
+/// environment variable, or the `default_profile` in the configuration file, in this order. If
+/// none of them is set, the profile is called `default`.
+///
+/// ```rust,ignore
+/// #[command]
+/// pub async fn deploy(_args: DeployArgs, context: Context) -> CommandResult {
+///     let profile = context.profile();
+///     let url: String = profile.require("url")?;
+///
+///     if profile.get::<bool>("protected")?.unwrap_or(false)
+///         && !context
+///             .prompt()
+///             .confirm(&format!("Deploy to {}?", profile.name()), Some(false))?
+///     {
+///         return Ok(());
+///     }
+///
+///     // ...
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Getters, TypedBuilder)]
+pub struct Profile {
+    /// The name of the profile
+    #[builder(default = DEFAULT_PROFILE.into(), setter(into))]
+    name: String,
+
+    /// The settings in the `[profile.<name>]` section of the configuration file
+    #[builder(default)]
+    #[getset(get = "pub")]
+    settings: Table,
+}
+
+impl Profile {
+    /// Select the active profile and read its settings from the configuration
+    ///
+    /// A profile that is selected explicitly, i.e. with `--profile` or the environment variable,
+    /// must be defined in the configuration if the configuration defines any profiles. This
+    /// prevents typos from targeting the wrong environment.
+    pub(crate) fn resolve(
+        global_args: &GlobalArgs,
+        environment: &Environment,
+        config: &Config,
+    ) -> Result<Self> {
+        let profiles = profiles(config)?;
+
+        let explicit = global_args
+            .profile
+            .clone()
+            .or_else(|| environment.app_var("PROFILE").map(String::from))
+            .filter(|name| !name.is_empty());
+        let configured = default_profile(config)?;
+
+        let is_explicit = explicit.is_some();
+        let name = explicit
+            .or(configured)
+            .unwrap_or_else(|| DEFAULT_PROFILE.into());
+
+        let settings = match profiles.get(&name) {
+            Some(settings) => settings
+                .as_table()
+                .cloned()
+                .ok_or_else(|| anyhow!("profile `{name}` must be a table"))?,
+            None if is_explicit && !profiles.is_empty() => {
+                let available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                bail!(
+                    "profile `{name}` is not defined, available profiles are: {}",
+                    available.join(", ")
+                );
+            }
+            None => Table::new(),
+        };
+
+        Ok(Self { name, settings })
+    }
+
+    /// Returns the name of the profile
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if no profile has been selected
+    pub fn is_default(&self) -> bool {
+        self.name == DEFAULT_PROFILE
+    }
+
+    /// Returns the value of a setting of the profile
+    ///
+    /// Returns `None` if the setting is not defined, and an error if its value has the wrong type.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.settings
+            .get(key)
+            .map(|value| {
+                value.clone().try_into().context(format!(
+                    "invalid value for `{key}` in profile `{}`",
+                    self.name
+                ))
+            })
+            .transpose()
+    }
+
+    /// Returns the value of a setting of the profile, and fails if it is not defined
+    pub fn require<T>(&self, key: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.get(key)?
+            .ok_or_else(|| anyhow!("`{key}` is not set in profile `{}`", self.name))
+    }
+}
+
+// Settings can contain floats, which are neither `Eq` nor `Ord`, so profiles are compared by the
+// TOML representation of their settings
+
+impl Profile {
+    fn key(&self) -> (&str, String) {
+        (&self.name, self.settings.to_string())
+    }
+}
+
+impl PartialEq for Profile {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Profile {}
+
+impl PartialOrd for Profile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Profile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for Profile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// Returns the `[profile]` table of the configuration
+fn profiles(config: &Config) -> Result<Table> {
+    match config.get("profile") {
+        Some(entry) => entry
+            .value()
+            .as_table()
+            .cloned()
+            .ok_or_else(|| anyhow!("`profile` must be a table of profiles{}", location(&entry))),
+        None => Ok(Table::new()),
+    }
+}
+
+/// Returns the `default_profile` of the configuration
+fn default_profile(config: &Config) -> Result<Option<String>> {
+    match config.get("default_profile") {
+        Some(entry) => entry
+            .value()
+            .as_str()
+            .map(|name| Some(name.into()))
+            .ok_or_else(|| anyhow!("`default_profile` must be a string{}", location(&entry))),
+        None => Ok(None),
+    }
+}
+
+/// Returns the file that a value comes from, formatted for an error message
+fn location(entry: &ConfigEntry) -> String {
+    entry
+        .path()
+        .as_ref()
+        .map(|path| format!(" in {}", path.display()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::config::ConfigFile;
+
+    fn config() -> Config {
+        let config_file = ConfigFile::new(
+            PathBuf::from("config.toml"),
+            toml::from_str(
+                r#"
+                default_profile = "staging"
+
+                [profile.staging]
+                url = "https://staging.example.com"
+
+                [profile.prod]
+                url = "https://example.com"
+                protected = true
+                "#,
+            )
+            .unwrap(),
+        );
+
+        Config::builder().user_file(config_file).build()
+    }
+
+    fn resolve(profile: Option<&str>, environment: Environment) -> Result<Profile> {
+        let global_args = GlobalArgs {
+            profile: profile.map(String::from),
+            ..GlobalArgs::default()
+        };
+
+        Profile::resolve(&global_args, &environment, &config())
+    }
+
+    #[test]
+    fn resolve_prefers_flag() {
+        let environment = Environment::new("my-app").with("MY_APP_PROFILE", "staging");
+
+        let profile = resolve(Some("prod"), environment).unwrap();
+
+        assert_eq!("prod", profile.name());
+        assert_eq!(Some(true), profile.get::<bool>("protected").unwrap());
+    }
+
+    #[test]
+    fn resolve_reads_environment_variable() {
+        let environment = Environment::new("my-app").with("MY_APP_PROFILE", "prod");
+
+        assert_eq!("prod", resolve(None, environment).unwrap().name());
+    }
+
+    #[test]
+    fn resolve_falls_back_to_default_profile() {
+        let profile = resolve(None, Environment::new("my-app")).unwrap();
+
+        assert_eq!("staging", profile.name());
+        assert_eq!(
+            "https://staging.example.com",
+            profile.require::<String>("url").unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_without_config_file() {
+        let profile = Profile::resolve(
+            &GlobalArgs::default(),
+            &Environment::new("my-app"),
+            &Config::default(),
+        )
+        .unwrap();
+
+        assert!(profile.is_default());
+        assert!(profile.settings().is_empty());
+    }
+
+    #[test]
+    fn resolve_fails_for_undefined_profile() {
+        let error = resolve(Some("prdo"), Environment::new("my-app")).unwrap_err();
+
+        assert_eq!(
+            "profile `prdo` is not defined, available profiles are: prod, staging",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn get_fails_for_wrong_type() {
+        let profile = resolve(Some("prod"), Environment::new("my-app")).unwrap();
+
+        let error = profile.get::<bool>("url").unwrap_err();
+
+        assert_eq!(
+            "invalid value for `url` in profile `prod`",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn require_fails_for_missing_setting() {
+        let profile = resolve(Some("staging"), Environment::new("my-app")).unwrap();
+
+        let error = profile.require::<bool>("protected").unwrap_err();
+
+        assert_eq!(
+            "`protected` is not set in profile `staging`",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+use super::{GlobalArgs, OutputFormat, Terminal};
+
+/// Template of progress bars with a known length
+const BAR_TEMPLATE: &str = "{spinner} {msg} [{wide_bar}] {pos}/{len} ({eta})";
+
+/// Template of spinners for tasks with an unknown length
+const SPINNER_TEMPLATE: &str = "{spinner} {msg} ({elapsed})";
+
+/// The interval in which spinners are redrawn
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress reporting for long-running tasks
+///
+/// Commands can show progress bars and spinners through the progress in the `Context`. Progress is
+/// only drawn when stderr is a terminal, and it is hidden when the user passes `--quiet` or asks
+/// for JSON output. Hidden progress bars can be used like visible ones, so commands don't need to
+/// check whether progress is shown.
+///
+/// All progress bars of an application are drawn together below each other, which makes it
+/// possible to track concurrent tasks. Messages that are printed through the `Output` of the
+/// context are printed above the progress bars without corrupting them.
+///
+/// ```rust,ignore
+/// #[command]
+/// pub async fn upload(args: UploadArgs, context: Context) -> CommandResult {
+///     let bar = context.progress().bar(args.files.len() as u64, "Uploading");
+///
+///     for file in &args.files {
+///         upload_file(file).await?;
+///         context.output().info(format!("Uploaded {}", file.display()));
+///         bar.inc(1);
+///     }
+///
+///     bar.finish_and_clear();
+///     Ok(())
+/// }
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Progress {
+    enabled: bool,
+}
+
+impl Progress {
+    /// Create the progress reporting for the terminal that the application runs in
+    pub fn new(global_args: &GlobalArgs, terminal: &Terminal) -> Self {
+        Self {
+            enabled: terminal.stderr_is_terminal()
+                && !global_args.quiet
+                && global_args.output == OutputFormat::Human,
+        }
+    }
+
+    /// Returns true if progress is drawn to the terminal
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Create a progress bar for a task with a known number of steps
+    pub fn bar(&self, len: u64, message: impl Into<Cow<'static, str>>) -> ProgressBar {
+        let bar = self.add(ProgressBar::new(len), BAR_TEMPLATE);
+        bar.set_message(message);
+
+        bar
+    }
+
+    /// Create a spinner for a task with an unknown number of steps
+    ///
+    /// The spinner is redrawn automatically until it is finished.
+    pub fn spinner(&self, message: impl Into<Cow<'static, str>>) -> ProgressBar {
+        let spinner = self.add(ProgressBar::new_spinner(), SPINNER_TEMPLATE);
+        spinner.set_message(message);
+        spinner.enable_steady_tick(TICK_INTERVAL);
+
+        spinner
+    }
+
+    fn add(&self, bar: ProgressBar, template: &str) -> ProgressBar {
+        if !self.enabled {
+            return ProgressBar::hidden();
+        }
+
+        // The templates are constants, so parsing them cannot fail at runtime
+        let style = ProgressStyle::with_template(template)
+            .expect("progress template must be valid")
+            .progress_chars("=> ");
+
+        multi_progress().add(bar.with_style(style))
+    }
+}
+
+/// Returns the container that draws all progress bars of the application
+///
+/// Output that is printed while progress bars are drawn must be printed through this container to
+/// avoid that the progress bars and the output overwrite each other.
+pub(crate) fn multi_progress() -> &'static MultiProgress {
+    static MULTI_PROGRESS: OnceLock<MultiProgress> = OnceLock::new();
+
+    MULTI_PROGRESS.get_or_init(MultiProgress::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terminal() -> Terminal {
+        Terminal::builder().stderr_is_terminal(true).build()
+    }
+
+    #[test]
+    fn new_disables_progress_when_quiet() {
+        let global_args = GlobalArgs {
+            quiet: true,
+            ..GlobalArgs::default()
+        };
+
+        assert!(!Progress::new(&global_args, &terminal()).is_enabled());
+    }
+
+    #[test]
+    fn new_disables_progress_for_json_output() {
+        let global_args = GlobalArgs {
+            output: OutputFormat::Json,
+            ..GlobalArgs::default()
+        };
+
+        assert!(!Progress::new(&global_args, &terminal()).is_enabled());
+    }
+
+    #[test]
+    fn new_disables_progress_without_terminal() {
+        let terminal = Terminal::default();
+
+        assert!(!Progress::new(&GlobalArgs::default(), &terminal).is_enabled());
+    }
+
+    #[test]
+    fn new_enables_progress_for_terminal() {
+        assert!(Progress::new(&GlobalArgs::default(), &terminal()).is_enabled());
+    }
+
+    #[test]
+    fn bar_is_hidden_when_disabled() {
+        let bar = Progress::default().bar(10, "Uploading");
+
+        assert!(bar.is_hidden());
+    }
+
+    #[test]
+    fn spinner_is_hidden_when_disabled() {
+        let spinner = Progress::default().spinner("Waiting");
+
+        assert!(spinner.is_hidden());
+    }
+
+    #[test]
+    fn templates_are_valid() {
+        assert!(ProgressStyle::with_template(BAR_TEMPLATE).is_ok());
+        assert!(ProgressStyle::with_template(SPINNER_TEMPLATE).is_ok());
+    }
+}
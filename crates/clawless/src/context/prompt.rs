@@ -0,0 +1,343 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow};
+use clap::Arg;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, MultiSelect, Password, Select};
+
+use super::{GlobalArgs, Terminal};
+use crate::runtime;
+
+/// Interactive prompts with fallbacks for non-interactive environments
+///
+/// Commands can ask users for input through the prompt in the `Context`. Prompts are only shown
+/// when stdin is a terminal and the user has not passed `--no-input` or `--yes`. Otherwise, the
+/// prompt returns its default value, or fails with an error that explains why the user could not
+/// be asked. This allows the same command to guide humans through a task and to run unattended in
+/// CI.
+///
+/// ```rust,ignore
+/// #[command]
+/// pub async fn deploy(args: DeployArgs, context: Context) -> CommandResult {
+///     let environment = context.prompt().text_if_missing(args.environment, "Environment")?;
+///
+///     if context.prompt().confirm(&format!("Deploy to {environment}?"), Some(false))? {
+///         // ...
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Prompt {
+    terminal: bool,
+    no_input: bool,
+    assume_yes: bool,
+}
+
+impl Prompt {
+    /// Create a prompt for the terminal that the application runs in
+    pub fn new(global_args: &GlobalArgs, terminal: &Terminal) -> Self {
+        Self {
+            terminal: terminal.stdin_is_terminal(),
+            no_input: global_args.no_input,
+            assume_yes: global_args.yes,
+        }
+    }
+
+    /// Returns true if prompts are shown to the user
+    pub fn is_interactive(&self) -> bool {
+        self.terminal && !self.no_input && !self.assume_yes
+    }
+
+    /// Ask the user for a line of text
+    pub fn text(&self, message: &str, default: Option<&str>) -> Result<String> {
+        if !self.is_interactive() {
+            return default
+                .map(String::from)
+                .ok_or_else(|| self.unavailable(message));
+        }
+
+        let theme = ColorfulTheme::default();
+        let mut input = Input::<String>::with_theme(&theme).with_prompt(message);
+
+        if let Some(default) = default {
+            input = input.default(default.to_string());
+        }
+
+        Ok(input.interact_text()?)
+    }
+
+    /// Ask the user for a secret, without echoing the input
+    ///
+    /// Passwords have no default value, so this prompt always fails in non-interactive
+    /// environments.
+    pub fn password(&self, message: &str) -> Result<String> {
+        if !self.is_interactive() {
+            return Err(self.unavailable(message));
+        }
+
+        let theme = ColorfulTheme::default();
+
+        Ok(Password::with_theme(&theme)
+            .with_prompt(message)
+            .interact()?)
+    }
+
+    /// Ask the user a yes or no question
+    ///
+    /// When the user has passed `--yes`, the question is answered with yes without prompting.
+    pub fn confirm(&self, message: &str, default: Option<bool>) -> Result<bool> {
+        if self.assume_yes {
+            return Ok(true);
+        }
+
+        if !self.is_interactive() {
+            return default.ok_or_else(|| self.unavailable(message));
+        }
+
+        let theme = ColorfulTheme::default();
+        let mut confirm = Confirm::with_theme(&theme).with_prompt(message);
+
+        if let Some(default) = default {
+            confirm = confirm.default(default);
+        }
+
+        Ok(confirm.interact()?)
+    }
+
+    /// Ask the user to pick one of several items, and return its index
+    pub fn select<T: ToString>(
+        &self,
+        message: &str,
+        items: &[T],
+        default: Option<usize>,
+    ) -> Result<usize> {
+        if !self.is_interactive() {
+            return default.ok_or_else(|| self.unavailable(message));
+        }
+
+        let theme = ColorfulTheme::default();
+        let select = Select::with_theme(&theme)
+            .with_prompt(message)
+            .items(items.iter().map(ToString::to_string))
+            .default(default.unwrap_or_default());
+
+        Ok(select.interact()?)
+    }
+
+    /// Ask the user to pick any number of items, and return their indices
+    pub fn multi_select<T: ToString>(
+        &self,
+        message: &str,
+        items: &[T],
+        default: Option<&[usize]>,
+    ) -> Result<Vec<usize>> {
+        if !self.is_interactive() {
+            return default
+                .map(<[usize]>::to_vec)
+                .ok_or_else(|| self.unavailable(message));
+        }
+
+        let checked: Vec<bool> = (0..items.len())
+            .map(|index| default.is_some_and(|default| default.contains(&index)))
+            .collect();
+
+        let theme = ColorfulTheme::default();
+        let multi_select = MultiSelect::with_theme(&theme)
+            .with_prompt(message)
+            .items(items.iter().map(ToString::to_string))
+            .defaults(&checked);
+
+        Ok(multi_select.interact()?)
+    }
+
+    /// Return the value of an optional argument, or ask the user for it if it is missing
+    ///
+    /// This turns an argument into one that is required for scripts but can be omitted by humans.
+    /// Declare the argument as an `Option` and pass it to this method:
+    ///
+    /// ```rust,ignore
+    /// #[derive(Debug, Args)]
+    /// pub struct InitArgs {
+    ///     /// The name of the project
+    ///     #[arg(long)]
+    ///     name: Option<String>,
+    /// }
+    ///
+    /// let name = context.prompt().text_if_missing(args.name, "Name of the project")?;
+    /// ```
+    ///
+    /// The input is parsed with `FromStr`, and the user is asked again if it cannot be parsed.
+    pub fn text_if_missing<T>(&self, value: Option<T>, message: &str) -> Result<T>
+    where
+        T: Clone + ToString + FromStr,
+        T::Err: Display,
+    {
+        if let Some(value) = value {
+            return Ok(value);
+        }
+
+        if !self.is_interactive() {
+            return Err(anyhow!(
+                "missing a value for \"{message}\": pass it as an argument, since {}",
+                self.reason()
+            ));
+        }
+
+        let theme = ColorfulTheme::default();
+
+        Ok(Input::<T>::with_theme(&theme)
+            .with_prompt(message)
+            .interact_text()?)
+    }
+
+    fn unavailable(&self, message: &str) -> Error {
+        anyhow!(
+            "cannot prompt for \"{message}\" without a default value, since {}",
+            self.reason()
+        )
+    }
+
+    fn reason(&self) -> &'static str {
+        if self.no_input {
+            "prompts are disabled with --no-input"
+        } else if self.assume_yes {
+            "prompts are disabled with --yes"
+        } else {
+            "stdin is not a terminal"
+        }
+    }
+}
+
+/// Prompt for required arguments that are missing on the command line
+///
+/// Instead of failing, the application asks the user for the value of a required argument that
+/// is missing, but only if it can prompt. Scripts and CI still have to pass the argument, and get
+/// the usual error from clap if they don't. Since clap passes unknown attributes to the builder
+/// of the argument, the prompt is declared with `#[arg(prompt = "...")]`:
+///
+/// ```rust,ignore
+/// #[derive(Debug, Args)]
+/// pub struct InitArgs {
+///     /// The name of the project
+///     #[arg(long, prompt = "Name of the project")]
+///     name: String,
+/// }
+/// ```
+pub trait ArgPrompt {
+    /// Ask the user for the value of the argument with the message if it is missing
+    fn prompt(self, message: impl Into<String>) -> Self;
+}
+
+impl ArgPrompt for Arg {
+    fn prompt(self, message: impl Into<String>) -> Self {
+        runtime::record_prompt(self.get_id().as_str(), message.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(no_input: bool, assume_yes: bool) -> Prompt {
+        Prompt {
+            terminal: true,
+            no_input,
+            assume_yes,
+        }
+    }
+
+    #[test]
+    fn is_interactive_requires_terminal() {
+        assert!(prompt(false, false).is_interactive());
+        assert!(!Prompt::default().is_interactive());
+        assert!(!prompt(true, false).is_interactive());
+        assert!(!prompt(false, true).is_interactive());
+    }
+
+    #[test]
+    fn text_returns_default_when_not_interactive() {
+        let text = Prompt::default().text("Name", Some("otter")).unwrap();
+
+        assert_eq!("otter", text);
+    }
+
+    #[test]
+    fn text_fails_without_default_when_not_interactive() {
+        let error = Prompt::default().text("Name", None).unwrap_err();
+
+        assert_eq!(
+            "cannot prompt for \"Name\" without a default value, since stdin is not a terminal",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn password_fails_with_no_input() {
+        let error = prompt(true, false).password("Token").unwrap_err();
+
+        assert_eq!(
+            "cannot prompt for \"Token\" without a default value, since prompts are disabled with --no-input",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn confirm_returns_true_with_yes() {
+        assert!(
+            prompt(false, true)
+                .confirm("Continue?", Some(false))
+                .unwrap()
+        );
+        assert!(prompt(false, true).confirm("Continue?", None).unwrap());
+    }
+
+    #[test]
+    fn confirm_returns_default_when_not_interactive() {
+        assert!(!Prompt::default().confirm("Continue?", Some(false)).unwrap());
+        assert!(Prompt::default().confirm("Continue?", None).is_err());
+    }
+
+    #[test]
+    fn select_returns_default_when_not_interactive() {
+        let items = ["dev", "prod"];
+
+        assert_eq!(1, Prompt::default().select("Env", &items, Some(1)).unwrap());
+        assert!(Prompt::default().select("Env", &items, None).is_err());
+    }
+
+    #[test]
+    fn multi_select_returns_default_when_not_interactive() {
+        let items = ["api", "web", "worker"];
+
+        let selected = Prompt::default()
+            .multi_select("Services", &items, Some(&[0, 2]))
+            .unwrap();
+
+        assert_eq!(vec![0, 2], selected);
+    }
+
+    #[test]
+    fn text_if_missing_returns_value() {
+        let value = Prompt::default()
+            .text_if_missing(Some(42u16), "Port")
+            .unwrap();
+
+        assert_eq!(42, value);
+    }
+
+    #[test]
+    fn text_if_missing_fails_when_not_interactive() {
+        let error = Prompt::default()
+            .text_if_missing::<String>(None, "Name")
+            .unwrap_err();
+
+        assert_eq!(
+            "missing a value for \"Name\": pass it as an argument, since stdin is not a terminal",
+            error.to_string()
+        );
+    }
+}
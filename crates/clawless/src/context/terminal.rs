@@ -0,0 +1,230 @@
+use std::io::IsTerminal;
+
+use clap::{ColorChoice, ValueEnum};
+use getset::CopyGetters;
+use typed_builder::TypedBuilder;
+
+use super::Environment;
+
+/// Environment variables that are set by popular CI providers
+const CI_VARIABLES: &[&str] = &[
+    "CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+    "TF_BUILD",
+];
+
+/// When to use colors in the output
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, ValueEnum)]
+pub enum ColorMode {
+    /// Use colors if the output is a terminal and the environment does not disable them
+    #[default]
+    Auto,
+
+    /// Always use colors
+    Always,
+
+    /// Never use colors
+    Never,
+}
+
+impl From<ColorMode> for ColorChoice {
+    fn from(color: ColorMode) -> Self {
+        match color {
+            ColorMode::Auto => ColorChoice::Auto,
+            ColorMode::Always => ColorChoice::Always,
+            ColorMode::Never => ColorChoice::Never,
+        }
+    }
+}
+
+/// Capabilities of the terminal that the application runs in
+///
+/// The terminal reports whether the standard streams are connected to a terminal, how wide the
+/// terminal is, whether colors should be used, and whether the application runs in CI. Commands
+/// can use it to adapt their output to the environment without reimplementing these checks.
+///
+/// Colors follow the `--color` flag. In the default `auto` mode, colors are used when the stream
+/// is a terminal, unless they are disabled with `NO_COLOR` or `CLICOLOR=0`. `CLICOLOR_FORCE`
+/// enables colors even if the stream is not a terminal.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, CopyGetters, TypedBuilder,
+)]
+pub struct Terminal {
+    /// Whether stdin is connected to a terminal
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    stdin_is_terminal: bool,
+
+    /// Whether stdout is connected to a terminal
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    stdout_is_terminal: bool,
+
+    /// Whether stderr is connected to a terminal
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    stderr_is_terminal: bool,
+
+    /// The width of the terminal in columns, if it is known
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    width: Option<u16>,
+
+    /// Whether colors should be used for output on stdout
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    stdout_color: bool,
+
+    /// Whether colors should be used for output on stderr
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    stderr_color: bool,
+
+    /// Whether the application runs in a continuous integration environment
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    ci: bool,
+}
+
+impl Terminal {
+    /// Detect the capabilities of the terminal that the application runs in
+    ///
+    /// The environment variables that control colors, the width, and CI are read from the snapshot
+    /// of the environment.
+    pub fn detect(color: ColorMode, environment: &Environment) -> Self {
+        Self::from_env(
+            color,
+            [
+                std::io::stdin().is_terminal(),
+                std::io::stdout().is_terminal(),
+                std::io::stderr().is_terminal(),
+            ],
+            console::Term::stdout()
+                .size_checked()
+                .map(|(_, width)| width),
+            |key| environment.get(key).map(String::from),
+        )
+    }
+
+    fn from_env(
+        color: ColorMode,
+        [stdin, stdout, stderr]: [bool; 3],
+        size: Option<u16>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let width = env("COLUMNS")
+            .and_then(|columns| columns.parse().ok())
+            .or(size);
+
+        Self {
+            stdin_is_terminal: stdin,
+            stdout_is_terminal: stdout,
+            stderr_is_terminal: stderr,
+            width,
+            stdout_color: use_color(color, stdout, &env),
+            stderr_color: use_color(color, stderr, &env),
+            ci: CI_VARIABLES
+                .iter()
+                .any(|key| env(key).is_some_and(|value| !value.is_empty() && value != "false")),
+        }
+    }
+
+    /// Apply the color settings to the libraries that draw prompts and progress bars
+    pub(crate) fn apply(&self) {
+        console::set_colors_enabled(self.stdout_color);
+        console::set_colors_enabled_stderr(self.stderr_color);
+    }
+}
+
+fn use_color(color: ColorMode, is_terminal: bool, env: &impl Fn(&str) -> Option<String>) -> bool {
+    match color {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => {
+            let is_set = |key: &str| env(key).is_some_and(|value| !value.is_empty());
+
+            if is_set("NO_COLOR") {
+                false
+            } else if is_set("CLICOLOR_FORCE") && env("CLICOLOR_FORCE").as_deref() != Some("0") {
+                true
+            } else if env("CLICOLOR").as_deref() == Some("0")
+                || env("TERM").as_deref() == Some("dumb")
+            {
+                false
+            } else {
+                is_terminal
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn terminal(color: ColorMode, is_terminal: bool, vars: &[(&str, &str)]) -> Terminal {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        Terminal::from_env(color, [is_terminal; 3], Some(80), |key| {
+            vars.get(key).cloned()
+        })
+    }
+
+    #[test]
+    fn auto_uses_colors_for_terminals() {
+        assert!(terminal(ColorMode::Auto, true, &[]).stdout_color());
+        assert!(!terminal(ColorMode::Auto, false, &[]).stdout_color());
+    }
+
+    #[test]
+    fn auto_respects_no_color() {
+        let terminal = terminal(ColorMode::Auto, true, &[("NO_COLOR", "1")]);
+
+        assert!(!terminal.stdout_color());
+        assert!(!terminal.stderr_color());
+    }
+
+    #[test]
+    fn auto_respects_clicolor_force() {
+        assert!(terminal(ColorMode::Auto, false, &[("CLICOLOR_FORCE", "1")]).stdout_color());
+        assert!(!terminal(ColorMode::Auto, false, &[("CLICOLOR_FORCE", "0")]).stdout_color());
+    }
+
+    #[test]
+    fn auto_respects_clicolor() {
+        assert!(!terminal(ColorMode::Auto, true, &[("CLICOLOR", "0")]).stdout_color());
+    }
+
+    #[test]
+    fn color_flag_overrides_environment() {
+        assert!(terminal(ColorMode::Always, false, &[("NO_COLOR", "1")]).stdout_color());
+        assert!(!terminal(ColorMode::Never, true, &[("CLICOLOR_FORCE", "1")]).stdout_color());
+    }
+
+    #[test]
+    fn width_prefers_columns() {
+        assert_eq!(Some(80), terminal(ColorMode::Auto, true, &[]).width());
+        assert_eq!(
+            Some(120),
+            terminal(ColorMode::Auto, true, &[("COLUMNS", "120")]).width()
+        );
+    }
+
+    #[test]
+    fn ci_is_detected_from_environment() {
+        assert!(!terminal(ColorMode::Auto, true, &[]).ci());
+        assert!(terminal(ColorMode::Auto, true, &[("CI", "true")]).ci());
+        assert!(terminal(ColorMode::Auto, true, &[("GITHUB_ACTIONS", "true")]).ci());
+        assert!(!terminal(ColorMode::Auto, true, &[("CI", "false")]).ci());
+    }
+}
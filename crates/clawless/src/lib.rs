@@ -17,9 +17,18 @@ pub mod prelude {
 
 pub use clawless_derive::{command, commands, main};
 pub use error::{CommandResult, Error, ErrorContext};
+pub use registry::registry;
 
+pub mod aliases;
+pub mod config;
 pub mod context;
 mod error;
+pub mod manifest;
+pub mod plugins;
+pub mod registry;
+#[doc(hidden)]
+pub mod runtime;
+pub mod theme;
 
 // Re-export the clap crate for use with the `clawless-derive` crate
 #[doc(hidden)]
@@ -0,0 +1,433 @@
+//! Manifests describing the command-line surface of an application
+//!
+//! Users script against the commands, aliases, arguments, and flags of a command-line application,
+//! which turns every change to them into a potential breaking change. This module serializes the
+//! command tree of a Clawless application into a `Manifest`, which can be stored as JSON and
+//! compared against the manifest of another version of the application to find breaking changes.
+//!
+//! ```rust,ignore
+//! use clawless::manifest::Manifest;
+//!
+//! // The root command is registered by the `commands!` macro
+//! let manifest = Manifest::from_registry().context("no commands are registered")?;
+//! std::fs::write("cli.json", manifest.to_json()?)?;
+//! ```
+//!
+//! Every Clawless application also prints its manifest with the hidden `--clawless-manifest` flag,
+//! e.g. `mycli --clawless-manifest > cli.json`.
+
+use std::fmt::{Display, Formatter};
+
+use anyhow::Result;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use getset::{CopyGetters, Getters};
+use serde::{Deserialize, Serialize};
+
+pub use self::breaking_change::BreakingChange;
+use crate::CommandResult;
+use crate::context::Context;
+use crate::registry::RootCommand;
+
+mod breaking_change;
+
+/// The id of the hidden flag that prints the manifest of an application
+const FLAG_ID: &str = "clawless_manifest";
+
+/// The version of the manifest format
+///
+/// The version is increased whenever the format changes in a way that prevents older manifests
+/// from being compared with newer ones.
+const FORMAT_VERSION: u32 = 1;
+
+/// Manifest of the command-line surface of an application
+///
+/// The manifest captures everything that users of a command-line application can rely on: the
+/// commands and their aliases, and the arguments of each command with their flags, default values,
+/// and whether they are required.
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, CopyGetters, Getters, Serialize, Deserialize,
+)]
+pub struct Manifest {
+    /// The version of the manifest format
+    #[getset(get_copy = "pub")]
+    format_version: u32,
+
+    /// The root command of the application
+    #[getset(get = "pub")]
+    root: CommandManifest,
+}
+
+/// Manifest of a single command and its subcommands
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Getters, Serialize, Deserialize)]
+pub struct CommandManifest {
+    /// The name of the command
+    #[getset(get = "pub")]
+    name: String,
+
+    /// All aliases of the command, including hidden ones
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+
+    /// The short description of the command
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    about: Option<String>,
+
+    /// Whether the command is hidden from the help output
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "is_false")]
+    hidden: bool,
+
+    /// The arguments that the command accepts
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    arguments: Vec<ArgumentManifest>,
+
+    /// The subcommands of the command
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subcommands: Vec<CommandManifest>,
+}
+
+/// Manifest of a single argument of a command
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Getters, Serialize, Deserialize)]
+pub struct ArgumentManifest {
+    /// The identifier of the argument, which is usually the name of the struct field
+    #[getset(get = "pub")]
+    id: String,
+
+    /// The long flag of the argument, without the leading dashes
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    long: Option<String>,
+
+    /// All aliases of the long flag, including hidden ones
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    long_aliases: Vec<String>,
+
+    /// The short flag of the argument, without the leading dash
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    short: Option<char>,
+
+    /// All aliases of the short flag, including hidden ones
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    short_aliases: Vec<char>,
+
+    /// The position of a positional argument, starting at 1
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    index: Option<usize>,
+
+    /// Whether the argument must be provided by the user
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "is_false")]
+    required: bool,
+
+    /// Whether the argument takes a value, or is a flag that is toggled by its presence
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "is_false")]
+    takes_value: bool,
+
+    /// The default values of the argument
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    default_values: Vec<String>,
+
+    /// The values that the argument accepts, or an empty list if it accepts any value
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    possible_values: Vec<String>,
+
+    /// Whether the argument is hidden from the help output
+    #[getset(get = "pub")]
+    #[serde(default, skip_serializing_if = "is_false")]
+    hidden: bool,
+}
+
+impl Manifest {
+    /// Create the manifest for a command and all of its subcommands
+    ///
+    /// Pass the root command of the application, which is returned by the `clawless_init`
+    /// function that the `commands!` macro generates, to create a manifest for the whole
+    /// application.
+    pub fn from_command(command: &Command) -> Self {
+        // Building the command finalizes its arguments, e.g. assigns indices to positionals
+        let mut command = command.clone();
+        command.build();
+
+        Self {
+            format_version: FORMAT_VERSION,
+            root: CommandManifest::from_command(&command),
+        }
+    }
+
+    /// Create the manifest of the running application
+    ///
+    /// The manifest is created from the root command that the `commands!` macro registers. `None`
+    /// is returned if no root command has been registered, like for [`crate::registry`].
+    pub fn from_registry() -> Option<Self> {
+        inventory::iter::<RootCommand>
+            .into_iter()
+            .next()
+            .map(|root| Self::from_command(&(root.init)()))
+    }
+
+    /// Parse a manifest from its JSON representation
+    pub fn from_json(json: &str) -> Result<Self> {
+        let manifest: Manifest = serde_json::from_str(json)?;
+
+        if manifest.format_version != FORMAT_VERSION {
+            anyhow::bail!(
+                "unsupported manifest format version {} (expected {})",
+                manifest.format_version,
+                FORMAT_VERSION
+            );
+        }
+
+        Ok(manifest)
+    }
+
+    /// Serialize the manifest to JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Find the breaking changes between this manifest and a newer one
+    ///
+    /// Changes are breaking if scripts that work with the application described by this manifest
+    /// can fail with the application described by `newer`. For example, removing a command or
+    /// making an optional argument required are breaking changes, while adding a new command is
+    /// not.
+    pub fn breaking_changes(&self, newer: &Manifest) -> Vec<BreakingChange> {
+        breaking_change::compare(self, newer)
+    }
+}
+
+impl Display for Manifest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let json = self.to_json().map_err(|_| std::fmt::Error)?;
+
+        write!(f, "{json}")
+    }
+}
+
+impl CommandManifest {
+    fn from_command(command: &Command) -> Self {
+        Self {
+            name: command.get_name().to_string(),
+            aliases: command.get_all_aliases().map(String::from).collect(),
+            about: command.get_about().map(ToString::to_string),
+            hidden: command.is_hide_set(),
+            arguments: command
+                .get_arguments()
+                .filter(|arg| !is_generated(arg))
+                .map(ArgumentManifest::from_arg)
+                .collect(),
+            subcommands: command
+                .get_subcommands()
+                .filter(|subcommand| subcommand.get_name() != "help")
+                .map(CommandManifest::from_command)
+                .collect(),
+        }
+    }
+
+    /// Find a subcommand by its name or one of its aliases
+    pub fn find_subcommand(&self, name: &str) -> Option<&CommandManifest> {
+        self.subcommands
+            .iter()
+            .find(|subcommand| subcommand.name == name)
+            .or_else(|| {
+                self.subcommands
+                    .iter()
+                    .find(|subcommand| subcommand.aliases.iter().any(|alias| alias == name))
+            })
+    }
+}
+
+impl ArgumentManifest {
+    fn from_arg(arg: &Arg) -> Self {
+        Self {
+            id: arg.get_id().to_string(),
+            long: arg.get_long().map(String::from),
+            long_aliases: arg
+                .get_all_aliases()
+                .unwrap_or_default()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            short: arg.get_short(),
+            short_aliases: arg.get_all_short_aliases().unwrap_or_default(),
+            index: arg.get_index(),
+            required: arg.is_required_set(),
+            takes_value: arg.get_action().takes_values(),
+            default_values: arg
+                .get_default_values()
+                .iter()
+                .map(|value| value.to_string_lossy().into_owned())
+                .collect(),
+            possible_values: arg
+                .get_possible_values()
+                .iter()
+                .map(|value| value.get_name().to_string())
+                .collect(),
+            hidden: arg.is_hide_set(),
+        }
+    }
+
+    /// Returns a human-readable name for the argument, e.g. `--name` or `<NAME>`
+    pub fn display_name(&self) -> String {
+        match (&self.long, self.short) {
+            (Some(long), _) => format!("--{long}"),
+            (None, Some(short)) => format!("-{short}"),
+            (None, None) => format!("<{}>", self.id.to_uppercase()),
+        }
+    }
+}
+
+/// Returns the hidden `--clawless-manifest` flag, which the `commands!` macro adds to the root command
+#[doc(hidden)]
+pub fn flag() -> Arg {
+    Arg::new(FLAG_ID)
+        .long("clawless-manifest")
+        .action(ArgAction::SetTrue)
+        .hide(true)
+        .help("Print the manifest of the command-line interface as JSON")
+}
+
+/// Returns whether the hidden `--clawless-manifest` flag was passed
+#[doc(hidden)]
+pub fn is_requested(matches: &ArgMatches) -> bool {
+    matches
+        .try_get_one::<bool>(FLAG_ID)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Print the manifest of the running application
+#[doc(hidden)]
+pub fn print(context: &Context) -> CommandResult {
+    let manifest =
+        Manifest::from_registry().ok_or_else(|| anyhow::anyhow!("no commands are registered"))?;
+
+    context.output().print(&manifest)?;
+
+    Ok(())
+}
+
+/// Returns true for the arguments that clap and Clawless generate, e.g. `--help` and `--version`
+fn is_generated(arg: &Arg) -> bool {
+    arg.get_id() == FLAG_ID
+        || matches!(
+            arg.get_action(),
+            ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version
+        )
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Arg;
+
+    use super::*;
+
+    fn command() -> Command {
+        Command::new("app").subcommand(
+            Command::new("deploy")
+                .about("Deploy the application")
+                .visible_alias("d")
+                .arg(
+                    Arg::new("env")
+                        .long("env")
+                        .short('e')
+                        .default_value("staging"),
+                )
+                .arg(Arg::new("target").required(true)),
+        )
+    }
+
+    #[test]
+    fn from_command_skips_generated_arguments() {
+        let manifest = Manifest::from_command(&command());
+
+        let deploy = manifest.root().find_subcommand("deploy").unwrap();
+        let ids: Vec<&str> = deploy
+            .arguments()
+            .iter()
+            .map(|arg| arg.id().as_str())
+            .collect();
+
+        assert_eq!(vec!["env", "target"], ids);
+    }
+
+    #[test]
+    fn from_command_captures_arguments() {
+        let manifest = Manifest::from_command(&command());
+
+        let deploy = manifest.root().find_subcommand("deploy").unwrap();
+        let env = &deploy.arguments()[0];
+        let target = &deploy.arguments()[1];
+
+        assert_eq!(&Some("env".to_string()), env.long());
+        assert_eq!(&Some('e'), env.short());
+        assert_eq!(&vec!["staging".to_string()], env.default_values());
+        assert!(!env.required());
+        assert_eq!(&Some(1), target.index());
+        assert!(target.required());
+    }
+
+    #[test]
+    fn from_command_skips_manifest_flag() {
+        let manifest = Manifest::from_command(&command().arg(flag()));
+
+        assert!(manifest.root().arguments().is_empty());
+    }
+
+    #[test]
+    fn is_requested_reads_flag() {
+        let command = command().arg(flag());
+
+        assert!(is_requested(
+            &command
+                .clone()
+                .get_matches_from(["app", "--clawless-manifest"])
+        ));
+        assert!(!is_requested(&command.get_matches_from(["app"])));
+        assert!(!is_requested(
+            &Command::new("app").get_matches_from(["app"])
+        ));
+    }
+
+    #[test]
+    fn find_subcommand_by_alias() {
+        let manifest = Manifest::from_command(&command());
+
+        let deploy = manifest.root().find_subcommand("d").unwrap();
+
+        assert_eq!("deploy", deploy.name());
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let manifest = Manifest::from_command(&command());
+
+        let json = manifest.to_json().unwrap();
+
+        assert_eq!(manifest, Manifest::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn from_json_rejects_unknown_format_version() {
+        let json = r#"{ "format_version": 999, "root": { "name": "app" } }"#;
+
+        assert!(Manifest::from_json(json).is_err());
+    }
+}
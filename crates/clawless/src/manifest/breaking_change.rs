@@ -0,0 +1,530 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ArgumentManifest, CommandManifest, Manifest};
+
+/// A change to the command-line surface that can break existing scripts
+///
+/// Each change references the command it applies to by its path, which is the list of subcommand
+/// names that lead to the command from the root command. The root command itself has an empty
+/// path.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BreakingChange {
+    /// A command was removed
+    RemovedCommand {
+        /// The path of the removed command
+        command: Vec<String>,
+    },
+
+    /// An alias of a command was removed
+    RemovedAlias {
+        /// The path of the command
+        command: Vec<String>,
+        /// The removed alias
+        alias: String,
+    },
+
+    /// An argument was removed from a command
+    RemovedArgument {
+        /// The path of the command
+        command: Vec<String>,
+        /// The name of the removed argument
+        argument: String,
+    },
+
+    /// An argument is now required, either because it was added or because it was optional before
+    RequiredArgument {
+        /// The path of the command
+        command: Vec<String>,
+        /// The name of the required argument
+        argument: String,
+    },
+
+    /// The long flag of an argument was renamed or removed
+    RenamedFlag {
+        /// The path of the command
+        command: Vec<String>,
+        /// The old long flag
+        old: String,
+        /// The new long flag, if the argument still has one
+        new: Option<String>,
+    },
+
+    /// The short flag of an argument was removed
+    RemovedShortFlag {
+        /// The path of the command
+        command: Vec<String>,
+        /// The name of the argument
+        argument: String,
+        /// The removed short flag
+        short: char,
+    },
+
+    /// A flag started or stopped taking a value
+    ChangedValueRequirement {
+        /// The path of the command
+        command: Vec<String>,
+        /// The name of the argument
+        argument: String,
+        /// Whether the argument now takes a value
+        takes_value: bool,
+    },
+
+    /// The default value of an argument changed
+    ChangedDefault {
+        /// The path of the command
+        command: Vec<String>,
+        /// The name of the argument
+        argument: String,
+        /// The old default values
+        old: Vec<String>,
+        /// The new default values
+        new: Vec<String>,
+    },
+
+    /// A value is no longer accepted by an argument
+    RemovedPossibleValue {
+        /// The path of the command
+        command: Vec<String>,
+        /// The name of the argument
+        argument: String,
+        /// The value that is no longer accepted
+        value: String,
+    },
+
+    /// A positional argument moved to a different position
+    MovedPositional {
+        /// The path of the command
+        command: Vec<String>,
+        /// The name of the argument
+        argument: String,
+        /// The old position
+        old: usize,
+        /// The new position
+        new: usize,
+    },
+}
+
+impl Display for BreakingChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BreakingChange::RemovedCommand { command } => {
+                write!(f, "{} was removed", display_command(command))
+            }
+            BreakingChange::RemovedAlias { command, alias } => {
+                write!(
+                    f,
+                    "alias `{alias}` of {} was removed",
+                    display_command(command)
+                )
+            }
+            BreakingChange::RemovedArgument { command, argument } => write!(
+                f,
+                "argument `{argument}` of {} was removed",
+                display_command(command)
+            ),
+            BreakingChange::RequiredArgument { command, argument } => write!(
+                f,
+                "argument `{argument}` of {} is now required",
+                display_command(command)
+            ),
+            BreakingChange::RenamedFlag { command, old, new } => match new {
+                Some(new) => write!(
+                    f,
+                    "flag `--{old}` of {} was renamed to `--{new}`",
+                    display_command(command)
+                ),
+                None => write!(
+                    f,
+                    "flag `--{old}` of {} was removed",
+                    display_command(command)
+                ),
+            },
+            BreakingChange::RemovedShortFlag {
+                command,
+                argument,
+                short,
+            } => write!(
+                f,
+                "short flag `-{short}` of argument `{argument}` of {} was removed",
+                display_command(command)
+            ),
+            BreakingChange::ChangedValueRequirement {
+                command,
+                argument,
+                takes_value,
+            } => write!(
+                f,
+                "argument `{argument}` of {} {}",
+                display_command(command),
+                if *takes_value {
+                    "now requires a value"
+                } else {
+                    "no longer accepts a value"
+                }
+            ),
+            BreakingChange::ChangedDefault {
+                command,
+                argument,
+                old,
+                new,
+            } => write!(
+                f,
+                "default value of argument `{argument}` of {} changed from {} to {}",
+                display_command(command),
+                display_values(old),
+                display_values(new)
+            ),
+            BreakingChange::RemovedPossibleValue {
+                command,
+                argument,
+                value,
+            } => write!(
+                f,
+                "argument `{argument}` of {} no longer accepts `{value}`",
+                display_command(command)
+            ),
+            BreakingChange::MovedPositional {
+                command,
+                argument,
+                old,
+                new,
+            } => write!(
+                f,
+                "positional argument `{argument}` of {} moved from position {old} to {new}",
+                display_command(command)
+            ),
+        }
+    }
+}
+
+/// Compare two manifests and return the breaking changes from `older` to `newer`
+pub(super) fn compare(older: &Manifest, newer: &Manifest) -> Vec<BreakingChange> {
+    let mut changes = Vec::new();
+
+    compare_commands(&mut changes, &[], older.root(), newer.root());
+
+    changes
+}
+
+fn compare_commands(
+    changes: &mut Vec<BreakingChange>,
+    path: &[String],
+    older: &CommandManifest,
+    newer: &CommandManifest,
+) {
+    for alias in older.aliases() {
+        if !newer.aliases().contains(alias) && newer.name() != alias {
+            changes.push(BreakingChange::RemovedAlias {
+                command: path.to_vec(),
+                alias: alias.clone(),
+            });
+        }
+    }
+
+    compare_arguments(changes, path, older.arguments(), newer.arguments());
+
+    for old_subcommand in older.subcommands() {
+        let mut subcommand_path = path.to_vec();
+        subcommand_path.push(old_subcommand.name().clone());
+
+        // A command that was renamed but kept its old name as an alias still works for users
+        match newer.find_subcommand(old_subcommand.name()) {
+            Some(new_subcommand) => {
+                compare_commands(changes, &subcommand_path, old_subcommand, new_subcommand)
+            }
+            None => changes.push(BreakingChange::RemovedCommand {
+                command: subcommand_path,
+            }),
+        }
+    }
+}
+
+fn compare_arguments(
+    changes: &mut Vec<BreakingChange>,
+    path: &[String],
+    older: &[ArgumentManifest],
+    newer: &[ArgumentManifest],
+) {
+    for old_argument in older {
+        match find_argument(newer, old_argument) {
+            Some(new_argument) => compare_argument(changes, path, old_argument, new_argument),
+            None => changes.push(BreakingChange::RemovedArgument {
+                command: path.to_vec(),
+                argument: old_argument.display_name(),
+            }),
+        }
+    }
+
+    for new_argument in newer {
+        if *new_argument.required() && find_argument(older, new_argument).is_none() {
+            changes.push(BreakingChange::RequiredArgument {
+                command: path.to_vec(),
+                argument: new_argument.display_name(),
+            });
+        }
+    }
+}
+
+/// Find the counterpart of an argument, either by its identifier or by its long flag
+fn find_argument<'a>(
+    arguments: &'a [ArgumentManifest],
+    argument: &ArgumentManifest,
+) -> Option<&'a ArgumentManifest> {
+    arguments
+        .iter()
+        .find(|candidate| candidate.id() == argument.id())
+        .or_else(|| {
+            let long = argument.long().as_ref()?;
+            arguments.iter().find(|candidate| {
+                candidate.long().as_ref() == Some(long) || candidate.long_aliases().contains(long)
+            })
+        })
+}
+
+fn compare_argument(
+    changes: &mut Vec<BreakingChange>,
+    path: &[String],
+    older: &ArgumentManifest,
+    newer: &ArgumentManifest,
+) {
+    let argument = older.display_name();
+
+    if let Some(long) = older.long()
+        && newer.long().as_ref() != Some(long)
+        && !newer.long_aliases().contains(long)
+    {
+        changes.push(BreakingChange::RenamedFlag {
+            command: path.to_vec(),
+            old: long.clone(),
+            new: newer.long().clone(),
+        });
+    }
+
+    if let Some(short) = older.short()
+        && newer.short() != &Some(*short)
+        && !newer.short_aliases().contains(short)
+    {
+        changes.push(BreakingChange::RemovedShortFlag {
+            command: path.to_vec(),
+            argument: argument.clone(),
+            short: *short,
+        });
+    }
+
+    if !older.required() && *newer.required() {
+        changes.push(BreakingChange::RequiredArgument {
+            command: path.to_vec(),
+            argument: argument.clone(),
+        });
+    }
+
+    if older.takes_value() != newer.takes_value() {
+        changes.push(BreakingChange::ChangedValueRequirement {
+            command: path.to_vec(),
+            argument: argument.clone(),
+            takes_value: *newer.takes_value(),
+        });
+    }
+
+    if older.default_values() != newer.default_values() {
+        changes.push(BreakingChange::ChangedDefault {
+            command: path.to_vec(),
+            argument: argument.clone(),
+            old: older.default_values().clone(),
+            new: newer.default_values().clone(),
+        });
+    }
+
+    // An empty list of possible values means that the argument accepts any value
+    if !newer.possible_values().is_empty() {
+        for value in older.possible_values() {
+            if !newer.possible_values().contains(value) {
+                changes.push(BreakingChange::RemovedPossibleValue {
+                    command: path.to_vec(),
+                    argument: argument.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+    }
+
+    if let (Some(old), Some(new)) = (older.index(), newer.index())
+        && old != new
+    {
+        changes.push(BreakingChange::MovedPositional {
+            command: path.to_vec(),
+            argument,
+            old: *old,
+            new: *new,
+        });
+    }
+}
+
+fn display_command(path: &[String]) -> String {
+    if path.is_empty() {
+        "the root command".to_string()
+    } else {
+        format!("command `{}`", path.join(" "))
+    }
+}
+
+fn display_values(values: &[String]) -> String {
+    if values.is_empty() {
+        "none".to_string()
+    } else {
+        format!("`{}`", values.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Arg, ArgAction, Command};
+
+    use super::*;
+
+    fn deploy() -> Command {
+        Command::new("deploy")
+            .arg(
+                Arg::new("env")
+                    .long("env")
+                    .short('e')
+                    .default_value("staging"),
+            )
+            .arg(Arg::new("force").long("force").action(ArgAction::SetTrue))
+    }
+
+    fn changes(older: Command, newer: Command) -> Vec<BreakingChange> {
+        let older = Manifest::from_command(&older);
+        let newer = Manifest::from_command(&newer);
+
+        older.breaking_changes(&newer)
+    }
+
+    fn path(command: &str) -> Vec<String> {
+        vec![command.to_string()]
+    }
+
+    #[test]
+    fn identical_manifests_have_no_changes() {
+        let app = Command::new("app").subcommand(deploy());
+
+        assert!(changes(app.clone(), app).is_empty());
+    }
+
+    #[test]
+    fn added_command_is_not_breaking() {
+        let older = Command::new("app").subcommand(deploy());
+        let newer = older.clone().subcommand(Command::new("status"));
+
+        assert!(changes(older, newer).is_empty());
+    }
+
+    #[test]
+    fn removed_command_is_breaking() {
+        let older = Command::new("app").subcommand(deploy());
+        let newer = Command::new("app");
+
+        assert_eq!(
+            vec![BreakingChange::RemovedCommand {
+                command: path("deploy")
+            }],
+            changes(older, newer)
+        );
+    }
+
+    #[test]
+    fn renamed_command_with_alias_is_not_breaking() {
+        let older = Command::new("app").subcommand(Command::new("deploy"));
+        let newer = Command::new("app").subcommand(Command::new("release").alias("deploy"));
+
+        assert!(changes(older, newer).is_empty());
+    }
+
+    #[test]
+    fn removed_alias_is_breaking() {
+        let older = Command::new("app").subcommand(deploy().visible_alias("d"));
+        let newer = Command::new("app").subcommand(deploy());
+
+        assert_eq!(
+            vec![BreakingChange::RemovedAlias {
+                command: path("deploy"),
+                alias: "d".to_string(),
+            }],
+            changes(older, newer)
+        );
+    }
+
+    #[test]
+    fn newly_required_argument_is_breaking() {
+        let older = Command::new("app").subcommand(deploy());
+        let newer = Command::new("app")
+            .subcommand(deploy().arg(Arg::new("region").long("region").required(true)));
+
+        assert_eq!(
+            vec![BreakingChange::RequiredArgument {
+                command: path("deploy"),
+                argument: "--region".to_string(),
+            }],
+            changes(older, newer)
+        );
+    }
+
+    #[test]
+    fn renamed_flag_is_breaking() {
+        let older = Command::new("app").subcommand(deploy());
+        let newer =
+            Command::new("app").subcommand(deploy().mut_arg("env", |arg| arg.long("environment")));
+
+        assert_eq!(
+            vec![BreakingChange::RenamedFlag {
+                command: path("deploy"),
+                old: "env".to_string(),
+                new: Some("environment".to_string()),
+            }],
+            changes(older, newer)
+        );
+    }
+
+    #[test]
+    fn renamed_flag_with_alias_is_not_breaking() {
+        let older = Command::new("app").subcommand(deploy());
+        let newer = Command::new("app")
+            .subcommand(deploy().mut_arg("env", |arg| arg.long("environment").alias("env")));
+
+        assert!(changes(older, newer).is_empty());
+    }
+
+    #[test]
+    fn changed_default_is_breaking() {
+        let older = Command::new("app").subcommand(deploy());
+        let newer = Command::new("app")
+            .subcommand(deploy().mut_arg("env", |arg| arg.default_value("production")));
+
+        assert_eq!(
+            vec![BreakingChange::ChangedDefault {
+                command: path("deploy"),
+                argument: "--env".to_string(),
+                old: vec!["staging".to_string()],
+                new: vec!["production".to_string()],
+            }],
+            changes(older, newer)
+        );
+    }
+
+    #[test]
+    fn display_renamed_flag() {
+        let change = BreakingChange::RenamedFlag {
+            command: vec!["db".to_string(), "migrate".to_string()],
+            old: "env".to_string(),
+            new: Some("environment".to_string()),
+        };
+
+        assert_eq!(
+            "flag `--env` of command `db migrate` was renamed to `--environment`",
+            change.to_string()
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! External subcommands that are provided by other executables
+//!
+//! Applications can opt into dispatching unknown subcommands to executables on the `PATH`, like
+//! `cargo` and `git` do. When a user runs `mycli deploy` and the application has no `deploy`
+//! command, Clawless looks for an executable named `mycli-deploy` and runs it with the remaining
+//! arguments. This allows other teams to extend an application without changing its source code.
+//!
+//! Enable plugins in the commands module of the application:
+//!
+//! ```rust,ignore
+//! // src/commands.rs
+//! mod greet;
+//!
+//! clawless::commands!(external_subcommands);
+//! ```
+
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use anyhow::{Context as _, Result};
+use clap::builder::{StyledStr, Styles};
+use clap::{ArgMatches, Command};
+use getset::Getters;
+
+use crate::CommandResult;
+use crate::context::Context;
+use crate::runtime::{Replacements, missing_plugin};
+
+/// An executable that provides a subcommand for an application
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Getters)]
+pub struct Plugin {
+    /// The name of the subcommand that the plugin provides
+    #[getset(get = "pub")]
+    name: String,
+
+    /// The path to the executable
+    #[getset(get = "pub")]
+    path: PathBuf,
+}
+
+impl Plugin {
+    /// Run the plugin with the given arguments
+    ///
+    /// The plugin inherits the environment of the application, including the variables that were
+    /// loaded from `.env` files, and is run in the working directory that is captured in the
+    /// context.
+    pub async fn run(&self, args: &[OsString], context: &Context) -> Result<ExitStatus> {
+        tokio::process::Command::new(&self.path)
+            .args(args)
+            .envs(context.environment().iter())
+            .current_dir(context.current_working_directory().get())
+            .status()
+            .await
+            .context(format!("failed to run plugin at {}", self.path.display()))
+    }
+}
+
+/// Discover all plugins for an application in the directories of a `PATH`-like variable
+///
+/// Directories are searched in order, and the first executable for a subcommand wins. The plugins
+/// are returned sorted by their name.
+pub fn discover(app_name: &str, path: &OsStr) -> Vec<Plugin> {
+    let prefix = format!("{app_name}-");
+    let mut plugins = BTreeMap::new();
+
+    for directory in std::env::split_paths(path) {
+        let Ok(entries) = std::fs::read_dir(&directory) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            let Some(name) = path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .and_then(|file_name| plugin_name(file_name, &prefix))
+                .map(ToString::to_string)
+            else {
+                continue;
+            };
+
+            if is_executable(&path) {
+                plugins
+                    .entry(name.clone())
+                    .or_insert_with(|| Plugin { name, path });
+            }
+        }
+    }
+
+    plugins.into_values().collect()
+}
+
+/// Find the plugin for a subcommand in the directories of a `PATH`-like variable
+pub fn find(app_name: &str, subcommand: &str, path: &OsStr) -> Option<Plugin> {
+    let file_name = format!("{app_name}-{subcommand}{}", std::env::consts::EXE_SUFFIX);
+
+    std::env::split_paths(path)
+        .map(|directory| directory.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+        .map(|path| Plugin {
+            name: subcommand.to_string(),
+            path,
+        })
+}
+
+/// Returns a help section that lists the plugins that are installed for an application
+///
+/// The section is appended to the help of the root command when plugins are enabled. Plugins are
+/// only discovered when the help is rendered, since searching the `PATH` is too slow to do on
+/// every invocation.
+#[doc(hidden)]
+pub fn help(app_name: &str, styles: &Styles) -> StyledStr {
+    let Some(path) = std::env::var_os("PATH") else {
+        return StyledStr::new();
+    };
+
+    help_for(&discover(app_name, &path), styles)
+}
+
+fn help_for(plugins: &[Plugin], styles: &Styles) -> StyledStr {
+    if plugins.is_empty() {
+        return StyledStr::new();
+    }
+
+    let header = styles.get_header();
+    let literal = styles.get_literal();
+    let width = plugins
+        .iter()
+        .map(|plugin| plugin.name.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut help = format!("{header}Plugins:{header:#}\n");
+    for plugin in plugins {
+        help.push_str(&format!(
+            "  {literal}{:width$}{literal:#}  {}\n",
+            plugin.name,
+            plugin.path.display()
+        ));
+    }
+
+    StyledStr::from(help.trim_end().to_string())
+}
+
+/// Run the plugin for an external subcommand
+///
+/// This function is called by the root command when clap could not match a subcommand. Plugins are
+/// named after the package of the application, so that `mycli` runs `mycli-<subcommand>` no matter
+/// how its executable was invoked. If the plugin exits with an error, the application exits with
+/// the same status code.
+///
+/// If no plugin provides the subcommand, the error suggests similar commands of the application,
+/// which are found in the root command and its replacements.
+#[doc(hidden)]
+pub async fn dispatch(
+    app_name: &str,
+    subcommand: &str,
+    matches: &ArgMatches,
+    context: &Context,
+    command: fn() -> Command,
+    replacements: Replacements,
+) -> CommandResult {
+    let path = context.environment().get("PATH").unwrap_or_default();
+
+    let Some(plugin) = find(app_name, subcommand, OsStr::new(path)) else {
+        let (message, code) = missing_plugin(command(), app_name, subcommand, replacements);
+        context.output().error(message);
+        std::process::exit(code);
+    };
+
+    let args: Vec<OsString> = matches
+        .get_many::<OsString>("")
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default();
+
+    let status = plugin.run(&args, context).await?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+fn plugin_name<'a>(file_name: &'a str, prefix: &str) -> Option<&'a str> {
+    let name = file_name
+        .strip_prefix(prefix)?
+        .strip_suffix(std::env::consts::EXE_SUFFIX)?;
+
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs::{Permissions, read_to_string, set_permissions, write};
+    use std::os::unix::fs::PermissionsExt;
+
+    use clap::builder::styling::AnsiColor;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn create_plugin(directory: &Path, file_name: &str, script: &str) -> PathBuf {
+        let path = directory.join(file_name);
+
+        write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        set_permissions(&path, Permissions::from_mode(0o755)).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn discover_finds_executables_with_prefix() {
+        let directory = TempDir::new().unwrap();
+
+        create_plugin(directory.path(), "app-deploy", "");
+        create_plugin(directory.path(), "other-lint", "");
+        write(directory.path().join("app-readme"), "not executable").unwrap();
+
+        let plugins = discover("app", directory.path().as_os_str());
+        let names: Vec<&str> = plugins
+            .iter()
+            .map(|plugin| plugin.name().as_str())
+            .collect();
+
+        assert_eq!(vec!["deploy"], names);
+    }
+
+    #[test]
+    fn discover_prefers_earlier_directories() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+
+        let expected = create_plugin(first.path(), "app-deploy", "");
+        create_plugin(second.path(), "app-deploy", "");
+
+        let path = std::env::join_paths([first.path(), second.path()]).unwrap();
+        let plugins = discover("app", &path);
+
+        assert_eq!(1, plugins.len());
+        assert_eq!(&expected, plugins[0].path());
+    }
+
+    #[test]
+    fn find_returns_plugin_for_subcommand() {
+        let directory = TempDir::new().unwrap();
+
+        let expected = create_plugin(directory.path(), "app-deploy", "");
+
+        let plugin = find("app", "deploy", directory.path().as_os_str()).unwrap();
+
+        assert_eq!(&expected, plugin.path());
+        assert!(find("app", "lint", directory.path().as_os_str()).is_none());
+    }
+
+    #[test]
+    fn help_for_lists_plugins() {
+        let plugins = vec![Plugin {
+            name: "deploy".to_string(),
+            path: PathBuf::from("/usr/bin/app-deploy"),
+        }];
+
+        let help = help_for(&plugins, &Styles::plain()).to_string();
+
+        assert!(help.contains("Plugins:"));
+        assert!(help.contains("deploy  /usr/bin/app-deploy"));
+    }
+
+    #[test]
+    fn help_for_uses_styles() {
+        let plugins = vec![Plugin {
+            name: "deploy".to_string(),
+            path: PathBuf::from("/usr/bin/app-deploy"),
+        }];
+        let styles = Styles::plain().header(AnsiColor::Magenta.on_default());
+
+        let help = help_for(&plugins, &styles).ansi().to_string();
+
+        assert!(help.starts_with(&format!("{}Plugins:", AnsiColor::Magenta.on_default())));
+    }
+
+    #[test]
+    fn help_for_is_empty_without_plugins() {
+        assert!(help_for(&[], &Styles::default()).to_string().is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_forwards_arguments_and_working_directory() {
+        let bin = TempDir::new().unwrap();
+        let cwd = TempDir::new().unwrap();
+
+        create_plugin(bin.path(), "app-echo", r#"echo "$@" > output.txt"#);
+        let plugin = find("app", "echo", bin.path().as_os_str()).unwrap();
+
+        let context = Context::builder()
+            .current_working_directory(cwd.path())
+            .build();
+
+        let status = plugin
+            .run(&["--env".into(), "prod".into()], &context)
+            .await
+            .unwrap();
+
+        assert!(status.success());
+        assert_eq!(
+            "--env prod\n",
+            read_to_string(cwd.path().join("output.txt")).unwrap()
+        );
+    }
+}
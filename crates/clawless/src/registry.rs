@@ -0,0 +1,209 @@
+//! Runtime introspection of the registered commands
+//!
+//! Commands in Clawless are registered with their parent module by the `#[command]` macro, which
+//! makes it impossible for applications to look at the command tree without going through clap.
+//! This module exposes the registered commands as a tree of `CommandMetadata`, which applications
+//! can use to build their own help pages, command listings, or documentation.
+//!
+//! ```rust,ignore
+//! use clawless::prelude::*;
+//!
+//! #[command]
+//! pub async fn commands(_args: CommandsArgs, _context: Context) -> CommandResult {
+//!     let registry = clawless::registry().context("no commands are registered")?;
+//!
+//!     for command in registry.iter().skip(1) {
+//!         println!("{}", command.path().join(" "));
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use clap::Command;
+use getset::Getters;
+use serde::Serialize;
+
+/// Registration of the root command of an application
+///
+/// The `commands!` macro submits the root command of the application to the inventory, which
+/// enables the `registry` function to find it at runtime.
+#[doc(hidden)]
+pub struct RootCommand {
+    /// Initialization function that returns the root command and all of its subcommands
+    pub init: fn() -> Command,
+}
+
+inventory::collect!(RootCommand);
+
+/// Metadata of a registered command
+///
+/// The metadata describes a command and, recursively, all of its subcommands. Use `iter` to walk
+/// the whole tree, or `find` to look up a command by its path.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Getters, Serialize)]
+pub struct CommandMetadata {
+    /// The name of the command
+    #[getset(get = "pub")]
+    name: String,
+
+    /// The names of the subcommands leading from the root command to this command
+    ///
+    /// The path of the root command is empty, while the path of `mycli db migrate` is
+    /// `["db", "migrate"]`.
+    #[getset(get = "pub")]
+    path: Vec<String>,
+
+    /// The aliases of the command that are shown to users
+    #[getset(get = "pub")]
+    aliases: Vec<String>,
+
+    /// The short description of the command, taken from the first line of its doc comment
+    #[getset(get = "pub")]
+    about: Option<String>,
+
+    /// The long description of the command, taken from its full doc comment
+    #[getset(get = "pub")]
+    long_about: Option<String>,
+
+    /// Whether the command is hidden from the help output
+    #[getset(get = "pub")]
+    hidden: bool,
+
+    /// The subcommands of the command
+    #[getset(get = "pub")]
+    subcommands: Vec<CommandMetadata>,
+}
+
+/// Returns the command tree of the running application
+///
+/// The tree starts at the root command that is generated by the `commands!` macro. `None` is
+/// returned if no root command has been registered, e.g. when the `commands!` macro has not been
+/// called in the binary.
+pub fn registry() -> Option<CommandMetadata> {
+    inventory::iter::<RootCommand>
+        .into_iter()
+        .next()
+        .map(|root| CommandMetadata::from_command(&(root.init)()))
+}
+
+impl CommandMetadata {
+    /// Create the metadata for a clap command and all of its subcommands
+    pub fn from_command(command: &Command) -> Self {
+        Self::with_path(command, Vec::new())
+    }
+
+    fn with_path(command: &Command, path: Vec<String>) -> Self {
+        let subcommands = command
+            .get_subcommands()
+            .map(|subcommand| {
+                let mut subcommand_path = path.clone();
+                subcommand_path.push(subcommand.get_name().to_string());
+
+                Self::with_path(subcommand, subcommand_path)
+            })
+            .collect();
+
+        Self {
+            name: command.get_name().to_string(),
+            path,
+            aliases: command.get_visible_aliases().map(String::from).collect(),
+            about: command.get_about().map(ToString::to_string),
+            long_about: command.get_long_about().map(ToString::to_string),
+            hidden: command.is_hide_set(),
+            subcommands,
+        }
+    }
+
+    /// Find a command in the tree by its path, matching both names and aliases
+    pub fn find(&self, path: &[&str]) -> Option<&CommandMetadata> {
+        let Some((name, rest)) = path.split_first() else {
+            return Some(self);
+        };
+
+        self.subcommands
+            .iter()
+            .find(|subcommand| {
+                subcommand.name == *name || subcommand.aliases.iter().any(|alias| alias == name)
+            })
+            .and_then(|subcommand| subcommand.find(rest))
+    }
+
+    /// Iterate over this command and all of its descendants in depth-first order
+    pub fn iter(&self) -> impl Iterator<Item = &CommandMetadata> {
+        let mut stack = vec![self];
+
+        std::iter::from_fn(move || {
+            let command = stack.pop()?;
+            stack.extend(command.subcommands.iter().rev());
+            Some(command)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> Command {
+        Command::new("app")
+            .subcommand(
+                Command::new("db")
+                    .about("Manage the database")
+                    .visible_alias("d")
+                    .subcommand(Command::new("migrate"))
+                    .subcommand(Command::new("seed")),
+            )
+            .subcommand(Command::new("greet").hide(true))
+    }
+
+    inventory::submit!(RootCommand { init: app });
+
+    #[test]
+    fn registry_returns_registered_root_command() {
+        let registry = registry().unwrap();
+
+        assert_eq!("app", registry.name());
+    }
+
+    #[test]
+    fn from_command_sets_paths() {
+        let metadata = CommandMetadata::from_command(&app());
+
+        let paths: Vec<String> = metadata
+            .iter()
+            .map(|command| command.path().join(" "))
+            .collect();
+
+        assert_eq!(vec!["", "db", "db migrate", "db seed", "greet"], paths);
+    }
+
+    #[test]
+    fn from_command_captures_metadata() {
+        let metadata = CommandMetadata::from_command(&app());
+
+        let db = metadata.find(&["db"]).unwrap();
+
+        assert_eq!(&Some("Manage the database".to_string()), db.about());
+        assert_eq!(&vec!["d".to_string()], db.aliases());
+        assert!(metadata.find(&["greet"]).unwrap().hidden());
+    }
+
+    #[test]
+    fn find_matches_aliases() {
+        let metadata = CommandMetadata::from_command(&app());
+
+        let migrate = metadata.find(&["d", "migrate"]).unwrap();
+
+        assert_eq!(
+            &vec!["db".to_string(), "migrate".to_string()],
+            migrate.path()
+        );
+    }
+
+    #[test]
+    fn find_returns_none_for_unknown_command() {
+        let metadata = CommandMetadata::from_command(&app());
+
+        assert!(metadata.find(&["db", "unknown"]).is_none());
+    }
+}
@@ -0,0 +1,672 @@
+//! Functions that the `main!` macro uses to run an application
+//!
+//! The functions in this module are called by the code that the macros of Clawless generate, and
+//! are not meant to be used directly by applications.
+
+use std::cell::RefCell;
+use std::ffi::OsString;
+
+use anyhow::Result;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{Arg, ArgMatches, Command, ValueEnum};
+
+use crate::aliases::positional_index;
+use crate::config::Config;
+use crate::context::{
+    ColorMode, CurrentWorkingDirectory, Dirs, Environment, GlobalArgs, Output, Prompt, Terminal,
+};
+use crate::plugins;
+use crate::theme::Theme;
+
+/// Function that returns the replacement of a deprecated command
+///
+/// The command is identified by the names of the subcommands that lead to it from the root
+/// command. The `#[command]` macro generates such a function for every command.
+pub type Replacements = fn(&[&str]) -> Option<&'static str>;
+
+/// Function that returns the ids of the arguments of a command and the messages to prompt for them
+///
+/// The command is identified like in [`Replacements`], and its arguments declare their prompts with
+/// `#[arg(prompt = "...")]`. The `#[command]` macro generates such a function for every command.
+pub type Prompts = fn(&[&str]) -> Vec<(String, String)>;
+
+thread_local! {
+    static PROMPTS: RefCell<Option<Vec<(String, String)>>> = const { RefCell::new(None) };
+}
+
+/// Collect the prompts that are declared while the arguments of a command are built
+pub fn collect_prompts(augment_args: impl FnOnce()) -> Vec<(String, String)> {
+    PROMPTS.with(|prompts| prompts.replace(Some(Vec::new())));
+    augment_args();
+
+    PROMPTS.with(|prompts| prompts.take()).unwrap_or_default()
+}
+
+/// Record the prompt of an argument if the prompts of a command are being collected
+pub(crate) fn record_prompt(id: &str, message: String) {
+    PROMPTS.with(|prompts| {
+        if let Some(prompts) = prompts.borrow_mut().as_mut() {
+            prompts.push((id.to_string(), message));
+        }
+    });
+}
+
+/// Load the configuration of an application from the user's and the project's configuration files
+///
+/// The configuration is loaded before the command line is parsed, so that the user-defined aliases
+/// can be expanded, and is then passed on to the context.
+pub fn load_config(app_name: &str) -> Result<Config> {
+    let current_working_directory = CurrentWorkingDirectory::try_from_env()?;
+    let environment = Environment::from_env(app_name);
+    let dirs = Dirs::new(app_name, &environment);
+
+    Config::load(app_name, &dirs, current_working_directory.get())
+}
+
+/// Returns the output for messages that are printed before the context has been created
+pub fn output(app_name: &str, theme: Theme) -> Output {
+    let terminal = Terminal::detect(ColorMode::default(), &Environment::from_env(app_name));
+
+    Output::new(&GlobalArgs::default(), &terminal, theme)
+}
+
+/// Parse the command line, and exit if it is invalid
+///
+/// Unknown subcommands are reported through the output of the application, with suggestions for
+/// similar commands. Hidden commands are never suggested. If a hidden command has been replaced by
+/// another command, the replacement is suggested instead.
+///
+/// When the application dispatches unknown subcommands to plugins, the installed plugins are listed
+/// in the help. They are only discovered when the help is actually rendered.
+///
+/// Required arguments that are missing are prompted for if they declare a prompt and the user can
+/// be asked. The values are then added to the command line, so that clap validates them like any
+/// other argument.
+///
+/// The `--color` flag is read before the command line is parsed, so that it also applies to the
+/// help and the errors of clap.
+pub fn get_matches(
+    mut command: Command,
+    args: Vec<OsString>,
+    app_name: &str,
+    replacements: Replacements,
+    prompts: Prompts,
+    output: &Output,
+) -> ArgMatches {
+    if let Some(color) = color_mode(&command, &args) {
+        command = command.color(color.into());
+    }
+
+    match command.try_get_matches_from_mut(&args) {
+        Ok(matches) => matches,
+        Err(error) if error.kind() == ErrorKind::InvalidSubcommand => {
+            output.error(invalid_subcommand(
+                &error,
+                &command,
+                &args,
+                replacements,
+                &[],
+            ));
+            std::process::exit(error.exit_code());
+        }
+        Err(error) if error.kind() == ErrorKind::MissingRequiredArgument => {
+            let args = match prompt_missing(&command, args, app_name, prompts) {
+                Ok(Some(args)) => args,
+                Ok(None) => error.exit(),
+                Err(error) => {
+                    output.error(format!("{error:#}"));
+                    std::process::exit(1);
+                }
+            };
+
+            match command.try_get_matches_from_mut(&args) {
+                Ok(matches) => matches,
+                Err(error) => error.exit(),
+            }
+        }
+        Err(error) if is_help(&error) && command.is_allow_external_subcommands_set() => {
+            let help = plugins::help(app_name, command.get_styles());
+
+            match command.after_help(help).try_get_matches_from(&args) {
+                Ok(matches) => matches,
+                Err(error) => error.exit(),
+            }
+        }
+        Err(error) => error.exit(),
+    }
+}
+
+/// Returns the color mode that is passed with `--color`, if the application has the flag
+///
+/// The arguments after `--` are not options, and values that clap would reject are ignored, since
+/// clap reports them when it parses the command line.
+fn color_mode(command: &Command, args: &[OsString]) -> Option<ColorMode> {
+    let long = command
+        .get_arguments()
+        .find(|arg| arg.get_id() == "clawless_color")?
+        .get_long()?;
+    let flag = format!("--{long}");
+
+    let mut color = None;
+    let mut args = args.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix(&flag) {
+            _ if arg == "--" => break,
+            Some("") => args.next(),
+            Some(value) => value
+                .strip_prefix('=')
+                .map(|value| value.to_string().into()),
+            None => None,
+        };
+
+        if let Some(value) = value.and_then(|value| ColorMode::from_str(&value, true).ok()) {
+            color = Some(value);
+        }
+    }
+
+    color
+}
+
+/// Ask the user for the missing arguments that declare a prompt, and add them to the command line
+///
+/// `None` is returned if there is nothing to prompt for, or if the user cannot be asked, in which
+/// case the original error is reported.
+fn prompt_missing(
+    command: &Command,
+    mut args: Vec<OsString>,
+    app_name: &str,
+    prompts: Prompts,
+) -> Result<Option<Vec<OsString>>> {
+    let (path, end_of_path) = command_path(command, &args);
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+
+    let leaf = path.iter().fold(command, |command, name| {
+        command.find_subcommand(name).unwrap_or(command)
+    });
+    let prompts: Vec<(&Arg, String)> = prompts(&path)
+        .into_iter()
+        .filter_map(|(id, message)| {
+            leaf.get_arguments()
+                .find(|arg| arg.get_id() == id.as_str() && arg.is_required_set())
+                .map(|arg| (arg, message))
+        })
+        .collect();
+    if prompts.is_empty() {
+        return Ok(None);
+    }
+
+    // Parse the command line again without requiring the arguments that can be prompted for, so
+    // that the global flags and the missing arguments are known
+    let ids: Vec<&str> = prompts
+        .iter()
+        .map(|(arg, _)| arg.get_id().as_str())
+        .collect();
+    let Ok(matches) = relax(command.clone(), &path, &ids).try_get_matches_from(&args) else {
+        return Ok(None);
+    };
+    let matches = path.iter().fold(&matches, |matches, name| {
+        matches.subcommand_matches(name).unwrap_or(matches)
+    });
+
+    let global_args = GlobalArgs::from_matches(matches);
+    let terminal = Terminal::detect(global_args.color, &Environment::from_env(app_name));
+    let prompt = Prompt::new(&global_args, &terminal);
+    if !prompt.is_interactive() {
+        return Ok(None);
+    }
+
+    let mut options = Vec::new();
+    let mut positionals = Vec::new();
+
+    for (arg, message) in &prompts {
+        if matches.contains_id(arg.get_id().as_str()) {
+            continue;
+        }
+
+        let value = prompt.text(message, None)?;
+
+        if arg.is_positional() {
+            positionals.push((arg.get_index().unwrap_or_default(), value));
+        } else if let Some(long) = arg.get_long() {
+            options.push(OsString::from(format!("--{long}={value}")));
+        } else if let Some(short) = arg.get_short() {
+            options.push(OsString::from(format!("-{short}")));
+            options.push(OsString::from(value));
+        }
+    }
+
+    // Positional arguments are filled in order, so the missing ones are always the last ones
+    positionals.sort();
+    if !positionals.is_empty() && !args[end_of_path..].iter().any(|arg| arg == "--") {
+        args.push("--".into());
+    }
+    args.extend(
+        positionals
+            .into_iter()
+            .map(|(_, value)| OsString::from(value)),
+    );
+    args.splice(end_of_path..end_of_path, options);
+
+    Ok(Some(args))
+}
+
+/// Returns the command with the arguments of a subcommand no longer being required
+fn relax(command: Command, path: &[&str], ids: &[&str]) -> Command {
+    match path.split_first() {
+        Some((name, path)) => {
+            command.mut_subcommand(*name, |subcommand| relax(subcommand, path, ids))
+        }
+        None => ids.iter().fold(command, |command, id| {
+            command.mut_arg(*id, |arg| arg.required(false))
+        }),
+    }
+}
+
+/// Returns the names of the subcommands in a command line, and the position after the last one
+fn command_path(command: &Command, args: &[OsString]) -> (Vec<String>, usize) {
+    let mut path = Vec::new();
+    let mut current = command;
+    let mut end = 1;
+
+    while let Some(index) = positional_index(current, args, end) {
+        let Some(subcommand) = args[index]
+            .to_str()
+            .and_then(|arg| find_subcommand(current, arg))
+        else {
+            break;
+        };
+
+        path.push(subcommand.get_name().to_string());
+        current = subcommand;
+        end = index + 1;
+    }
+
+    (path, end)
+}
+
+fn is_help(error: &clap::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::DisplayHelp | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+    )
+}
+
+/// Render the error for an external subcommand that no plugin provides
+///
+/// Unknown subcommands are accepted by clap when the application dispatches them to plugins. If no
+/// plugin is installed for the subcommand, it is parsed again without plugins, so that the error
+/// suggests similar commands like for any other unknown subcommand. Returns the message and the
+/// exit code of the error.
+pub(crate) fn missing_plugin(
+    command: Command,
+    app_name: &str,
+    subcommand: &str,
+    replacements: Replacements,
+) -> (String, i32) {
+    let mut command = command.allow_external_subcommands(false);
+    let args: Vec<OsString> = [app_name, subcommand].map(OsString::from).to_vec();
+    let tip = format!("no plugin '{app_name}-{subcommand}' was found on the PATH");
+
+    match command.try_get_matches_from_mut(&args) {
+        Err(error) if error.kind() == ErrorKind::InvalidSubcommand => (
+            invalid_subcommand(&error, &command, &args, replacements, &[tip]),
+            error.exit_code(),
+        ),
+        _ => (
+            format!("unrecognized subcommand '{subcommand}'\n\n  tip: {tip}"),
+            2,
+        ),
+    }
+}
+
+/// Render the error for an unknown subcommand, with the suggestions of Clawless and extra tips
+fn invalid_subcommand(
+    error: &clap::Error,
+    command: &Command,
+    args: &[OsString],
+    replacements: Replacements,
+    tips: &[String],
+) -> String {
+    let Some(ContextValue::String(invalid)) = error.get(ContextKind::InvalidSubcommand) else {
+        return error.kind().to_string();
+    };
+
+    let path = parent_path(command, args, invalid);
+    let parent = path.iter().fold(command, |command, name| {
+        command.find_subcommand(name).unwrap_or(command)
+    });
+
+    let mut suggestions: Vec<String> = Vec::new();
+    if let Some(ContextValue::Strings(names)) = error.get(ContextKind::SuggestedSubcommand) {
+        for name in names {
+            if let Some(suggestion) = suggestion(parent, &path, name, replacements)
+                && !suggestions.contains(&suggestion)
+            {
+                suggestions.push(suggestion);
+            }
+        }
+    }
+
+    let mut message = format!("unrecognized subcommand '{invalid}'\n");
+
+    match suggestions.as_slice() {
+        [] => {}
+        [suggestion] => {
+            message.push_str(&format!(
+                "\n  tip: a similar subcommand exists: '{suggestion}'"
+            ));
+        }
+        suggestions => {
+            let suggestions: Vec<String> = suggestions
+                .iter()
+                .map(|suggestion| format!("'{suggestion}'"))
+                .collect();
+            message.push_str(&format!(
+                "\n  tip: some similar subcommands exist: {}",
+                suggestions.join(", ")
+            ));
+        }
+    }
+
+    if let Some(ContextValue::StyledStrs(tips)) = error.get(ContextKind::Suggested) {
+        for tip in tips {
+            message.push_str(&format!("\n  tip: {tip}"));
+        }
+    }
+
+    for tip in tips {
+        message.push_str(&format!("\n  tip: {tip}"));
+    }
+
+    if let Some(ContextValue::StyledStr(usage)) = error.get(ContextKind::Usage) {
+        message.push_str(&format!("\n\n{usage}"));
+    }
+
+    message.push_str("\n\nFor more information, try '--help'.");
+
+    message
+}
+
+/// Returns the name to suggest for a similar subcommand or alias, or `None` if it is hidden
+fn suggestion(
+    parent: &Command,
+    path: &[String],
+    name: &str,
+    replacements: Replacements,
+) -> Option<String> {
+    let subcommand = parent.find_subcommand(name)?;
+
+    if !subcommand.is_hide_set() {
+        return Some(subcommand.get_name().to_string());
+    }
+
+    let mut path: Vec<&str> = path.iter().map(String::as_str).collect();
+    path.push(subcommand.get_name());
+
+    replacements(&path).map(String::from)
+}
+
+/// Returns the names of the subcommands that lead to the command in which an argument was invalid
+fn parent_path(command: &Command, args: &[OsString], invalid: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = command;
+    let mut start = 1;
+
+    while let Some(index) = positional_index(current, args, start) {
+        let arg = args[index].to_str().unwrap_or_default();
+
+        if arg == invalid {
+            break;
+        }
+
+        if let Some(subcommand) = find_subcommand(current, arg) {
+            path.push(subcommand.get_name().to_string());
+            current = subcommand;
+        }
+
+        start = index + 1;
+    }
+
+    path
+}
+
+/// Find a subcommand by its name or alias, or by an unambiguous prefix
+///
+/// clap does not expose whether a command infers subcommands from their prefixes. Since an
+/// argument that clap did not accept is reported as the invalid subcommand, any prefix that is
+/// found before it must have been inferred.
+fn find_subcommand<'a>(command: &'a Command, name: &str) -> Option<&'a Command> {
+    if let Some(subcommand) = command.find_subcommand(name) {
+        return Some(subcommand);
+    }
+
+    let mut candidates = command.get_subcommands().filter(|subcommand| {
+        subcommand.get_name().starts_with(name)
+            || subcommand
+                .get_all_aliases()
+                .any(|alias| alias.starts_with(name))
+    });
+
+    match (candidates.next(), candidates.next()) {
+        (Some(subcommand), None) => Some(subcommand),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ArgPrompt;
+
+    fn command() -> Command {
+        Command::new("app")
+            .arg(Arg::new("profile").long("profile").global(true))
+            .subcommand(Command::new("deploy").visible_alias("d"))
+            .subcommand(Command::new("deplyo").hide(true))
+            .subcommand(Command::new("publish").hide(true))
+            .subcommand(
+                Command::new("db")
+                    .subcommand(Command::new("migrate"))
+                    .subcommand(Command::new("seed-data").hide(true)),
+            )
+    }
+
+    fn replacements(path: &[&str]) -> Option<&'static str> {
+        match path {
+            ["publish"] => Some("deploy"),
+            ["db", "seed-data"] => Some("seed"),
+            _ => None,
+        }
+    }
+
+    fn render(args: &[&str]) -> String {
+        let mut command = command();
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+
+        let error = command.try_get_matches_from_mut(&args).unwrap_err();
+        assert_eq!(ErrorKind::InvalidSubcommand, error.kind());
+
+        invalid_subcommand(&error, &command, &args, replacements, &[])
+    }
+
+    fn color(args: &[&str]) -> Option<ColorMode> {
+        let command = GlobalArgs::augment_command_without(command(), &["profile"]);
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+
+        color_mode(&command, &args)
+    }
+
+    #[test]
+    fn color_mode_reads_flag_before_parsing() {
+        assert_eq!(
+            Some(ColorMode::Always),
+            color(&["app", "--color", "always"])
+        );
+        assert_eq!(
+            Some(ColorMode::Never),
+            color(&["app", "deploy", "--color=never"])
+        );
+        assert_eq!(None, color(&["app", "deploy"]));
+    }
+
+    #[test]
+    fn color_mode_ignores_arguments_after_separator() {
+        assert_eq!(None, color(&["app", "deploy", "--", "--color", "always"]));
+    }
+
+    #[test]
+    fn color_mode_requires_global_flag() {
+        let args: Vec<OsString> = ["app", "--color", "always"].map(OsString::from).to_vec();
+
+        assert_eq!(None, color_mode(&command(), &args));
+    }
+
+    #[test]
+    fn invalid_subcommand_suggests_visible_commands() {
+        let message = render(&["app", "deplo"]);
+
+        assert!(
+            message.starts_with(
+                "unrecognized subcommand 'deplo'\n\n  tip: a similar subcommand exists: 'deploy'\n"
+            ),
+            "{message}"
+        );
+        assert!(message.ends_with("For more information, try '--help'."));
+    }
+
+    #[test]
+    fn invalid_subcommand_suggests_replacements_of_hidden_commands() {
+        let message = render(&["app", "publsh"]);
+
+        assert!(
+            message.contains("tip: a similar subcommand exists: 'deploy'"),
+            "{message}"
+        );
+        assert!(!message.contains("publish"), "{message}");
+    }
+
+    #[test]
+    fn invalid_subcommand_suggests_replacements_of_nested_commands() {
+        let message = render(&["app", "--profile", "prod", "db", "seed-dat"]);
+
+        assert!(
+            message.contains("tip: a similar subcommand exists: 'seed'"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn missing_plugin_suggests_similar_commands() {
+        let command = command().allow_external_subcommands(true);
+
+        let (message, code) = missing_plugin(command, "app", "deplo", replacements);
+
+        assert!(
+            message.starts_with(
+                "unrecognized subcommand 'deplo'\n\n  tip: a similar subcommand exists: 'deploy'\n  tip: no plugin 'app-deplo' was found on the PATH\n"
+            ),
+            "{message}"
+        );
+        assert_eq!(2, code);
+    }
+
+    #[test]
+    fn missing_plugin_without_suggestions() {
+        let command = command().allow_external_subcommands(true);
+
+        let (message, _) = missing_plugin(command, "app", "unknown", replacements);
+
+        assert!(
+            message.starts_with(
+                "unrecognized subcommand 'unknown'\n\n  tip: no plugin 'app-unknown' was found on the PATH\n"
+            ),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn invalid_subcommand_without_suggestions() {
+        let message = render(&["app", "unknown"]);
+
+        assert!(!message.contains("tip:"), "{message}");
+    }
+
+    #[test]
+    fn parent_path_skips_flags_and_values() {
+        let mut command = command();
+        command.build();
+        let args: Vec<OsString> = ["app", "--profile", "db", "db", "--profile=x", "seed"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+
+        assert_eq!(vec!["db"], parent_path(&command, &args, "seed"));
+    }
+
+    #[test]
+    fn command_path_stops_at_arguments() {
+        let mut command = command();
+        command.build();
+        let args: Vec<OsString> = ["app", "--profile", "prod", "db", "migrate", "extra"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+
+        assert_eq!(
+            (vec!["db".to_string(), "migrate".to_string()], 5),
+            command_path(&command, &args)
+        );
+    }
+
+    #[test]
+    fn relax_makes_arguments_of_subcommand_optional() {
+        let command = Command::new("app")
+            .subcommand(Command::new("init").arg(Arg::new("name").long("name").required(true)));
+
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["app", "init"])
+                .is_err()
+        );
+        assert!(
+            relax(command, &["init"], &["name"])
+                .try_get_matches_from(["app", "init"])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn collect_prompts_records_prompts_of_arguments() {
+        let prompts = collect_prompts(|| {
+            let _ = Command::new("init")
+                .arg(Arg::new("name").long("name").prompt("Name of the project"))
+                .arg(Arg::new("force").long("force"));
+        });
+
+        assert_eq!(
+            vec![("name".to_string(), "Name of the project".to_string())],
+            prompts
+        );
+        assert!(collect_prompts(|| {}).is_empty());
+    }
+
+    #[test]
+    fn find_subcommand_accepts_unambiguous_prefixes() {
+        let command = command();
+
+        assert_eq!(
+            Some("db"),
+            find_subcommand(&command, "db").map(Command::get_name)
+        );
+        assert_eq!(
+            Some("deploy"),
+            find_subcommand(&command, "d").map(Command::get_name)
+        );
+        assert_eq!(
+            Some("publish"),
+            find_subcommand(&command, "pub").map(Command::get_name)
+        );
+        assert!(find_subcommand(&command, "dep").is_none());
+    }
+}
@@ -0,0 +1,128 @@
+//! Colors and styles of Clawless applications
+//!
+//! A theme defines how headings, literals, placeholders, and messages are styled. It is applied to
+//! the help and errors that clap generates as well as to the messages that commands print through
+//! the `Output` of the context, which gives all parts of an application a consistent look.
+//!
+//! Applications configure their theme in one place, by passing a function that returns the theme
+//! to the `commands!` macro:
+//!
+//! ```rust,ignore
+//! // src/commands.rs
+//! use clawless::clap::builder::styling::{AnsiColor, Style};
+//! use clawless::theme::Theme;
+//!
+//! mod greet;
+//!
+//! pub fn theme() -> Theme {
+//!     Theme::builder()
+//!         .heading(AnsiColor::Magenta.on_default().bold())
+//!         .literal(AnsiColor::Cyan.on_default())
+//!         .build()
+//! }
+//!
+//! clawless::commands!(theme = theme);
+//! ```
+
+use std::fmt::Display;
+
+use clap::builder::Styles;
+use clap::builder::styling::Style;
+use getset::CopyGetters;
+use typed_builder::TypedBuilder;
+
+/// The colors and styles of an application
+///
+/// The default theme uses the same styles as clap. Use the builder to override individual styles.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, CopyGetters, TypedBuilder)]
+pub struct Theme {
+    /// The style of headings, e.g. `Usage:` and `Commands:` in the help
+    #[builder(default = *Styles::styled().get_header())]
+    #[getset(get_copy = "pub")]
+    heading: Style,
+
+    /// The style of literal text that users can type, e.g. commands and flags
+    #[builder(default = *Styles::styled().get_literal())]
+    #[getset(get_copy = "pub")]
+    literal: Style,
+
+    /// The style of placeholders for values, e.g. `<NAME>`
+    #[builder(default = *Styles::styled().get_placeholder())]
+    #[getset(get_copy = "pub")]
+    placeholder: Style,
+
+    /// The style of messages that report success
+    #[builder(default = *Styles::styled().get_valid())]
+    #[getset(get_copy = "pub")]
+    success: Style,
+
+    /// The style of warnings
+    #[builder(default = *Styles::styled().get_invalid())]
+    #[getset(get_copy = "pub")]
+    warning: Style,
+
+    /// The style of errors
+    #[builder(default = *Styles::styled().get_error())]
+    #[getset(get_copy = "pub")]
+    error: Style,
+}
+
+impl Theme {
+    /// Returns the styles that clap uses to render help and errors
+    pub fn styles(&self) -> Styles {
+        Styles::styled()
+            .header(self.heading)
+            .usage(self.heading)
+            .literal(self.literal)
+            .placeholder(self.placeholder)
+            .valid(self.success)
+            .invalid(self.warning)
+            .error(self.error)
+    }
+
+    /// Render text with a style of the theme
+    pub fn paint(style: Style, text: impl Display) -> String {
+        format!("{style}{text}{style:#}")
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::builder::styling::AnsiColor;
+
+    use super::*;
+
+    #[test]
+    fn default_matches_clap() {
+        let styles = Theme::default().styles();
+        let clap = Styles::styled();
+
+        assert_eq!(clap.get_header(), styles.get_header());
+        assert_eq!(clap.get_literal(), styles.get_literal());
+        assert_eq!(clap.get_error(), styles.get_error());
+    }
+
+    #[test]
+    fn styles_uses_theme() {
+        let heading = AnsiColor::Magenta.on_default().bold();
+        let theme = Theme::builder().heading(heading).build();
+
+        let styles = theme.styles();
+
+        assert_eq!(&heading, styles.get_header());
+        assert_eq!(&heading, styles.get_usage());
+    }
+
+    #[test]
+    fn paint_wraps_text_in_style() {
+        let style = AnsiColor::Green.on_default();
+
+        assert_eq!("\u{1b}[32mdone\u{1b}[0m", Theme::paint(style, "done"));
+    }
+}
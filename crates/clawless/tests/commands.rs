@@ -0,0 +1,171 @@
+//! Tests for the command tree that the macros of Clawless generate
+
+mod commands {
+    mod deploy {
+        use clawless::prelude::*;
+
+        #[derive(Debug, Args)]
+        pub struct DeployArgs {}
+
+        /// Deploy the application
+        #[command(alias = "ship")]
+        pub async fn deploy(_args: DeployArgs, _context: Context) -> CommandResult {
+            Ok(())
+        }
+    }
+
+    mod publish {
+        use clawless::prelude::*;
+
+        #[derive(Debug, Args)]
+        pub struct PublishArgs {}
+
+        /// Deploy the application
+        #[command(replaced_by = "deploy")]
+        pub async fn publish(_args: PublishArgs, _context: Context) -> CommandResult {
+            Ok(())
+        }
+    }
+
+    mod status {
+        use clawless::prelude::*;
+
+        #[derive(Debug, Args)]
+        pub struct StatusArgs {
+            /// Show the status of every service
+            #[arg(short, long)]
+            verbose: bool,
+        }
+
+        /// Show the status of the application
+        #[command]
+        pub async fn status(_args: StatusArgs, _context: Context) -> CommandResult {
+            Ok(())
+        }
+    }
+
+    mod init {
+        use clawless::prelude::*;
+
+        #[derive(Debug, Args)]
+        pub struct InitArgs {
+            /// The name of the project
+            #[arg(long, prompt = "Name of the project")]
+            name: String,
+
+            /// The template of the project
+            template: Option<String>,
+        }
+
+        /// Create a new project
+        #[command]
+        pub async fn init(_args: InitArgs, _context: Context) -> CommandResult {
+            Ok(())
+        }
+    }
+
+    mod db {
+        use clawless::prelude::*;
+
+        #[derive(Debug, Args)]
+        pub struct DbArgs {}
+
+        /// Manage the database
+        #[command(require_subcommand)]
+        pub async fn db(_args: DbArgs, _context: Context) -> CommandResult {
+            Ok(())
+        }
+
+        mod seed {
+            use clawless::prelude::*;
+
+            #[derive(Debug, Args)]
+            pub struct SeedArgs {}
+
+            #[command]
+            pub async fn seed(_args: SeedArgs, _context: Context) -> CommandResult {
+                Ok(())
+            }
+        }
+
+        mod seed_data {
+            use clawless::prelude::*;
+
+            #[derive(Debug, Args)]
+            pub struct SeedDataArgs {}
+
+            #[command(name = "seed-data", replaced_by = "seed")]
+            pub async fn seed_data(_args: SeedDataArgs, _context: Context) -> CommandResult {
+                Ok(())
+            }
+        }
+    }
+
+    clawless::commands!(infer_subcommands, without_global_flags = ["verbose"]);
+}
+
+#[test]
+fn infers_subcommands_from_prefixes() {
+    let matches = commands::clawless_init()
+        .try_get_matches_from(["app", "dep"])
+        .unwrap();
+
+    assert_eq!(Some("deploy"), matches.subcommand_name());
+}
+
+#[test]
+fn hides_deprecated_commands() {
+    let mut command = commands::clawless_init();
+    command.build();
+
+    assert!(command.find_subcommand("publish").unwrap().is_hide_set());
+    assert!(!command.find_subcommand("deploy").unwrap().is_hide_set());
+}
+
+#[test]
+fn finds_replacements_of_deprecated_commands() {
+    assert_eq!(Some("deploy"), commands::clawless_replacement(&["publish"]));
+    assert_eq!(
+        Some("seed"),
+        commands::clawless_replacement(&["db", "seed-data"])
+    );
+    assert_eq!(None, commands::clawless_replacement(&["deploy"]));
+    assert_eq!(None, commands::clawless_replacement(&["db", "seed"]));
+    assert_eq!(None, commands::clawless_replacement(&["unknown"]));
+}
+
+#[test]
+fn leaves_out_global_flags_that_are_used_by_commands() {
+    let matches = commands::clawless_init()
+        .try_get_matches_from(["app", "--quiet", "status", "-v"])
+        .unwrap();
+    let (_, status) = matches.subcommand().unwrap();
+
+    let global_args = clawless::context::GlobalArgs::from_matches(status);
+
+    assert!(status.get_flag("verbose"));
+    assert!(global_args.quiet);
+    assert!(!global_args.verbose);
+}
+
+#[test]
+fn finds_prompts_of_arguments() {
+    assert_eq!(
+        vec![("name".to_string(), "Name of the project".to_string())],
+        commands::clawless_prompts(&["init"])
+    );
+    assert!(commands::clawless_prompts(&["deploy"]).is_empty());
+    assert!(commands::clawless_prompts(&["db", "seed"]).is_empty());
+}
+
+#[test]
+fn requires_arguments_with_prompts() {
+    let error = commands::clawless_init()
+        .try_get_matches_from(["app", "init"])
+        .unwrap_err();
+
+    assert_eq!(
+        clawless::clap::error::ErrorKind::MissingRequiredArgument,
+        error.kind()
+    );
+}
@@ -1,5 +1,7 @@
 use clawless::prelude::*;
 
+use crate::settings::Settings;
+
 /// Arguments for the `greet` command
 ///
 /// This struct defines the command-line arguments for the `greet` command, which either greets a
@@ -14,9 +16,12 @@ pub struct GreetArgs {
 /// Greet the user
 ///
 /// This command prints a greeting message to the console using the provided name. If no name is
-/// given, the greeting default to "Hello, World!".
+/// given, the greeting default to "Hello, World!". The greeting can be changed in the
+/// configuration.
 #[command]
-pub async fn greet(args: GreetArgs, _context: Context) -> CommandResult {
-    println!("Hello, {}!", args.name);
+pub async fn greet(args: GreetArgs, context: Context) -> CommandResult {
+    let settings: Settings = context.config().deserialize()?;
+
+    println!("{}, {}!", settings.greeting, args.name);
     Ok(())
 }
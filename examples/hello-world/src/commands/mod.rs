@@ -1,3 +1,3 @@
 mod greet;
 
-clawless::commands!();
+clawless::commands!(config = crate::settings::Settings);
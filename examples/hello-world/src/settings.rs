@@ -0,0 +1,23 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of the `hello-world` application
+///
+/// Users can change the settings with the built-in `config` command, e.g. with
+/// `hello-world config set greeting Howdy`.
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Deserialize, Serialize, JsonSchema,
+)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// The greeting that is printed before the name
+    pub greeting: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            greeting: "Hello".into(),
+        }
+    }
+}
@@ -1,9 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
 use assert_cmd::cargo::*;
 use predicates::prelude::*;
+use tempfile::TempDir;
+
+/// An isolated environment for running the example
+///
+/// Every test gets its own configuration directory and working directory, so that the
+/// configuration and `.env` files on the machine that runs the tests cannot change the results.
+struct Sandbox {
+    config_home: TempDir,
+    project: TempDir,
+}
+
+impl Sandbox {
+    fn new() -> Self {
+        Self {
+            config_home: TempDir::new().unwrap(),
+            project: TempDir::new().unwrap(),
+        }
+    }
+
+    /// Returns a command for the example that runs in the sandbox
+    fn command(&self) -> Command {
+        let mut cmd = cargo_bin_cmd!("hello-world");
+
+        cmd.env("XDG_CONFIG_HOME", self.config_home.path())
+            .current_dir(self.project.path());
+
+        cmd
+    }
+
+    /// Returns the directory of the user's configuration file
+    fn config_dir(&self) -> PathBuf {
+        let config_dir = self.config_home.path().join("hello-world");
+        std::fs::create_dir_all(&config_dir).unwrap();
+
+        config_dir
+    }
+
+    /// Returns the working directory of the commands
+    fn project(&self) -> &Path {
+        self.project.path()
+    }
+}
 
 #[test]
 fn greets_default() {
-    let mut cmd = cargo_bin_cmd!("hello-world");
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
 
     cmd.arg("greet");
 
@@ -14,7 +60,8 @@ fn greets_default() {
 
 #[test]
 fn greets_name() {
-    let mut cmd = cargo_bin_cmd!("hello-world");
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
 
     cmd.arg("greet").arg("Otter");
 
@@ -22,3 +69,218 @@ fn greets_name() {
         .success()
         .stdout(predicate::str::contains("Hello, Otter!"));
 }
+
+#[test]
+fn colors_help_with_color_flag() {
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
+
+    cmd.args(["--color", "always", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}["));
+}
+
+#[test]
+fn does_not_color_help_with_color_never() {
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
+
+    cmd.env("CLICOLOR_FORCE", "1")
+        .args(["--color=never", "--help"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}[").not());
+}
+
+#[test]
+fn prints_manifest() {
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
+
+    cmd.arg("--clawless-manifest");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"format_version\": 1"))
+        .stdout(predicate::str::contains("\"name\": \"greet\""))
+        .stdout(predicate::str::contains("clawless-manifest").not());
+}
+
+#[test]
+fn hides_manifest_flag_from_help() {
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
+
+    cmd.arg("--help");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("clawless-manifest").not());
+}
+
+#[test]
+fn suggests_similar_subcommand() {
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
+
+    cmd.arg("gret");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "similar subcommand exists: 'greet'",
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn expands_user_defined_alias() {
+    let sandbox = Sandbox::new();
+    std::fs::write(
+        sandbox.config_dir().join("config.toml"),
+        "[alias]\nhi = \"greet Otter\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = sandbox.command();
+
+    cmd.arg("hi");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Hello, Otter!"));
+}
+
+#[cfg(unix)]
+#[test]
+fn ignores_invalid_user_defined_alias() {
+    let sandbox = Sandbox::new();
+    std::fs::write(
+        sandbox.config_dir().join("config.toml"),
+        "[alias]\nbroken = 1\nhi = \"greet Otter\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = sandbox.command();
+
+    cmd.arg("hi");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Hello, Otter!"))
+        .stderr(predicate::str::contains(
+            "warning: alias `broken` must be a string or an array of strings",
+        ));
+}
+
+#[test]
+fn reports_loaded_env_file() {
+    let sandbox = Sandbox::new();
+    let nested = sandbox.project().join("src");
+
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(sandbox.project().join(".env"), "HELLO_WORLD_TOKEN=secret\n").unwrap();
+
+    let mut cmd = sandbox.command();
+
+    cmd.current_dir(&nested).arg("--verbose").arg("greet");
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "Loaded environment variables from {}",
+            sandbox.project().join(".env").display()
+        )));
+}
+
+#[cfg(unix)]
+#[test]
+fn rejects_undefined_profile() {
+    let sandbox = Sandbox::new();
+    std::fs::write(
+        sandbox.config_dir().join("config.toml"),
+        "[profile.staging]\n\n[profile.prod]\n",
+    )
+    .unwrap();
+
+    let mut cmd = sandbox.command();
+
+    cmd.args(["--profile", "prdo", "greet"]);
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "profile `prdo` is not defined, available profiles are: prod, staging",
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn configures_greeting() {
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .command()
+        .args(["config", "set", "greeting", "Howdy"])
+        .assert()
+        .success();
+
+    sandbox
+        .command()
+        .arg("greet")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Howdy, World!"));
+
+    sandbox
+        .command()
+        .args(["config", "list", "--show-origin"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "user:{}\tgreeting = \"Howdy\"",
+            sandbox.config_dir().join("config.toml").display()
+        )));
+}
+
+#[test]
+fn rejects_invalid_configuration() {
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
+
+    cmd.args(["config", "set", "--project", "greeting", "42"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value for `greeting`"));
+}
+
+#[test]
+fn reports_line_of_invalid_configuration() {
+    let sandbox = Sandbox::new();
+    std::fs::write(
+        sandbox.project().join(".hello-world.toml"),
+        "# Greeting\ngreting = \"Howdy\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = sandbox.command();
+
+    cmd.arg("greet");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        ".hello-world.toml:2: unknown field `greting`",
+    ));
+}
+
+#[test]
+fn prints_configuration_schema() {
+    let sandbox = Sandbox::new();
+    let mut cmd = sandbox.command();
+
+    cmd.args(["config", "schema"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"greeting\""))
+        .stdout(predicate::str::contains("\"default_profile\""));
+}